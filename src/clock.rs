@@ -0,0 +1,208 @@
+use std::{
+    fmt,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use bma_ts::Timestamp;
+use chrono::TimeDelta;
+
+/// Time source
+///
+/// Time-dependent components read the time from a clock instead of the system one, so tests
+/// and simulations can use a [`ManualClock`] and advance time instantly and deterministically.
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// Monotonic time (timers, timeouts)
+    fn now(&self) -> Instant;
+    /// Wall clock time (time tags)
+    fn timestamp(&self) -> Timestamp;
+}
+
+/// System clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+    fn timestamp(&self) -> Timestamp {
+        Timestamp::now()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ManualTime {
+    instant: Instant,
+    timestamp: Timestamp,
+}
+
+/// Manually advanced (virtual) clock
+///
+/// The time stands still until advanced. The monotonic time starts at the creation instant,
+/// the wall clock time at the given timestamp.
+#[derive(Debug)]
+pub struct ManualClock {
+    time: Mutex<ManualTime>,
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new(Timestamp::now())
+    }
+}
+
+impl ManualClock {
+    /// Create a new clock starting at the wall clock time
+    pub fn new(timestamp: Timestamp) -> Self {
+        Self {
+            time: Mutex::new(ManualTime {
+                instant: Instant::now(),
+                timestamp,
+            }),
+        }
+    }
+    /// Advance both the monotonic and the wall clock time
+    pub fn advance(&self, duration: Duration) {
+        let mut time = self.time.lock().unwrap_or_else(PoisonError::into_inner);
+        time.instant += duration;
+        time.timestamp += duration;
+    }
+    /// Set the wall clock time (e.g. clock synchronization), the monotonic time is not changed
+    pub fn set_timestamp(&self, timestamp: Timestamp) {
+        self.time
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .timestamp = timestamp;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.time
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .instant
+    }
+    fn timestamp(&self) -> Timestamp {
+        self.time
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .timestamp
+    }
+}
+
+/// Adjustable clock: the wall clock time of the base clock shifted by an offset
+///
+/// Used to apply clock synchronization without changing the system time. The monotonic time
+/// is the one of the base clock.
+#[derive(Debug)]
+pub struct VirtualClock {
+    base: Arc<dyn Clock>,
+    offset: Mutex<TimeDelta>,
+}
+
+impl Default for VirtualClock {
+    fn default() -> Self {
+        Self::new(Arc::new(SystemClock))
+    }
+}
+
+impl VirtualClock {
+    /// Create a new clock without offset
+    pub fn new(base: Arc<dyn Clock>) -> Self {
+        Self {
+            base,
+            offset: Mutex::new(TimeDelta::zero()),
+        }
+    }
+    /// Offset from the base clock
+    pub fn offset(&self) -> TimeDelta {
+        *self.offset.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+    /// Set the offset from the base clock
+    pub fn set_offset(&self, offset: TimeDelta) {
+        *self.offset.lock().unwrap_or_else(PoisonError::into_inner) = offset;
+    }
+    /// Adjust the time (the adjustment is added to the offset)
+    pub fn adjust(&self, adjustment: TimeDelta) {
+        let mut offset = self.offset.lock().unwrap_or_else(PoisonError::into_inner);
+        *offset = offset.checked_add(&adjustment).unwrap_or(*offset);
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Instant {
+        self.base.now()
+    }
+    fn timestamp(&self) -> Timestamp {
+        shift(self.base.timestamp(), self.offset())
+    }
+}
+
+/// Signed difference `a - b`
+pub(crate) fn difference(a: Timestamp, b: Timestamp) -> TimeDelta {
+    if a >= b {
+        TimeDelta::from_std(a - b).unwrap_or(TimeDelta::MAX)
+    } else {
+        -TimeDelta::from_std(b - a).unwrap_or(TimeDelta::MAX)
+    }
+}
+
+/// Shifts a timestamp, saturates at the epoch
+pub(crate) fn shift(t: Timestamp, delta: TimeDelta) -> Timestamp {
+    let Ok(abs) = delta.abs().to_std() else {
+        return t;
+    };
+    if delta >= TimeDelta::zero() {
+        t + abs
+    } else if t.as_duration() > abs {
+        t - abs
+    } else {
+        Timestamp::from_nanos(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use bma_ts::Timestamp;
+    use chrono::TimeDelta;
+
+    use super::{Clock, ManualClock, VirtualClock, difference};
+
+    #[test]
+    fn manual_clock() {
+        let clock = ManualClock::new(Timestamp::from_secs(1_700_000_000));
+        let start = clock.now();
+        assert_eq!(clock.now(), start);
+        clock.advance(Duration::from_secs(90));
+        assert_eq!(clock.now() - start, Duration::from_secs(90));
+        assert_eq!(clock.timestamp(), Timestamp::from_secs(1_700_000_090));
+        clock.set_timestamp(Timestamp::from_secs(1_600_000_000));
+        assert_eq!(clock.timestamp(), Timestamp::from_secs(1_600_000_000));
+        assert_eq!(clock.now() - start, Duration::from_secs(90));
+    }
+
+    #[test]
+    fn virtual_clock() {
+        let base = Arc::new(ManualClock::new(Timestamp::from_secs(1_700_000_000)));
+        let clock = VirtualClock::new(base.clone());
+        clock.adjust(TimeDelta::milliseconds(-1_500));
+        clock.adjust(TimeDelta::seconds(10));
+        assert_eq!(clock.offset(), TimeDelta::milliseconds(8_500));
+        assert_eq!(clock.timestamp(), Timestamp::from_millis(1_700_000_008_500));
+        base.advance(Duration::from_secs(1));
+        assert_eq!(
+            difference(clock.timestamp(), base.timestamp()),
+            TimeDelta::milliseconds(8_500)
+        );
+        clock.set_offset(TimeDelta::seconds(-1));
+        assert_eq!(
+            difference(base.timestamp(), clock.timestamp()),
+            TimeDelta::seconds(1)
+        );
+        assert_eq!(clock.now(), base.now());
+    }
+}
@@ -0,0 +1,283 @@
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use bma_ts::Timestamp;
+
+use crate::{
+    Error,
+    clock::{Clock, SystemClock},
+    telegram104::{CA_BROADCAST, Telegram104_I},
+    types::{
+        COT,
+        datatype::{C_CS_NA_1, DataType},
+        time::{CP16Time2a, CP56Time2a, TimePolicy},
+    },
+};
+
+const DEFAULT_CONFIRM_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Clock synchronization events
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ClockSyncEvent {
+    /// The station has confirmed the synchronization
+    Confirmed(u16),
+    /// The station has rejected the synchronization (negative ActCon)
+    Rejected(u16),
+    /// No confirmation has been received within the timeout
+    Timeout(u16),
+}
+
+/// Clock synchronization master output
+#[derive(Debug, Clone)]
+pub enum ClockSyncOutput {
+    /// A telegram to be sent
+    Send(Telegram104_I),
+    /// An event
+    Event(ClockSyncEvent),
+}
+
+/// Periodic clock synchronization of IEC 60870-5-104 stations (controlling station)
+///
+/// C_CS_NA_1 activations are sent to the configured common addresses (or the broadcast one if
+/// none configured) every interval. A station is not synchronized again until its ActCon is
+/// received or the confirmation timeout expires. For the broadcast address, the first ActCon
+/// completes the synchronization, all of them are reported.
+///
+/// The master is transport-agnostic: [`ClockSyncMaster::poll`] returns the telegrams to be sent
+/// (call it at [`ClockSyncMaster::next_due`]), received replies are given to
+/// [`ClockSyncMaster::handle`].
+#[derive(Debug)]
+pub struct ClockSyncMaster {
+    interval: Duration,
+    confirm_timeout: Duration,
+    targets: Vec<u16>,
+    clock: Arc<dyn Clock>,
+    policy: TimePolicy,
+    // None: due immediately
+    next: Option<Instant>,
+    // confirmation deadlines
+    pending: BTreeMap<u16, Instant>,
+}
+
+impl ClockSyncMaster {
+    /// Create a new master
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            confirm_timeout: DEFAULT_CONFIRM_TIMEOUT,
+            targets: Vec::new(),
+            clock: Arc::new(SystemClock),
+            policy: TimePolicy::default(),
+            next: None,
+            pending: BTreeMap::new(),
+        }
+    }
+    /// Synchronize the common address (instead of the broadcast one)
+    pub fn with_ca(mut self, ca: u16) -> Self {
+        if !self.targets.contains(&ca) {
+            self.targets.push(ca);
+        }
+        self
+    }
+    /// Set the confirmation timeout (default: 15 seconds)
+    pub fn with_confirm_timeout(mut self, confirm_timeout: Duration) -> Self {
+        self.confirm_timeout = confirm_timeout;
+        self
+    }
+    /// Set the clock (default: system clock)
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    /// Set the time zone policy of the sent time (default: UTC)
+    pub fn with_policy(mut self, policy: TimePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+    /// Is a synchronization of the common address waiting for confirmation
+    pub fn is_pending(&self, ca: u16) -> bool {
+        self.pending.contains_key(&ca)
+    }
+    /// Synchronize at the next poll
+    pub fn synchronize(&mut self) {
+        self.next = None;
+    }
+    /// The time the next poll is due
+    pub fn next_due(&self) -> Instant {
+        let next = self.next.unwrap_or_else(|| self.clock.now());
+        self.pending.values().fold(next, |due, d| due.min(*d))
+    }
+    /// Report expired confirmations and send the synchronization if due
+    pub fn poll(&mut self) -> Result<Vec<ClockSyncOutput>, Error> {
+        let now = self.clock.now();
+        let mut output = Vec::new();
+        self.pending.retain(|ca, deadline| {
+            if *deadline > now {
+                return true;
+            }
+            output.push(ClockSyncOutput::Event(ClockSyncEvent::Timeout(*ca)));
+            false
+        });
+        if self.next.is_some_and(|next| next > now) {
+            return Ok(output);
+        }
+        self.next = Some(now + self.interval);
+        let time = CP56Time2a::now(&*self.clock, &self.policy)?;
+        let targets = if self.targets.is_empty() {
+            vec![CA_BROADCAST]
+        } else {
+            self.targets.clone()
+        };
+        for ca in targets {
+            if self.pending.contains_key(&ca) {
+                continue;
+            }
+            let mut telegram = Telegram104_I::new(DataType::C_CS_NA_1, COT::Act, ca);
+            telegram.append_iou(0, C_CS_NA_1 { time: time.clone() });
+            self.pending.insert(ca, now + self.confirm_timeout);
+            output.push(ClockSyncOutput::Send(telegram));
+        }
+        Ok(output)
+    }
+    /// Handle a received telegram, returns an event for clock synchronization confirmations
+    pub fn handle(&mut self, telegram: &Telegram104_I) -> Option<ClockSyncEvent> {
+        if telegram.data_type() != DataType::C_CS_NA_1 || telegram.cot() != COT::ActCon {
+            return None;
+        }
+        let ca = telegram.adsu();
+        if self.pending.remove(&ca).is_none() && self.pending.remove(&CA_BROADCAST).is_none() {
+            // further confirmations of a broadcast synchronization
+            if !(self.targets.is_empty() && self.next.is_some()) {
+                return None;
+            }
+        }
+        Some(if telegram.is_negative() {
+            ClockSyncEvent::Rejected(ca)
+        } else {
+            ClockSyncEvent::Confirmed(ca)
+        })
+    }
+}
+
+/// Sending delay time (SDT) of the delay acquisition procedure (C_CD_NA_1): the seconds and
+/// milliseconds of the time within the minute
+pub fn sending_delay_time(time: Timestamp) -> CP16Time2a {
+    CP16Time2a {
+        ms: u16::try_from(time.as_millis() % 60_000).unwrap_or_default(),
+    }
+}
+
+/// Transmission delay of the delay acquisition procedure: half of the time between sending the
+/// activation and receiving the confirmation, the station adds its processing time to the
+/// returned SDT
+pub fn transmission_delay(sdt: CP16Time2a, received: Timestamp) -> Duration {
+    let received = sending_delay_time(received).ms;
+    let round_trip = (u32::from(received) + 60_000 - u32::from(sdt.ms.min(59_999))) % 60_000;
+    Duration::from_millis(u64::from(round_trip / 2))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use bma_ts::Timestamp;
+
+    use super::{
+        ClockSyncEvent, ClockSyncMaster, ClockSyncOutput, sending_delay_time, transmission_delay,
+    };
+    use crate::{
+        clock::{Clock, ManualClock},
+        telegram104::Telegram104_I,
+        types::{
+            COT,
+            datatype::{C_CS_NA_1, DataType},
+            time::{CP16Time2a, TimePolicy},
+        },
+    };
+
+    fn sent(output: &[ClockSyncOutput]) -> Vec<Telegram104_I> {
+        output
+            .iter()
+            .filter_map(|o| match o {
+                ClockSyncOutput::Send(telegram) => Some(telegram.clone()),
+                ClockSyncOutput::Event(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn clock_sync_master() {
+        let clock = Arc::new(ManualClock::new(Timestamp::from_secs(1_700_000_000)));
+        let mut master = ClockSyncMaster::new(Duration::from_secs(60))
+            .with_ca(1)
+            .with_ca(2)
+            .with_confirm_timeout(Duration::from_secs(5))
+            .with_clock(clock.clone());
+        let telegrams = sent(&master.poll().unwrap());
+        assert_eq!(telegrams.len(), 2);
+        assert_eq!(
+            (telegrams[0].data_type(), telegrams[0].cot()),
+            (DataType::C_CS_NA_1, COT::Act)
+        );
+        let time = C_CS_NA_1::from(telegrams[1].iou()[0].value()).time;
+        assert_eq!(
+            time.to_timestamp(&TimePolicy::Utc).unwrap(),
+            Timestamp::from_secs(1_700_000_000)
+        );
+        assert!(master.poll().unwrap().is_empty());
+        assert_eq!(master.next_due(), clock.now() + Duration::from_secs(5));
+        assert_eq!(
+            master.handle(&telegrams[0].clone().with_cot(COT::ActCon)),
+            Some(ClockSyncEvent::Confirmed(1))
+        );
+        // not pending
+        assert_eq!(
+            master.handle(&telegrams[0].clone().with_cot(COT::ActCon)),
+            None
+        );
+        clock.advance(Duration::from_secs(5));
+        let output = master.poll().unwrap();
+        assert!(matches!(
+            output[..],
+            [ClockSyncOutput::Event(ClockSyncEvent::Timeout(2))]
+        ));
+        clock.advance(Duration::from_secs(55));
+        let telegrams = sent(&master.poll().unwrap());
+        assert_eq!(telegrams.len(), 2);
+        assert_eq!(
+            master.handle(&telegrams[1].clone().with_cot(COT::ActCon).with_negative()),
+            Some(ClockSyncEvent::Rejected(2))
+        );
+    }
+
+    #[test]
+    fn clock_sync_master_broadcast() {
+        let clock = Arc::new(ManualClock::default());
+        let mut master = ClockSyncMaster::new(Duration::from_secs(60)).with_clock(clock);
+        let telegrams = sent(&master.poll().unwrap());
+        assert_eq!(telegrams.len(), 1);
+        assert_eq!(telegrams[0].adsu(), 0xFFFF);
+        for ca in [1, 2] {
+            let reply = telegrams[0].clone().with_cot(COT::ActCon).with_adsu(ca);
+            assert_eq!(master.handle(&reply), Some(ClockSyncEvent::Confirmed(ca)));
+        }
+        assert!(!master.is_pending(0xFFFF));
+    }
+
+    #[test]
+    fn delay_acquisition() {
+        let sent = Timestamp::from_millis(1_700_000_039_900);
+        let sdt = sending_delay_time(sent);
+        assert_eq!(sdt, CP16Time2a { ms: 59_900 });
+        // the station has added 100 ms of processing time, the minute rollover
+        let returned = CP16Time2a { ms: 0 };
+        let received = sent + Duration::from_millis(500);
+        assert_eq!(
+            transmission_delay(returned, received),
+            Duration::from_millis(200)
+        );
+    }
+}
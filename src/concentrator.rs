@@ -0,0 +1,997 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    Error,
+    telegram104::{CA_BROADCAST, IOA_MAX, MAX_IOU_LEN, Telegram104_I},
+    types::{
+        COT, DataBuffer, Iou,
+        datatype::{
+            C_IC_NA_1, DataType, M_BO_NA_1, M_DP_NA_1, M_ME_NA_1, M_ME_NB_1, M_ME_NC_1, M_PS_NA_1,
+            M_SP_NA_1, M_ST_NA_1, QOI,
+        },
+        point::HasQuality,
+    },
+};
+
+/// Maps a downstream common address to an upstream one
+#[derive(Debug, Clone)]
+pub struct CaMapping {
+    station: usize,
+    ca: u16,
+    up_ca: u16,
+    ioa_offset: u32,
+}
+
+impl CaMapping {
+    /// Create a new mapping
+    ///
+    /// `station` is the downstream station id returned by [`Concentrator::add_station`]
+    pub fn new(station: usize, ca: u16, up_ca: u16) -> Self {
+        Self {
+            station,
+            ca,
+            up_ca,
+            ioa_offset: 0,
+        }
+    }
+    /// Set the offset added to downstream information object addresses (upstream IOA =
+    /// downstream IOA + offset), IOA 0 is not shifted
+    pub fn with_ioa_offset(mut self, ioa_offset: u32) -> Self {
+        self.ioa_offset = ioa_offset;
+        self
+    }
+    /// Downstream station id
+    pub fn station(&self) -> usize {
+        self.station
+    }
+    /// Downstream common address
+    pub fn ca(&self) -> u16 {
+        self.ca
+    }
+    /// Upstream common address
+    pub fn up_ca(&self) -> u16 {
+        self.up_ca
+    }
+    /// Information object address offset
+    pub fn ioa_offset(&self) -> u32 {
+        self.ioa_offset
+    }
+}
+
+/// Maps a single downstream information object to an upstream one (overrides [`CaMapping`])
+#[derive(Debug, Clone)]
+pub struct PointMapping {
+    station: usize,
+    ca: u16,
+    ioa: u32,
+    up_ca: u16,
+    up_ioa: u32,
+}
+
+impl PointMapping {
+    /// Create a new mapping
+    pub fn new(station: usize, ca: u16, ioa: u32, up_ca: u16, up_ioa: u32) -> Self {
+        Self {
+            station,
+            ca,
+            ioa,
+            up_ca,
+            up_ioa,
+        }
+    }
+    /// Downstream station id
+    pub fn station(&self) -> usize {
+        self.station
+    }
+    /// Downstream common address
+    pub fn ca(&self) -> u16 {
+        self.ca
+    }
+    /// Downstream information object address
+    pub fn ioa(&self) -> u32 {
+        self.ioa
+    }
+    /// Upstream common address
+    pub fn up_ca(&self) -> u16 {
+        self.up_ca
+    }
+    /// Upstream information object address
+    pub fn up_ioa(&self) -> u32 {
+        self.up_ioa
+    }
+}
+
+/// Concentrator output
+#[derive(Debug, Clone)]
+pub enum ConcentratorOutput {
+    /// A telegram to be sent to a downstream station
+    Station {
+        /// Station id
+        station: usize,
+        /// The telegram
+        telegram: Telegram104_I,
+    },
+    /// A telegram to be sent to a single upstream master
+    Master {
+        /// Master id, as given to [`Concentrator::handle_upstream`]
+        master: usize,
+        /// The telegram
+        telegram: Telegram104_I,
+    },
+    /// A telegram to be sent to all connected upstream masters
+    Broadcast(Telegram104_I),
+}
+
+/// A cached point of the process image
+#[derive(Debug, Clone)]
+pub struct CachedPoint {
+    station: usize,
+    data_type: DataType,
+    value: DataBuffer,
+}
+
+impl CachedPoint {
+    /// Downstream station id
+    pub fn station(&self) -> usize {
+        self.station
+    }
+    /// Data type (always the type without time tag)
+    pub fn data_type(&self) -> DataType {
+        self.data_type
+    }
+    /// Value
+    pub fn value(&self) -> DataBuffer {
+        self.value
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PendingCommand {
+    master: usize,
+    station: usize,
+    ca: u16,
+    ioa: u32,
+    data_type: DataType,
+    // the command as received from the master
+    command: Telegram104_I,
+}
+
+/// IEC 60870-5-104 data concentrator
+///
+/// Aggregates downstream outstations into a single upstream view. The concentrator is
+/// transport-agnostic: I-frames received from the downstream stations are given to
+/// [`Concentrator::handle_downstream`], I-frames received from upstream masters to
+/// [`Concentrator::handle_upstream`], connection state changes of the downstream stations to
+/// [`Concentrator::station_connected`] and [`Concentrator::station_disconnected`]. All return
+/// telegrams to be sent.
+///
+/// Monitor information is kept in a process image, which is used to answer upstream general
+/// interrogations. The points of a lost downstream station are marked not topical. Commands are
+/// forwarded to the downstream stations, their confirmations and terminations are passed to the
+/// originating master.
+#[derive(Debug, Clone, Default)]
+pub struct Concentrator {
+    stations: Vec<bool>,
+    ca_mappings: Vec<CaMapping>,
+    point_mappings: Vec<PointMapping>,
+    image: BTreeMap<(u16, u32), CachedPoint>,
+    pending: Vec<PendingCommand>,
+}
+
+impl Concentrator {
+    /// Create a new concentrator
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Add a downstream station (initially disconnected), returns the station id
+    pub fn add_station(&mut self) -> usize {
+        self.stations.push(false);
+        self.stations.len() - 1
+    }
+    /// Add a common address mapping
+    pub fn add_ca_mapping(&mut self, mapping: CaMapping) -> Result<(), Error> {
+        self.check_station(mapping.station)?;
+        if mapping.up_ca == CA_BROADCAST || mapping.ca == CA_BROADCAST {
+            return Err(Error::invalid_config(
+                "the broadcast common address can not be mapped",
+            ));
+        }
+        if mapping.ioa_offset > IOA_MAX {
+            return Err(Error::invalid_config("IOA offset out of range"));
+        }
+        if self.ca_mappings.iter().any(|m| m.up_ca == mapping.up_ca) {
+            return Err(Error::invalid_config(format!(
+                "duplicate upstream common address: {}",
+                mapping.up_ca
+            )));
+        }
+        if self
+            .ca_mappings
+            .iter()
+            .any(|m| m.station == mapping.station && m.ca == mapping.ca)
+        {
+            return Err(Error::invalid_config(format!(
+                "duplicate downstream common address: {}",
+                mapping.ca
+            )));
+        }
+        self.ca_mappings.push(mapping);
+        Ok(())
+    }
+    /// Add an information object mapping
+    pub fn add_point_mapping(&mut self, mapping: PointMapping) -> Result<(), Error> {
+        self.check_station(mapping.station)?;
+        if mapping.up_ca == CA_BROADCAST || mapping.ca == CA_BROADCAST {
+            return Err(Error::invalid_config(
+                "the broadcast common address can not be mapped",
+            ));
+        }
+        if mapping.ioa == 0 || mapping.up_ioa == 0 || mapping.up_ioa > IOA_MAX {
+            return Err(Error::invalid_config(format!(
+                "invalid IOA mapping: {} -> {}",
+                mapping.ioa, mapping.up_ioa
+            )));
+        }
+        if self
+            .point_mappings
+            .iter()
+            .any(|m| m.up_ca == mapping.up_ca && m.up_ioa == mapping.up_ioa)
+        {
+            return Err(Error::invalid_config(format!(
+                "duplicate upstream point: {}/{}",
+                mapping.up_ca, mapping.up_ioa
+            )));
+        }
+        if self
+            .point_mappings
+            .iter()
+            .any(|m| m.station == mapping.station && m.ca == mapping.ca && m.ioa == mapping.ioa)
+        {
+            return Err(Error::invalid_config(format!(
+                "duplicate downstream point: {}/{}",
+                mapping.ca, mapping.ioa
+            )));
+        }
+        self.point_mappings.push(mapping);
+        Ok(())
+    }
+    /// Is the downstream station connected
+    pub fn is_connected(&self, station: usize) -> bool {
+        self.stations.get(station).copied().unwrap_or_default()
+    }
+    /// A point of the process image by its upstream address
+    pub fn point(&self, ca: u16, ioa: u32) -> Option<&CachedPoint> {
+        self.image.get(&(ca, ioa))
+    }
+    /// Forget the pending commands of a master (e.g. when its connection is closed)
+    pub fn remove_master(&mut self, master: usize) {
+        self.pending.retain(|p| p.master != master);
+    }
+    /// Must be called when the downstream station connection is established (data transfer
+    /// started), returns general interrogation commands to refresh the process image
+    pub fn station_connected(&mut self, station: usize) -> Result<Vec<ConcentratorOutput>, Error> {
+        self.check_station(station)?;
+        self.stations[station] = true;
+        let mut cas: Vec<u16> = self
+            .ca_mappings
+            .iter()
+            .filter(|m| m.station == station)
+            .map(|m| m.ca)
+            .chain(
+                self.point_mappings
+                    .iter()
+                    .filter(|m| m.station == station)
+                    .map(|m| m.ca),
+            )
+            .collect();
+        cas.sort_unstable();
+        cas.dedup();
+        Ok(cas
+            .into_iter()
+            .map(|ca| {
+                let mut telegram = Telegram104_I::new(DataType::C_IC_NA_1, COT::Act, ca);
+                telegram.append_iou(0, C_IC_NA_1 { qoi: QOI::Inrogen });
+                ConcentratorOutput::Station { station, telegram }
+            })
+            .collect())
+    }
+    /// Must be called when the downstream station connection is lost
+    ///
+    /// The cached points of the station are marked not topical and published spontaneously,
+    /// pending commands are confirmed negatively.
+    pub fn station_disconnected(
+        &mut self,
+        station: usize,
+    ) -> Result<Vec<ConcentratorOutput>, Error> {
+        self.check_station(station)?;
+        self.stations[station] = false;
+        let mut output = Vec::new();
+        let (failed, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|p| p.station == station);
+        self.pending = pending;
+        for p in failed {
+            output.push(ConcentratorOutput::Master {
+                master: p.master,
+                telegram: p
+                    .command
+                    .clone()
+                    .with_cot(p.command.cot().confirmation())
+                    .with_negative(),
+            });
+        }
+        let mut changed = Vec::new();
+        for (&(ca, ioa), point) in &mut self.image {
+            if point.station == station
+                && let Some(value) = set_not_topical(point.data_type, point.value)
+                && value != point.value
+            {
+                point.value = value;
+                changed.push((ca, ioa, point.data_type, value));
+            }
+        }
+        output.extend(
+            pack(changed, COT::Spontan)
+                .into_iter()
+                .map(ConcentratorOutput::Broadcast),
+        );
+        Ok(output)
+    }
+    /// Process an I-frame received from a downstream station
+    ///
+    /// Monitor information updates the process image and is published to all masters, except
+    /// interrogation responses, which are published only if the cached value has been changed.
+    /// Information objects without a mapping are dropped.
+    pub fn handle_downstream(
+        &mut self,
+        station: usize,
+        telegram: &Telegram104_I,
+    ) -> Result<Vec<ConcentratorOutput>, Error> {
+        self.check_station(station)?;
+        let data_type = telegram.data_type();
+        let cot = telegram.cot();
+        if cot.is_command_response() {
+            let ioa = telegram.iou().first().map_or(0, Iou::address);
+            if let Some(pos) = self.pending.iter().position(|p| {
+                p.station == station
+                    && p.ca == telegram.adsu()
+                    && p.data_type == data_type
+                    && p.ioa == ioa
+            }) {
+                let pending = &self.pending[pos];
+                let master = pending.master;
+                let mut reply = pending.command.clone().with_cot(cot);
+                if telegram.is_negative() {
+                    reply = reply.with_negative();
+                }
+                if cot != COT::ActCon || telegram.is_negative() || !data_type.has_termination() {
+                    self.pending.remove(pos);
+                }
+                return Ok(vec![ConcentratorOutput::Master {
+                    master,
+                    telegram: reply,
+                }]);
+            }
+            // confirmations of commands sent by the concentrator itself
+            return Ok(Vec::new());
+        }
+        // the time tag is always at the end of the information element, so the value and
+        // quality bytes of the timed types are the same
+        let cacheable = data_type.untimed().or(Some(data_type)).filter(|t| {
+            matches!(
+                t,
+                DataType::M_SP_NA_1
+                    | DataType::M_DP_NA_1
+                    | DataType::M_ST_NA_1
+                    | DataType::M_BO_NA_1
+                    | DataType::M_ME_NA_1
+                    | DataType::M_ME_NB_1
+                    | DataType::M_ME_NC_1
+                    | DataType::M_ME_ND_1
+                    | DataType::M_PS_NA_1
+            )
+        });
+        let interrogation = (COT::Inrogen..=COT::Inro16).contains(&cot);
+        let mut published = Vec::new();
+        let mut iou_up = Vec::new();
+        for iou in telegram.iou() {
+            let Some((up_ca, up_ioa)) = self.map_up(station, telegram.adsu(), iou.address()) else {
+                continue;
+            };
+            let mut changed = true;
+            if let Some(untimed_type) = cacheable {
+                let mut value = iou.value();
+                value[untimed_type.size()..].fill(0);
+                let point = CachedPoint {
+                    station,
+                    data_type: untimed_type,
+                    value,
+                };
+                if let Some(prev) = self.image.insert((up_ca, up_ioa), point) {
+                    changed = prev.data_type != untimed_type || prev.value != value;
+                }
+            }
+            if interrogation {
+                if changed && let Some(untimed_type) = cacheable {
+                    let mut value = iou.value();
+                    value[untimed_type.size()..].fill(0);
+                    published.push((up_ca, up_ioa, untimed_type, value));
+                }
+            } else {
+                iou_up.push((up_ca, Iou::new(up_ioa, iou.value())));
+            }
+        }
+        let mut output: Vec<ConcentratorOutput> = pack(published, COT::Spontan)
+            .into_iter()
+            .map(ConcentratorOutput::Broadcast)
+            .collect();
+        // a downstream ASDU may be split to several upstream common addresses
+        let mut cas: Vec<u16> = iou_up.iter().map(|(ca, _)| *ca).collect();
+        cas.sort_unstable();
+        cas.dedup();
+        for ca in cas {
+            let mut out = Telegram104_I::new(data_type, cot, ca)
+                .with_originator(telegram.originator())
+                .with_iou(
+                    iou_up
+                        .iter()
+                        .filter(|(c, _)| *c == ca)
+                        .map(|(_, iou)| iou.clone())
+                        .collect(),
+                );
+            if telegram.is_test() {
+                out = out.with_test();
+            }
+            if telegram.is_negative() {
+                out = out.with_negative();
+            }
+            output.push(ConcentratorOutput::Broadcast(out));
+        }
+        Ok(output)
+    }
+    /// Process an I-frame received from an upstream master
+    ///
+    /// General interrogations are answered from the process image, other commands are forwarded
+    /// to the downstream station. Commands for unknown addresses are mirrored back with COT 46/47,
+    /// commands for disconnected stations are confirmed negatively.
+    pub fn handle_upstream(
+        &mut self,
+        master: usize,
+        telegram: &Telegram104_I,
+    ) -> Result<Vec<ConcentratorOutput>, Error> {
+        let reply = |cot: COT| ConcentratorOutput::Master {
+            master,
+            telegram: telegram.clone().with_cot(cot).with_negative(),
+        };
+        if telegram.data_type() == DataType::C_IC_NA_1 && telegram.cot() == COT::Act {
+            return Ok(self.interrogation(master, telegram));
+        }
+        let Some(object) = telegram.iou().first() else {
+            return Err(Error::invalid_data("no information objects"));
+        };
+        if telegram.iou().len() > 1 {
+            return Err(Error::invalid_data(
+                "commands with multiple information objects are not supported",
+            ));
+        }
+        if !self.has_up_ca(telegram.adsu()) {
+            return Ok(vec![reply(COT::UnknownAsduAddress)]);
+        }
+        let Some((station, ca, ioa)) = self.map_down(telegram.adsu(), object.address()) else {
+            return Ok(vec![reply(COT::UnknownObjectAddress)]);
+        };
+        if !self.stations[station] {
+            return Ok(vec![reply(telegram.cot().confirmation())]);
+        }
+        let mut out = Telegram104_I::new(telegram.data_type(), telegram.cot(), ca)
+            .with_originator(telegram.originator())
+            .with_iou(vec![Iou::new(ioa, object.value())]);
+        if telegram.is_test() {
+            out = out.with_test();
+        }
+        if matches!(telegram.cot(), COT::Act | COT::Deact) {
+            self.pending.retain(|p| {
+                p.station != station || p.ca != ca || p.ioa != ioa || p.data_type != out.data_type()
+            });
+            self.pending.push(PendingCommand {
+                master,
+                station,
+                ca,
+                ioa,
+                data_type: telegram.data_type(),
+                command: telegram.clone(),
+            });
+        }
+        Ok(vec![ConcentratorOutput::Station {
+            station,
+            telegram: out,
+        }])
+    }
+    fn interrogation(&self, master: usize, telegram: &Telegram104_I) -> Vec<ConcentratorOutput> {
+        let to_master = |telegram: Telegram104_I| ConcentratorOutput::Master { master, telegram };
+        let qoi = telegram
+            .iou()
+            .first()
+            .map(|iou| C_IC_NA_1::from(iou.value()).qoi);
+        let mut cas: Vec<u16> = if telegram.adsu() == CA_BROADCAST {
+            self.ca_mappings
+                .iter()
+                .map(|m| m.up_ca)
+                .chain(self.point_mappings.iter().map(|m| m.up_ca))
+                .collect()
+        } else if self.has_up_ca(telegram.adsu()) {
+            vec![telegram.adsu()]
+        } else {
+            return vec![to_master(
+                telegram
+                    .clone()
+                    .with_cot(COT::UnknownAsduAddress)
+                    .with_negative(),
+            )];
+        };
+        if qoi != Some(QOI::Inrogen) {
+            // interrogation groups are not supported
+            return vec![to_master(
+                telegram.clone().with_cot(COT::ActCon).with_negative(),
+            )];
+        }
+        cas.sort_unstable();
+        cas.dedup();
+        let mut output = Vec::new();
+        for ca in cas {
+            output.push(to_master(
+                telegram.clone().with_adsu(ca).with_cot(COT::ActCon),
+            ));
+            let points = self
+                .image
+                .range((ca, 0)..=(ca, IOA_MAX))
+                .map(|(&(ca, ioa), p)| (ca, ioa, p.data_type, p.value))
+                .collect();
+            output.extend(pack(points, COT::Inrogen).into_iter().map(to_master));
+            output.push(to_master(
+                telegram.clone().with_adsu(ca).with_cot(COT::ActTerm),
+            ));
+        }
+        output
+    }
+    fn check_station(&self, station: usize) -> Result<(), Error> {
+        if station < self.stations.len() {
+            Ok(())
+        } else {
+            Err(Error::invalid_config(format!(
+                "no such station: {}",
+                station
+            )))
+        }
+    }
+    fn has_up_ca(&self, up_ca: u16) -> bool {
+        self.ca_mappings.iter().any(|m| m.up_ca == up_ca)
+            || self.point_mappings.iter().any(|m| m.up_ca == up_ca)
+    }
+    fn map_up(&self, station: usize, ca: u16, ioa: u32) -> Option<(u16, u32)> {
+        if let Some(m) = self
+            .point_mappings
+            .iter()
+            .find(|m| m.station == station && m.ca == ca && m.ioa == ioa)
+        {
+            return Some((m.up_ca, m.up_ioa));
+        }
+        let m = self
+            .ca_mappings
+            .iter()
+            .find(|m| m.station == station && m.ca == ca)?;
+        if ioa == 0 {
+            return Some((m.up_ca, 0));
+        }
+        let up_ioa = ioa.checked_add(m.ioa_offset).filter(|a| *a <= IOA_MAX)?;
+        Some((m.up_ca, up_ioa))
+    }
+    fn map_down(&self, up_ca: u16, up_ioa: u32) -> Option<(usize, u16, u32)> {
+        if let Some(m) = self
+            .point_mappings
+            .iter()
+            .find(|m| m.up_ca == up_ca && m.up_ioa == up_ioa)
+        {
+            return Some((m.station, m.ca, m.ioa));
+        }
+        let m = self.ca_mappings.iter().find(|m| m.up_ca == up_ca)?;
+        if up_ioa == 0 {
+            return Some((m.station, m.ca, 0));
+        }
+        let ioa = up_ioa.checked_sub(m.ioa_offset)?;
+        Some((m.station, m.ca, ioa))
+    }
+}
+
+/// Packs points into telegrams, grouped by common address and data type
+fn pack(mut points: Vec<(u16, u32, DataType, DataBuffer)>, cot: COT) -> Vec<Telegram104_I> {
+    points.sort_by_key(|(ca, ioa, data_type, _)| (*ca, *data_type as u8, *ioa));
+    let mut telegrams: Vec<Telegram104_I> = Vec::new();
+    for (ca, ioa, data_type, value) in points {
+        let max_iou = MAX_IOU_LEN / (3 + data_type.size());
+        match telegrams.last_mut() {
+            Some(t) if t.adsu() == ca && t.data_type() == data_type && t.iou().len() < max_iou => {
+                t.append_iou(ioa, value);
+            }
+            _ => {
+                let mut telegram = Telegram104_I::new(data_type, cot, ca);
+                telegram.append_iou(ioa, value);
+                telegrams.push(telegram);
+            }
+        }
+    }
+    telegrams
+}
+
+/// Sets the not topical flag, returns None for types without quality descriptor
+fn set_not_topical(data_type: DataType, value: DataBuffer) -> Option<DataBuffer> {
+    fn not_topical<T>(value: DataBuffer) -> DataBuffer
+    where
+        T: HasQuality + From<DataBuffer> + Into<DataBuffer>,
+    {
+        let mut v = T::from(value);
+        let mut quality = v.quality();
+        quality.nt = true;
+        v.set_quality(quality);
+        v.into()
+    }
+    Some(match data_type {
+        DataType::M_SP_NA_1 => not_topical::<M_SP_NA_1>(value),
+        DataType::M_DP_NA_1 => not_topical::<M_DP_NA_1>(value),
+        DataType::M_ST_NA_1 => not_topical::<M_ST_NA_1>(value),
+        DataType::M_BO_NA_1 => not_topical::<M_BO_NA_1>(value),
+        DataType::M_ME_NA_1 => not_topical::<M_ME_NA_1>(value),
+        DataType::M_ME_NB_1 => not_topical::<M_ME_NB_1>(value),
+        DataType::M_ME_NC_1 => not_topical::<M_ME_NC_1>(value),
+        DataType::M_PS_NA_1 => not_topical::<M_PS_NA_1>(value),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use super::{CaMapping, Concentrator, ConcentratorOutput, PointMapping};
+    use crate::{
+        telegram104::Telegram104_I,
+        types::{
+            COT,
+            datatype::{C_IC_NA_1, C_SC_NA_1, DataType, M_ME_NB_1, M_SP_NA_1, QOI, SIQ, SPI},
+        },
+    };
+
+    const BROADCAST: usize = usize::MAX;
+
+    /// In-process outstation with single points at IOA 1..=n
+    struct Outstation {
+        ca: u16,
+        points: Vec<SPI>,
+    }
+
+    impl Outstation {
+        fn point(&self, ioa: u32) -> M_SP_NA_1 {
+            M_SP_NA_1 {
+                siq: SIQ {
+                    spi: self.points[usize::try_from(ioa).unwrap() - 1],
+                    ..SIQ::default()
+                },
+            }
+        }
+        fn handle(&self, telegram: &Telegram104_I) -> Vec<Telegram104_I> {
+            let confirm = |cot| telegram.clone().with_cot(cot);
+            if telegram.adsu() != self.ca {
+                return vec![confirm(COT::UnknownAsduAddress).with_negative()];
+            }
+            match telegram.data_type() {
+                DataType::C_IC_NA_1 => {
+                    let mut data = Telegram104_I::new(DataType::M_SP_NA_1, COT::Inrogen, self.ca);
+                    for ioa in 1..=u32::try_from(self.points.len()).unwrap() {
+                        data.append_iou(ioa, self.point(ioa));
+                    }
+                    vec![confirm(COT::ActCon), data, confirm(COT::ActTerm)]
+                }
+                DataType::C_SC_NA_1 => vec![confirm(COT::ActCon), confirm(COT::ActTerm)],
+                _ => vec![confirm(COT::UnknownType).with_negative()],
+            }
+        }
+        fn spontaneous(&self, ioa: u32) -> Telegram104_I {
+            let mut telegram = Telegram104_I::new(DataType::M_SP_NA_1, COT::Spontan, self.ca);
+            telegram.append_iou(ioa, self.point(ioa));
+            telegram
+        }
+    }
+
+    /// Delivers the station telegrams to the outstations until all replies are processed,
+    /// returns the upstream telegrams
+    fn run(
+        concentrator: &mut Concentrator,
+        outstations: &[Outstation],
+        output: Vec<ConcentratorOutput>,
+    ) -> Vec<(usize, Telegram104_I)> {
+        let mut queue: VecDeque<ConcentratorOutput> = output.into();
+        let mut upstream = Vec::new();
+        while let Some(o) = queue.pop_front() {
+            match o {
+                ConcentratorOutput::Station { station, telegram } => {
+                    for reply in outstations[station].handle(&telegram) {
+                        queue.extend(concentrator.handle_downstream(station, &reply).unwrap());
+                    }
+                }
+                ConcentratorOutput::Master { master, telegram } => {
+                    upstream.push((master, telegram));
+                }
+                ConcentratorOutput::Broadcast(telegram) => upstream.push((BROADCAST, telegram)),
+            }
+        }
+        upstream
+    }
+
+    fn setup() -> (Concentrator, Vec<Outstation>) {
+        let outstations = vec![
+            Outstation {
+                ca: 1,
+                points: vec![SPI::On, SPI::Off, SPI::On],
+            },
+            Outstation {
+                ca: 1,
+                points: vec![SPI::Off, SPI::Off],
+            },
+        ];
+        let mut concentrator = Concentrator::new();
+        let s1 = concentrator.add_station();
+        let s2 = concentrator.add_station();
+        concentrator
+            .add_ca_mapping(CaMapping::new(s1, 1, 100).with_ioa_offset(1000))
+            .unwrap();
+        concentrator
+            .add_point_mapping(PointMapping::new(s2, 1, 2, 100, 5000))
+            .unwrap();
+        for station in [s1, s2] {
+            let output = concentrator.station_connected(station).unwrap();
+            assert_eq!(output.len(), 1);
+            run(&mut concentrator, &outstations, output);
+        }
+        (concentrator, outstations)
+    }
+
+    fn gi(ca: u16) -> Telegram104_I {
+        let mut telegram = Telegram104_I::new(DataType::C_IC_NA_1, COT::Act, ca);
+        telegram.append_iou(0, C_IC_NA_1 { qoi: QOI::Inrogen });
+        telegram
+    }
+
+    fn command(ca: u16, ioa: u32) -> Telegram104_I {
+        let mut telegram = Telegram104_I::new(DataType::C_SC_NA_1, COT::Act, ca);
+        telegram.append_iou(ioa, C_SC_NA_1::default());
+        telegram
+    }
+
+    #[test]
+    fn concentrator_mappings() {
+        let (mut concentrator, _) = setup();
+        assert!(concentrator.add_station() == 2);
+        assert!(
+            concentrator
+                .add_ca_mapping(CaMapping::new(2, 1, 100))
+                .is_err()
+        );
+        assert!(
+            concentrator
+                .add_ca_mapping(CaMapping::new(0, 1, 101))
+                .is_err()
+        );
+        assert!(
+            concentrator
+                .add_ca_mapping(CaMapping::new(5, 2, 101))
+                .is_err()
+        );
+        assert!(
+            concentrator
+                .add_point_mapping(PointMapping::new(2, 1, 1, 100, 5000))
+                .is_err()
+        );
+        assert!(
+            concentrator
+                .add_point_mapping(PointMapping::new(2, 1, 1, 100, 5001))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn concentrator_image_and_spontaneous() {
+        let (mut concentrator, mut outstations) = setup();
+        // initial interrogation data is cached, but not published
+        let point = concentrator.point(100, 1003).unwrap();
+        assert_eq!(point.data_type(), DataType::M_SP_NA_1);
+        assert_eq!(M_SP_NA_1::from(point.value()).siq.spi, SPI::On);
+        assert!(concentrator.point(100, 5000).is_some());
+        // not mapped
+        assert!(concentrator.point(100, 1).is_none());
+        outstations[1].points[1] = SPI::On;
+        let spontaneous = outstations[1].spontaneous(2);
+        let output = concentrator.handle_downstream(1, &spontaneous).unwrap();
+        let upstream = run(&mut concentrator, &outstations, output);
+        let [(BROADCAST, telegram)] = &upstream[..] else {
+            panic!("broadcast expected");
+        };
+        assert_eq!(telegram.adsu(), 100);
+        assert_eq!(telegram.iou()[0].address(), 5000);
+        assert_eq!(
+            M_SP_NA_1::from(concentrator.point(100, 5000).unwrap().value())
+                .siq
+                .spi,
+            SPI::On
+        );
+        // unmapped points are dropped
+        let spontaneous = outstations[1].spontaneous(1);
+        assert!(
+            concentrator
+                .handle_downstream(1, &spontaneous)
+                .unwrap()
+                .is_empty()
+        );
+        // measured values are cached as well
+        let mut telegram = Telegram104_I::new(DataType::M_ME_NB_1, COT::Cyclic, 1);
+        telegram.append_iou(10, M_ME_NB_1::default());
+        concentrator.handle_downstream(0, &telegram).unwrap();
+        assert_eq!(
+            concentrator.point(100, 1010).unwrap().data_type(),
+            DataType::M_ME_NB_1
+        );
+    }
+
+    #[test]
+    fn concentrator_interrogation() {
+        let (mut concentrator, outstations) = setup();
+        let output = concentrator.handle_upstream(7, &gi(100)).unwrap();
+        let upstream = run(&mut concentrator, &outstations, output);
+        assert!(upstream.iter().all(|(master, _)| *master == 7));
+        let cots: Vec<COT> = upstream.iter().map(|(_, t)| t.cot()).collect();
+        assert_eq!(cots, [COT::ActCon, COT::Inrogen, COT::ActTerm]);
+        let ioas: Vec<u32> = upstream[1]
+            .1
+            .iou()
+            .iter()
+            .map(crate::types::Iou::address)
+            .collect();
+        assert_eq!(ioas, [1001, 1002, 1003, 5000]);
+        // broadcast address
+        let output = concentrator.handle_upstream(7, &gi(0xFFFF)).unwrap();
+        let upstream = run(&mut concentrator, &outstations, output);
+        assert_eq!(upstream.len(), 3);
+        assert_eq!(upstream[0].1.adsu(), 100);
+        // unknown common address
+        let output = concentrator.handle_upstream(7, &gi(5)).unwrap();
+        let upstream = run(&mut concentrator, &outstations, output);
+        assert_eq!(upstream[0].1.cot(), COT::UnknownAsduAddress);
+        assert!(upstream[0].1.is_negative());
+    }
+
+    #[test]
+    fn concentrator_commands() {
+        let (mut concentrator, outstations) = setup();
+        let output = concentrator
+            .handle_upstream(3, &command(100, 5000))
+            .unwrap();
+        let [ConcentratorOutput::Station { station, telegram }] = &output[..] else {
+            panic!("station telegram expected");
+        };
+        assert_eq!(*station, 1);
+        assert_eq!(telegram.adsu(), 1);
+        assert_eq!(telegram.iou()[0].address(), 2);
+        let upstream = run(&mut concentrator, &outstations, output);
+        let replies: Vec<(usize, COT, u16, u32)> = upstream
+            .iter()
+            .map(|(m, t)| (*m, t.cot(), t.adsu(), t.iou()[0].address()))
+            .collect();
+        assert_eq!(
+            replies,
+            [(3, COT::ActCon, 100, 5000), (3, COT::ActTerm, 100, 5000)]
+        );
+        let output = concentrator.handle_upstream(3, &command(100, 500)).unwrap();
+        let upstream = run(&mut concentrator, &outstations, output);
+        assert_eq!(upstream[0].1.cot(), COT::UnknownObjectAddress);
+        let output = concentrator.handle_upstream(3, &command(200, 1)).unwrap();
+        let upstream = run(&mut concentrator, &outstations, output);
+        assert_eq!(upstream[0].1.cot(), COT::UnknownAsduAddress);
+    }
+
+    #[test]
+    fn concentrator_link_loss() {
+        let (mut concentrator, outstations) = setup();
+        // a command is pending when the link is lost
+        let pending = concentrator
+            .handle_upstream(3, &command(100, 1001))
+            .unwrap();
+        assert_eq!(pending.len(), 1);
+        let deact = concentrator
+            .handle_upstream(4, &command(100, 1002).with_cot(COT::Deact))
+            .unwrap();
+        assert_eq!(deact.len(), 1);
+        let output = concentrator.station_disconnected(0).unwrap();
+        let mut upstream = run(&mut concentrator, &outstations, output);
+        assert!(!concentrator.is_connected(0));
+        let (master, deact) = upstream.remove(1);
+        assert_eq!((master, deact.cot()), (4, COT::DeactCon));
+        assert!(deact.is_negative());
+        assert_eq!(upstream.len(), 2);
+        assert_eq!(upstream[0].0, 3);
+        assert_eq!(upstream[0].1.cot(), COT::ActCon);
+        assert!(upstream[0].1.is_negative());
+        let (master, telegram) = &upstream[1];
+        assert_eq!(*master, BROADCAST);
+        assert_eq!(telegram.cot(), COT::Spontan);
+        assert_eq!(telegram.iou().len(), 3);
+        assert!(
+            telegram
+                .iou()
+                .iter()
+                .all(|i| M_SP_NA_1::from(i.value()).siq.nt)
+        );
+        // the other station is not affected
+        assert!(
+            !M_SP_NA_1::from(concentrator.point(100, 5000).unwrap().value())
+                .siq
+                .nt
+        );
+        // commands for the lost station are rejected
+        let output = concentrator
+            .handle_upstream(3, &command(100, 1001))
+            .unwrap();
+        let upstream = run(&mut concentrator, &outstations, output);
+        assert_eq!(upstream[0].1.cot(), COT::ActCon);
+        assert!(upstream[0].1.is_negative());
+        let output = concentrator
+            .handle_upstream(3, &command(100, 1001).with_cot(COT::Deact))
+            .unwrap();
+        let upstream = run(&mut concentrator, &outstations, output);
+        assert_eq!(upstream[0].1.cot(), COT::DeactCon);
+        assert!(upstream[0].1.is_negative());
+        // the station is back, refreshed with a general interrogation
+        let output = concentrator.station_connected(0).unwrap();
+        let upstream = run(&mut concentrator, &outstations, output);
+        let [(BROADCAST, telegram)] = &upstream[..] else {
+            panic!("broadcast expected");
+        };
+        assert_eq!(telegram.cot(), COT::Spontan);
+        assert!(
+            telegram
+                .iou()
+                .iter()
+                .all(|i| !M_SP_NA_1::from(i.value()).siq.nt)
+        );
+    }
+
+    #[test]
+    fn concentrator_interleaved_mappings() {
+        let outstations = vec![Outstation {
+            ca: 1,
+            points: vec![SPI::On, SPI::Off, SPI::On],
+        }];
+        let mut concentrator = Concentrator::new();
+        let station = concentrator.add_station();
+        for (ioa, up_ca) in [(1, 10), (2, 20), (3, 10)] {
+            concentrator
+                .add_point_mapping(PointMapping::new(station, 1, ioa, up_ca, ioa))
+                .unwrap();
+        }
+        let output = concentrator.station_connected(station).unwrap();
+        run(&mut concentrator, &outstations, output);
+        let mut telegram = Telegram104_I::new(DataType::M_SP_NA_1, COT::Spontan, 1);
+        for ioa in 1..=3 {
+            telegram.append_iou(ioa, outstations[0].point(ioa));
+        }
+        let output = concentrator.handle_downstream(station, &telegram).unwrap();
+        let upstream: Vec<(u16, Vec<u32>)> = run(&mut concentrator, &outstations, output)
+            .iter()
+            .map(|(_, t)| {
+                (
+                    t.adsu(),
+                    t.iou().iter().map(crate::types::Iou::address).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(upstream, [(10, vec![1, 3]), (20, vec![2])]);
+    }
+}
@@ -0,0 +1,590 @@
+use std::sync::Arc;
+
+use crate::{
+    Error,
+    clock::{Clock, SystemClock},
+    telegram101::{Config, Telegram101, max_address},
+    telegram104::{CA_BROADCAST, IOA_MAX, Telegram104_I},
+    types::{
+        COT, Iou,
+        datatype::DataType,
+        time::{CP56Time2a, TimePolicy},
+    },
+};
+
+/// Gateway route: a 101 station common address, published as a 104 common address
+#[derive(Debug, Clone)]
+pub struct Route {
+    link: usize,
+    link_address: u32,
+    ca_101: u16,
+    ca_104: u16,
+    ioa_offset: u32,
+}
+
+impl Route {
+    /// Create a new route
+    ///
+    /// `link` is the link id returned by [`Gateway::add_link`]
+    pub fn new(link: usize, link_address: u32, ca_101: u16, ca_104: u16) -> Self {
+        Self {
+            link,
+            link_address,
+            ca_101,
+            ca_104,
+            ioa_offset: 0,
+        }
+    }
+    /// Set the offset added to 101 information object addresses (104 IOA = 101 IOA + offset),
+    /// IOA 0 is not shifted
+    pub fn with_ioa_offset(mut self, ioa_offset: u32) -> Self {
+        self.ioa_offset = ioa_offset;
+        self
+    }
+    /// 101 link id
+    pub fn link(&self) -> usize {
+        self.link
+    }
+    /// 101 link address of the station
+    pub fn link_address(&self) -> u32 {
+        self.link_address
+    }
+    /// 101 common address
+    pub fn ca_101(&self) -> u16 {
+        self.ca_101
+    }
+    /// 104 common address
+    pub fn ca_104(&self) -> u16 {
+        self.ca_104
+    }
+    /// Information object address offset
+    pub fn ioa_offset(&self) -> u32 {
+        self.ioa_offset
+    }
+    // IOA 0 (station-wide objects, e.g. interrogation) is never shifted
+    fn ioa_to_104(&self, ioa: u32) -> Result<u32, Error> {
+        if ioa == 0 {
+            return Ok(0);
+        }
+        ioa.checked_add(self.ioa_offset)
+            .filter(|a| *a <= IOA_MAX)
+            .ok_or_else(|| Error::invalid_data(format!("IOA {} can not be mapped", ioa)))
+    }
+    fn ioa_to_101(&self, ioa: u32, max_ioa: u32) -> Result<u32, Error> {
+        if ioa == 0 {
+            return Ok(0);
+        }
+        ioa.checked_sub(self.ioa_offset)
+            .filter(|a| *a <= max_ioa)
+            .ok_or_else(|| Error::invalid_data(format!("IOA {} can not be mapped", ioa)))
+    }
+}
+
+/// Gateway output
+#[derive(Debug, Clone)]
+pub enum GatewayOutput {
+    /// A telegram to be sent to a 101 station
+    Link101 {
+        /// 101 link id
+        link: usize,
+        /// The telegram, the link address is set to the station link address
+        telegram: Telegram101,
+    },
+    /// A telegram to be sent to a single 104 master (e.g. a command confirmation)
+    Master {
+        /// Master id, as given to [`Gateway::handle_104`]
+        master: usize,
+        /// The telegram
+        telegram: Telegram104_I,
+    },
+    /// A telegram to be sent to all connected 104 masters
+    Broadcast(Telegram104_I),
+}
+
+/// A command forwarded to a 101 station, waiting for its confirmation/termination
+#[derive(Debug, Clone)]
+struct PendingCommand {
+    master: usize,
+    link: usize,
+    ca_101: u16,
+    data_type: DataType,
+    ioa_101: u32,
+}
+
+/// IEC 60870-5-101 to 104 gateway
+///
+/// The gateway is transport-agnostic: 101 ASDUs received from the links are given to
+/// [`Gateway::handle_101`], 104 I-frames received from the masters to [`Gateway::handle_104`],
+/// both return telegrams to be sent. The 101 transmission procedures (e.g. polling with
+/// [`crate::link101::PollScheduler101`]) and the 104 connections, including the sequence
+/// numbers, are handled by the application.
+///
+/// Common addresses are translated according to the routing table, information object addresses
+/// are shifted by the route offset. Activation confirmations and terminations are passed to the
+/// master which has sent the command only, all other data is published to all masters.
+/// Information with CP24Time2a time tags, not allowed in IEC 60870-5-104, is converted to the
+/// equivalent types with CP56Time2a time tags, using the time of reception as the reference.
+#[derive(Debug, Clone)]
+pub struct Gateway {
+    links: Vec<Config>,
+    routes: Vec<Route>,
+    pending: Vec<PendingCommand>,
+    clock: Arc<dyn Clock>,
+    policy: TimePolicy,
+}
+
+impl Default for Gateway {
+    fn default() -> Self {
+        Self {
+            links: Vec::new(),
+            routes: Vec::new(),
+            pending: Vec::new(),
+            clock: Arc::new(SystemClock),
+            policy: TimePolicy::default(),
+        }
+    }
+}
+
+impl Gateway {
+    /// Create a new gateway
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Set the clock (default: system clock)
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    /// Set the time zone policy of the 101 time tags (default: UTC)
+    pub fn with_policy(mut self, policy: TimePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+    /// Add a 101 link with the given telegram configuration, returns the link id
+    ///
+    /// The telegrams forwarded to a link are written with its configuration, a link without
+    /// [`Config::strict_cot`] accepts causes of transmission deviating from the standard (the
+    /// 104 side then needs [`Telegram104::write_lenient`](crate::telegram104::Telegram104::write_lenient))
+    pub fn add_link(&mut self, config: Config) -> usize {
+        self.links.push(config);
+        self.links.len() - 1
+    }
+    /// Add a route
+    pub fn add_route(&mut self, route: Route) -> Result<(), Error> {
+        let config = self
+            .links
+            .get(route.link)
+            .ok_or_else(|| Error::invalid_config(format!("no such link: {}", route.link)))?;
+        if u32::from(route.ca_101) > max_address(config.adsu_address_len()) {
+            return Err(Error::invalid_config(format!(
+                "101 common address {} exceeds the ADSU address length",
+                route.ca_101
+            )));
+        }
+        if route.ca_104 == CA_BROADCAST {
+            return Err(Error::invalid_config(
+                "the 104 broadcast common address can not be routed",
+            ));
+        }
+        if route.ioa_offset > IOA_MAX {
+            return Err(Error::invalid_config("IOA offset out of range"));
+        }
+        if self.routes.iter().any(|r| r.ca_104 == route.ca_104) {
+            return Err(Error::invalid_config(format!(
+                "duplicate 104 common address: {}",
+                route.ca_104
+            )));
+        }
+        if self
+            .routes
+            .iter()
+            .any(|r| r.link == route.link && r.ca_101 == route.ca_101)
+        {
+            return Err(Error::invalid_config(format!(
+                "duplicate 101 common address: {}",
+                route.ca_101
+            )));
+        }
+        self.routes.push(route);
+        Ok(())
+    }
+    /// Routing table
+    pub fn routes(&self) -> &[Route] {
+        &self.routes
+    }
+    /// Forget the pending commands of a master (e.g. when its connection is closed)
+    pub fn remove_master(&mut self, master: usize) {
+        self.pending.retain(|p| p.master != master);
+    }
+    /// Process a telegram received from a 101 station
+    ///
+    /// Link layer frames (fixed length and single-character) are ignored.
+    pub fn handle_101(
+        &mut self,
+        link: usize,
+        telegram: &Telegram101,
+    ) -> Result<Vec<GatewayOutput>, Error> {
+        if telegram.is_fixed() || telegram.is_ack_only() {
+            return Ok(Vec::new());
+        }
+        let route = self
+            .routes
+            .iter()
+            .find(|r| r.link == link && r.ca_101 == telegram.adsu())
+            .ok_or_else(|| {
+                Error::invalid_data(format!(
+                    "no route for link {} common address {}",
+                    link,
+                    telegram.adsu()
+                ))
+            })?;
+        let converted;
+        let telegram = if telegram.data_type().cp56_equivalent().is_some() {
+            let reference = CP56Time2a::now(&*self.clock, &self.policy)?;
+            converted = telegram.clone().into_cp56_time_tags(&reference)?;
+            &converted
+        } else {
+            telegram
+        };
+        let mut iou = Vec::with_capacity(telegram.iou().len());
+        for i in telegram.iou() {
+            let address = route.ioa_to_104(i.address())?;
+            iou.push(Iou::new(address, i.value()));
+        }
+        let mut out = Telegram104_I::new(telegram.data_type(), telegram.cot(), route.ca_104)
+            .with_originator(u8::try_from(telegram.originator()).unwrap_or_default())
+            .with_iou(iou);
+        if telegram.is_sequental() {
+            out = out.with_seq();
+        }
+        if telegram.is_test() {
+            out = out.with_test();
+        }
+        if telegram.is_negative() {
+            out = out.with_negative();
+        }
+        if telegram.cot().is_command_response() {
+            let ioa_101 = telegram.iou().first().map_or(0, Iou::address);
+            if let Some(pos) = self.pending.iter().position(|p| {
+                p.link == link
+                    && p.ca_101 == telegram.adsu()
+                    && p.data_type == telegram.data_type()
+                    && p.ioa_101 == ioa_101
+            }) {
+                let master = self.pending[pos].master;
+                // the command is complete unless a termination is expected
+                if telegram.cot() != COT::ActCon
+                    || telegram.is_negative()
+                    || !telegram.data_type().has_termination()
+                {
+                    self.pending.remove(pos);
+                }
+                return Ok(vec![GatewayOutput::Master {
+                    master,
+                    telegram: out,
+                }]);
+            }
+        }
+        Ok(vec![GatewayOutput::Broadcast(out)])
+    }
+    /// Process an I-frame received from a 104 master
+    ///
+    /// Telegrams for the broadcast common address are forwarded to all routed stations. A
+    /// telegram for an unknown common address is mirrored back to the master with COT 46
+    /// (unknown ASDU address).
+    pub fn handle_104(
+        &mut self,
+        master: usize,
+        telegram: &Telegram104_I,
+    ) -> Result<Vec<GatewayOutput>, Error> {
+        let routes: Vec<Route> = if telegram.adsu() == CA_BROADCAST {
+            self.routes.clone()
+        } else {
+            self.routes
+                .iter()
+                .filter(|r| r.ca_104 == telegram.adsu())
+                .cloned()
+                .collect()
+        };
+        if routes.is_empty() {
+            let reply = telegram
+                .clone()
+                .with_cot(COT::UnknownAsduAddress)
+                .with_negative();
+            return Ok(vec![GatewayOutput::Master {
+                master,
+                telegram: reply,
+            }]);
+        }
+        // all routes are mapped before any command is registered as pending
+        let mut forwarded = Vec::with_capacity(routes.len());
+        for route in routes {
+            let config = self.links[route.link];
+            let max_ioa = max_address(config.iou_address_len());
+            let mut out =
+                Telegram101::new(telegram.data_type(), telegram.cot(), route.ca_101, config)
+                    .with_originator(u16::from(telegram.originator()))
+                    .with_link_address(route.link_address);
+            for i in telegram.iou() {
+                let address = route.ioa_to_101(i.address(), max_ioa)?;
+                out.append_iou(address, i.value());
+            }
+            if telegram.is_sequental() {
+                out = out.with_seq();
+            }
+            if telegram.is_test() {
+                out = out.with_test();
+            }
+            if telegram.is_negative() {
+                out = out.with_negative();
+            }
+            forwarded.push((route, out));
+        }
+        let mut output = Vec::with_capacity(forwarded.len());
+        for (route, out) in forwarded {
+            if matches!(telegram.cot(), COT::Act | COT::Deact) {
+                let pending = PendingCommand {
+                    master,
+                    link: route.link,
+                    ca_101: route.ca_101,
+                    data_type: telegram.data_type(),
+                    ioa_101: out.iou().first().map_or(0, Iou::address),
+                };
+                // a repeated command replaces the previous one
+                self.pending.retain(|p| {
+                    p.link != pending.link
+                        || p.ca_101 != pending.ca_101
+                        || p.data_type != pending.data_type
+                        || p.ioa_101 != pending.ioa_101
+                });
+                self.pending.push(pending);
+            }
+            output.push(GatewayOutput::Link101 {
+                link: route.link,
+                telegram: out,
+            });
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use bma_ts::Timestamp;
+
+    use super::{Gateway, GatewayOutput, Route};
+    use crate::{
+        clock::ManualClock,
+        telegram101::{Config, Telegram101},
+        telegram104::Telegram104_I,
+        types::{
+            COT,
+            datatype::{C_IC_NA_1, C_SC_NA_1, DataType, M_SP_NA_1, M_SP_TA_1, M_SP_TB_1, SCO},
+            time::CP24Time2a,
+        },
+    };
+
+    fn gateway() -> Gateway {
+        let mut gateway = Gateway::new();
+        let link = gateway.add_link(
+            Config::new()
+                .with_adsu_address_len(1)
+                .with_iou_address_len(2),
+        );
+        gateway
+            .add_route(Route::new(link, 3, 7, 1007).with_ioa_offset(0x01_0000))
+            .unwrap();
+        gateway.add_route(Route::new(link, 4, 8, 1008)).unwrap();
+        gateway
+    }
+
+    fn command(ca: u16, ioa: u32) -> Telegram104_I {
+        let mut telegram = Telegram104_I::new(DataType::C_SC_NA_1, COT::Act, ca);
+        telegram.append_iou(
+            ioa,
+            C_SC_NA_1 {
+                sco: SCO::default(),
+            },
+        );
+        telegram
+    }
+
+    fn reply(cot: COT, ca: u16, ioa: u32) -> Telegram101 {
+        let mut telegram = Telegram101::new(DataType::C_SC_NA_1, cot, ca, Config::new());
+        telegram.append_iou(
+            ioa,
+            C_SC_NA_1 {
+                sco: SCO::default(),
+            },
+        );
+        telegram
+    }
+
+    #[test]
+    fn gateway_routes() {
+        let mut gateway = gateway();
+        let link = gateway.add_link(Config::new());
+        assert!(gateway.add_route(Route::new(link, 1, 9, 1007)).is_err());
+        assert!(gateway.add_route(Route::new(0, 1, 7, 1009)).is_err());
+        assert!(gateway.add_route(Route::new(0, 1, 0x100, 1009)).is_err());
+        assert!(gateway.add_route(Route::new(0, 1, 9, 0xFFFF)).is_err());
+        assert!(gateway.add_route(Route::new(5, 1, 9, 1009)).is_err());
+        assert!(gateway.add_route(Route::new(link, 1, 7, 1009)).is_ok());
+    }
+
+    #[test]
+    fn gateway_lenient_link() {
+        let mut gateway = Gateway::new();
+        let link = gateway.add_link(Config::new().with_strict_cot(false));
+        gateway.add_route(Route::new(link, 1, 7, 1007)).unwrap();
+        let out = gateway
+            .handle_104(0, &command(1007, 100).with_cot(COT::Cyclic))
+            .unwrap();
+        let [GatewayOutput::Link101 { telegram, .. }] = &out[..] else {
+            panic!("101 telegram expected");
+        };
+        let mut buf = Vec::new();
+        telegram.write(&mut buf).unwrap();
+        let received = Telegram101::read(buf.as_slice(), telegram.config().unwrap()).unwrap();
+        let out = gateway.handle_101(link, &received).unwrap();
+        let [GatewayOutput::Broadcast(t)] = &out[..] else {
+            panic!("broadcast expected");
+        };
+        assert_eq!(t.cot(), COT::Cyclic);
+    }
+
+    #[test]
+    fn gateway_monitor_direction() {
+        let mut gateway = gateway();
+        let mut telegram = Telegram101::new(DataType::M_SP_NA_1, COT::Spontan, 7, Config::new());
+        telegram.append_iou(0xFFFF, M_SP_NA_1::default());
+        let out = gateway.handle_101(0, &telegram).unwrap();
+        let [GatewayOutput::Broadcast(t)] = &out[..] else {
+            panic!("broadcast expected");
+        };
+        assert_eq!(t.adsu(), 1007);
+        assert_eq!(t.iou()[0].address(), 0x01_FFFF);
+        assert_eq!(t.cot(), COT::Spontan);
+        let telegram = Telegram101::new(DataType::M_SP_NA_1, COT::Spontan, 9, Config::new());
+        assert!(gateway.handle_101(0, &telegram).is_err());
+        assert!(gateway.handle_101(1, &reply(COT::ActCon, 7, 1)).is_err());
+        let fixed = Telegram101::new_fixed(Config::new());
+        assert!(gateway.handle_101(0, &fixed).unwrap().is_empty());
+    }
+
+    #[test]
+    fn gateway_command_confirmations() {
+        let mut gateway = gateway();
+        let out = gateway.handle_104(5, &command(1008, 100)).unwrap();
+        let [GatewayOutput::Link101 { link, telegram }] = &out[..] else {
+            panic!("101 telegram expected");
+        };
+        assert_eq!(*link, 0);
+        assert_eq!(telegram.link_address(), 4);
+        assert_eq!(telegram.adsu(), 8);
+        assert_eq!(telegram.iou()[0].address(), 100);
+        // the IOA does not fit into the 101 address length
+        assert!(gateway.handle_104(5, &command(1008, 0x01_0000)).is_err());
+        let out = gateway.handle_101(0, &reply(COT::ActCon, 8, 100)).unwrap();
+        let [GatewayOutput::Master { master, telegram }] = &out[..] else {
+            panic!("master telegram expected");
+        };
+        assert_eq!(*master, 5);
+        assert_eq!(telegram.adsu(), 1008);
+        assert_eq!(telegram.cot(), COT::ActCon);
+        let out = gateway.handle_101(0, &reply(COT::ActTerm, 8, 100)).unwrap();
+        assert!(matches!(out[..], [GatewayOutput::Master { master: 5, .. }]));
+        // the command is complete
+        let out = gateway.handle_101(0, &reply(COT::ActTerm, 8, 100)).unwrap();
+        assert!(matches!(out[..], [GatewayOutput::Broadcast(_)]));
+        // negative confirmation completes the command as well
+        gateway.handle_104(6, &command(1007, 0x01_0001)).unwrap();
+        let out = gateway
+            .handle_101(0, &reply(COT::ActCon, 7, 1).with_negative())
+            .unwrap();
+        let [GatewayOutput::Master { master, telegram }] = &out[..] else {
+            panic!("master telegram expected");
+        };
+        assert_eq!(*master, 6);
+        assert!(telegram.is_negative());
+        assert_eq!(telegram.iou()[0].address(), 0x01_0001);
+        let out = gateway.handle_101(0, &reply(COT::ActTerm, 7, 1)).unwrap();
+        assert!(matches!(out[..], [GatewayOutput::Broadcast(_)]));
+        // pending commands of disconnected masters are dropped
+        gateway.handle_104(6, &command(1008, 100)).unwrap();
+        gateway.remove_master(6);
+        let out = gateway.handle_101(0, &reply(COT::ActCon, 8, 100)).unwrap();
+        assert!(matches!(out[..], [GatewayOutput::Broadcast(_)]));
+    }
+
+    #[test]
+    fn gateway_unknown_and_broadcast_ca() {
+        let mut gateway = gateway();
+        let out = gateway.handle_104(1, &command(999, 100)).unwrap();
+        let [GatewayOutput::Master { master, telegram }] = &out[..] else {
+            panic!("master telegram expected");
+        };
+        assert_eq!(*master, 1);
+        assert_eq!(telegram.cot(), COT::UnknownAsduAddress);
+        assert!(telegram.is_negative());
+        let mut gi = Telegram104_I::new(DataType::C_IC_NA_1, COT::Act, 0xFFFF);
+        gi.append_iou(0, C_IC_NA_1::default());
+        let out = gateway.handle_104(1, &gi).unwrap();
+        let cas: Vec<(u32, u16)> = out
+            .iter()
+            .map(|o| match o {
+                GatewayOutput::Link101 { telegram, .. } => {
+                    (telegram.link_address(), telegram.adsu())
+                }
+                _ => panic!("101 telegram expected"),
+            })
+            .collect();
+        assert_eq!(cas, [(3, 7), (4, 8)]);
+    }
+
+    #[test]
+    fn gateway_cp24_time_tags() {
+        // 2023-11-14 23:00:00 UTC
+        let clock = Arc::new(ManualClock::new(Timestamp::from_secs(1_700_002_800)));
+        let mut gateway = gateway().with_clock(clock);
+        let mut telegram = Telegram101::new(DataType::M_SP_TA_1, COT::Spontan, 8, Config::new());
+        telegram.append_iou(
+            1,
+            M_SP_TA_1 {
+                time: CP24Time2a {
+                    ms: 59_900,
+                    min: 59,
+                    iv: false,
+                },
+                ..M_SP_TA_1::default()
+            },
+        );
+        let out = gateway.handle_101(0, &telegram).unwrap();
+        let [GatewayOutput::Broadcast(t)] = &out[..] else {
+            panic!("broadcast expected");
+        };
+        assert_eq!(t.data_type(), DataType::M_SP_TB_1);
+        let time = M_SP_TB_1::from(t.iou()[0].value()).time;
+        assert_eq!(
+            (time.day, time.hour, time.min, time.ms),
+            (14, 22, 59, 59_900)
+        );
+    }
+
+    #[test]
+    fn gateway_broadcast_command_unmapped() {
+        let mut gateway = gateway();
+        // maps to IOA 5 on the first route, does not fit into the second one
+        let mut gi = Telegram104_I::new(DataType::C_IC_NA_1, COT::Act, 0xFFFF);
+        gi.append_iou(0x01_0005, C_IC_NA_1::default());
+        assert!(gateway.handle_104(1, &gi).is_err());
+        assert!(gateway.pending.is_empty());
+        // a confirmation of the first station is not routed to the master
+        let mut con = Telegram101::new(DataType::C_IC_NA_1, COT::ActCon, 7, Config::new());
+        con.append_iou(5, C_IC_NA_1::default());
+        let out = gateway.handle_101(0, &con).unwrap();
+        assert!(matches!(out[..], [GatewayOutput::Broadcast(_)]));
+    }
+}
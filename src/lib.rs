@@ -26,6 +26,9 @@ pub enum Error {
     /// Invalid data
     #[error("invalid data: {0}")]
     InvalidData(String),
+    /// Invalid configuration
+    #[error("invalid config: {0}")]
+    InvalidConfig(String),
 }
 
 impl Error {
@@ -35,10 +38,29 @@ impl Error {
     fn invalid_data(msg: impl fmt::Display) -> Self {
         Error::InvalidData(msg.to_string())
     }
+    fn invalid_config(msg: impl fmt::Display) -> Self {
+        Error::InvalidConfig(msg.to_string())
+    }
 }
 
+/// Time sources
+pub mod clock;
+/// Clock synchronization (controlling station)
+pub mod clock_sync;
+/// IEC 60870-5-104 data concentrator
+pub mod concentrator;
 /// Server events
 pub mod events;
+/// IEC 60870-5-101 to 104 gateway
+pub mod gateway;
+/// IEC 60870-5-101 link layer transports
+pub mod link101;
+/// IEC 60870-5 outstation (controlled station) services
+pub mod outstation;
+/// IEC 60870-5-104 transparent proxy
+pub mod proxy;
+/// Transformer tap changer control
+pub mod tap_changer;
 /// IEC 60870-5-101
 pub mod telegram101;
 /// IEC 60870-5-104
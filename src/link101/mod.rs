@@ -0,0 +1,197 @@
+use std::{io::Cursor, time::Duration};
+
+use crate::{
+    Error,
+    telegram101::{
+        Config, IEC_ACK_NEGATIVE, IEC_ACK_POSITIVE, IEC_HEADER, IEC_HEADER_FIXED, Telegram101,
+    },
+};
+
+mod primary;
+mod scheduler;
+#[cfg(feature = "serial")]
+mod serial;
+mod sim;
+mod tcp;
+
+pub use primary::{Primary101, StationLink};
+pub use scheduler::{PollEvent, PollScheduler101, PolledStation, StationState, StationStats};
+#[cfg(feature = "serial")]
+pub use serial::{Parity, SerialConfig, SerialLink101, StopBits};
+pub use sim::{SimBus, SimFaults, SimLink101, SimStats};
+pub use tcp::{TcpLink101, TcpServerLink101};
+
+/// Link layer function codes (unbalanced transmission)
+pub mod function_code {
+    /// Primary: reset of remote link
+    pub const RESET_REMOTE_LINK: u8 = 0;
+    /// Primary: reset of user process
+    pub const RESET_USER_PROCESS: u8 = 1;
+    /// Primary: user data, send/confirm
+    pub const USER_DATA_CONFIRM: u8 = 3;
+    /// Primary: user data, send/no reply
+    pub const USER_DATA_NO_REPLY: u8 = 4;
+    /// Primary: request for access demand
+    pub const REQUEST_ACCESS_DEMAND: u8 = 8;
+    /// Primary: request status of link
+    pub const REQUEST_LINK_STATUS: u8 = 9;
+    /// Primary: request user data class 1
+    pub const REQUEST_CLASS_1: u8 = 10;
+    /// Primary: request user data class 2
+    pub const REQUEST_CLASS_2: u8 = 11;
+    /// Secondary: positive acknowledgement
+    pub const ACK: u8 = 0;
+    /// Secondary: message not accepted, link busy
+    pub const NACK: u8 = 1;
+    /// Secondary: user data
+    pub const USER_DATA: u8 = 8;
+    /// Secondary: requested data not available
+    pub const NACK_NO_DATA: u8 = 9;
+    /// Secondary: status of link or access demand
+    pub const LINK_STATUS: u8 = 11;
+    /// Secondary: link service not functioning
+    pub const LINK_NOT_FUNCTIONING: u8 = 14;
+    /// Secondary: link service not implemented
+    pub const LINK_NOT_IMPLEMENTED: u8 = 15;
+}
+
+/// IEC 60870-5-101 frame transport (FT1.2 telegrams over a physical or virtual line)
+pub trait Link101 {
+    /// Telegram configuration used to encode and decode frames
+    fn config(&self) -> Config;
+    /// Send a telegram
+    fn send(&mut self, telegram: &Telegram101) -> Result<(), Error>;
+    /// Receive a telegram, waiting for its first character up to `timeout`
+    ///
+    /// Returns `Ok(None)` if no frame has been started within the timeout
+    fn recv(&mut self, timeout: Duration) -> Result<Option<Telegram101>, Error>;
+}
+
+/// Receive buffer for stream transports, where chunk boundaries have no relation to frame
+/// boundaries
+#[derive(Debug, Default)]
+pub(crate) struct FrameBuffer {
+    buf: Vec<u8>,
+}
+
+impl FrameBuffer {
+    pub(crate) fn extend(&mut self, data: &[u8]) {
+        self.buf.extend_from_slice(data);
+    }
+    pub(crate) fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+    /// Length of the frame at the start of the buffer, `None` if not known yet
+    fn frame_len(&self, config: Config) -> Option<usize> {
+        match self.buf[0] {
+            IEC_HEADER => self.buf.get(1).map(|len| usize::from(*len) + 6),
+            IEC_HEADER_FIXED => Some(4 + usize::from(config.link_address_len())),
+            _ => Some(1),
+        }
+    }
+    /// Takes the next complete frame from the buffer
+    ///
+    /// Returns `Ok(None)` if more data is required. On invalid data, the buffer is skipped to the
+    /// next possible frame start character and the error is returned.
+    pub(crate) fn next_frame(&mut self, config: Config) -> Result<Option<Telegram101>, Error> {
+        if self.buf.is_empty() {
+            return Ok(None);
+        }
+        if self
+            .frame_len(config)
+            .is_none_or(|len| self.buf.len() < len)
+        {
+            return Ok(None);
+        }
+        let mut cursor = Cursor::new(&self.buf);
+        match Telegram101::read(&mut cursor, config) {
+            Ok(telegram) => {
+                let pos = usize::try_from(cursor.position()).unwrap();
+                self.buf.drain(..pos);
+                Ok(Some(telegram))
+            }
+            Err(e) => {
+                let next_start = self.buf[1..]
+                    .iter()
+                    .position(|b| {
+                        matches!(
+                            *b,
+                            IEC_HEADER | IEC_HEADER_FIXED | IEC_ACK_POSITIVE | IEC_ACK_NEGATIVE
+                        )
+                    })
+                    .map_or(self.buf.len(), |pos| pos + 1);
+                self.buf.drain(..next_start);
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FrameBuffer;
+    use crate::Error;
+    use crate::{
+        telegram101::{Config, Telegram101},
+        types::{COT, datatype::DataType},
+    };
+
+    #[test]
+    fn frame_buffer_resync() {
+        let config = Config::new();
+        let mut telegram = Telegram101::new(DataType::M_SP_NA_1, COT::Spontan, 1, config);
+        telegram.append_iou(1, [1u8; 12]);
+        let mut frame = Vec::new();
+        telegram.write(&mut frame).unwrap();
+        let mut buffer = FrameBuffer::default();
+        // incomplete frame
+        buffer.extend(&frame[..5]);
+        assert!(buffer.next_frame(config).unwrap().is_none());
+        buffer.extend(&frame[5..]);
+        assert!(buffer.next_frame(config).unwrap().is_some());
+        assert!(buffer.is_empty());
+        // a complete frame announcing more user data than its ASDU holds
+        let mut bad = frame.clone();
+        bad.truncate(bad.len() - 3);
+        bad[1] -= 1;
+        bad[2] -= 1;
+        let checksum = bad[4..].iter().fold(0u8, |sum, b| sum.wrapping_add(*b));
+        bad.extend([checksum, 0x16]);
+        buffer.extend(&bad);
+        buffer.extend(&frame);
+        assert!(matches!(
+            buffer.next_frame(config),
+            Err(Error::InvalidData(ref e)) if e == "ASDU shorter than the frame length"
+        ));
+        let mut received = None;
+        for _ in 0..bad.len() {
+            match buffer.next_frame(config) {
+                Ok(Some(telegram)) => {
+                    received = Some(telegram);
+                    break;
+                }
+                Ok(None) => break,
+                Err(_) => {}
+            }
+        }
+        let received = received.expect("the valid frame is received after the bad one");
+        assert_eq!(received.data_type(), DataType::M_SP_NA_1);
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn frame_buffer_lenient_cot() {
+        let lenient = Config::new().with_strict_cot(false);
+        let mut telegram = Telegram101::new(DataType::C_SC_NA_1, COT::Cyclic, 1, lenient);
+        telegram.append_iou(1, [1u8; 12]);
+        let mut frame = Vec::new();
+        telegram.write(&mut frame).unwrap();
+        let mut buffer = FrameBuffer::default();
+        buffer.extend(&frame);
+        assert!(buffer.next_frame(Config::new()).is_err());
+        let mut buffer = FrameBuffer::default();
+        buffer.extend(&frame);
+        let received = buffer.next_frame(lenient).unwrap().unwrap();
+        assert_eq!(received.cot(), COT::Cyclic);
+    }
+}
@@ -0,0 +1,216 @@
+use std::{io, sync::Arc, time::Duration};
+
+use crate::{
+    Error,
+    clock::{Clock, SystemClock},
+    telegram101::Telegram101,
+};
+
+use super::{Link101, function_code};
+
+const DEFAULT_RESPONSE_TIMEOUT: Duration = Duration::from_millis(500);
+const DEFAULT_RETRIES: u32 = 2;
+
+/// Link layer state of a secondary station, kept by the primary station
+#[derive(Debug, Clone)]
+pub struct StationLink {
+    link_address: u32,
+    fcb: bool,
+    retries: u64,
+}
+
+impl StationLink {
+    /// Create a new station link state
+    pub fn new(link_address: u32) -> Self {
+        Self {
+            link_address,
+            fcb: false,
+            retries: 0,
+        }
+    }
+    /// Link address
+    pub fn link_address(&self) -> u32 {
+        self.link_address
+    }
+    /// The frame count bit to be used in the next SEND/CONFIRM or REQUEST/RESPOND exchange
+    pub fn fcb(&self) -> bool {
+        self.fcb
+    }
+    /// Total number of repeated transmissions
+    pub fn retries(&self) -> u64 {
+        self.retries
+    }
+}
+
+/// IEC 60870-5-101 primary station (unbalanced transmission)
+///
+/// Performs single link layer exchanges with secondary stations: the frame count bit is
+/// toggled after each successful exchange with FCV set, unanswered frames are repeated with
+/// the same frame count bit.
+pub struct Primary101<L: Link101> {
+    link: L,
+    response_timeout: Duration,
+    retries: u32,
+    clock: Arc<dyn Clock>,
+}
+
+impl<L: Link101> Primary101<L> {
+    /// Create a new primary station on the given link
+    pub fn new(link: L) -> Self {
+        Self {
+            link,
+            response_timeout: DEFAULT_RESPONSE_TIMEOUT,
+            retries: DEFAULT_RETRIES,
+            clock: Arc::new(SystemClock),
+        }
+    }
+    /// Set the clock (response timeout, default: system clock)
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    /// Set the secondary station response timeout (default: 500ms)
+    pub fn with_response_timeout(mut self, response_timeout: Duration) -> Self {
+        self.response_timeout = response_timeout;
+        self
+    }
+    /// Set the number of repetitions of unanswered frames (default: 2)
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+    /// The underlying link
+    pub fn link(&self) -> &L {
+        &self.link
+    }
+    /// The underlying link (mutable)
+    pub fn link_mut(&mut self) -> &mut L {
+        &mut self.link
+    }
+    /// Request status of link
+    pub fn request_link_status(&mut self, station: &mut StationLink) -> Result<Telegram101, Error> {
+        let reply = self.exchange(station, function_code::REQUEST_LINK_STATUS, false, None)?;
+        if reply.is_ack_only() || reply.function_code() != function_code::LINK_STATUS {
+            return Err(Error::invalid_data("unexpected link status reply"));
+        }
+        Ok(reply)
+    }
+    /// Reset of remote link, the next frame count bit is set to 1
+    pub fn reset_remote_link(&mut self, station: &mut StationLink) -> Result<Telegram101, Error> {
+        let reply = self.exchange(station, function_code::RESET_REMOTE_LINK, false, None)?;
+        if !is_ack(&reply) {
+            return Err(Error::invalid_data("remote link reset not confirmed"));
+        }
+        station.fcb = true;
+        Ok(reply)
+    }
+    /// Request user data class 1
+    ///
+    /// The reply is either user data, "no data" (NACK or the single-character ACK) or a link
+    /// status frame. The ACD bit of the reply signals that more class 1 data is available.
+    pub fn request_class_1(&mut self, station: &mut StationLink) -> Result<Telegram101, Error> {
+        self.exchange(station, function_code::REQUEST_CLASS_1, true, None)
+    }
+    /// Request user data class 2
+    pub fn request_class_2(&mut self, station: &mut StationLink) -> Result<Telegram101, Error> {
+        self.exchange(station, function_code::REQUEST_CLASS_2, true, None)
+    }
+    /// Send user data with confirmation (SEND/CONFIRM)
+    pub fn send_confirm(
+        &mut self,
+        station: &mut StationLink,
+        telegram: Telegram101,
+    ) -> Result<Telegram101, Error> {
+        if telegram.is_fixed() || telegram.is_ack_only() {
+            return Err(Error::invalid_data("user data telegram required"));
+        }
+        let reply = self.exchange(
+            station,
+            function_code::USER_DATA_CONFIRM,
+            true,
+            Some(telegram),
+        )?;
+        if !is_ack(&reply) {
+            return Err(Error::invalid_data("user data not confirmed"));
+        }
+        Ok(reply)
+    }
+    /// Send user data without reply (SEND/NO REPLY), e.g. to the broadcast link address
+    pub fn send_no_reply(&mut self, link_address: u32, telegram: Telegram101) -> Result<(), Error> {
+        if telegram.is_fixed() || telegram.is_ack_only() {
+            return Err(Error::invalid_data("user data telegram required"));
+        }
+        let frame = telegram
+            .with_prm(true)
+            .with_fcb_acd(false)
+            .with_fcv_dfc(false)
+            .with_function_code(function_code::USER_DATA_NO_REPLY)
+            .with_link_address(link_address);
+        self.link.send(&frame)
+    }
+    fn exchange(
+        &mut self,
+        station: &mut StationLink,
+        function_code: u8,
+        fcv: bool,
+        telegram: Option<Telegram101>,
+    ) -> Result<Telegram101, Error> {
+        let frame = telegram
+            .unwrap_or_else(|| Telegram101::new_fixed(self.link.config()))
+            .with_prm(true)
+            .with_fcb_acd(fcv && station.fcb)
+            .with_fcv_dfc(fcv)
+            .with_function_code(function_code)
+            .with_link_address(station.link_address);
+        let mut last_error: Error = io::Error::from(io::ErrorKind::TimedOut).into();
+        for attempt in 0..=self.retries {
+            if attempt > 0 {
+                station.retries += 1;
+            }
+            self.link.send(&frame)?;
+            match self.wait_reply(station.link_address) {
+                Ok(Some(reply)) => {
+                    if fcv {
+                        station.fcb = !station.fcb;
+                    }
+                    return Ok(reply);
+                }
+                Ok(None) => {}
+                Err(Error::Io(e)) if e.kind() != io::ErrorKind::TimedOut => {
+                    return Err(e.into());
+                }
+                Err(e) => last_error = e,
+            }
+        }
+        Err(last_error)
+    }
+    fn wait_reply(&mut self, link_address: u32) -> Result<Option<Telegram101>, Error> {
+        let deadline = self.clock.now() + self.response_timeout;
+        loop {
+            let now = self.clock.now();
+            if now >= deadline {
+                return Ok(None);
+            }
+            let Some(reply) = self.link.recv(deadline - now)? else {
+                return Ok(None);
+            };
+            if reply.is_ack_only() {
+                return Ok(Some(reply));
+            }
+            // skip frames of other primary stations and replies of other secondaries
+            if reply.is_prm() || reply.link_address() != link_address {
+                continue;
+            }
+            return Ok(Some(reply));
+        }
+    }
+}
+
+/// Is the secondary station reply a positive acknowledgement
+pub(crate) fn is_ack(reply: &Telegram101) -> bool {
+    if reply.is_ack_only() {
+        !reply.is_negative()
+    } else {
+        reply.is_fixed() && reply.function_code() == function_code::ACK
+    }
+}
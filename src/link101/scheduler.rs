@@ -0,0 +1,948 @@
+use std::{
+    io,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    Error,
+    clock::{Clock, SystemClock},
+    clock_sync::{sending_delay_time, transmission_delay},
+    telegram101::Telegram101,
+    types::{
+        COT,
+        datatype::{C_CD_NA_1, C_CS_NA_1, DataType},
+        time::{CP16Time2a, CP56Time2a, TimePolicy},
+    },
+};
+
+use super::{Link101, Primary101, StationLink, function_code, primary::is_ack};
+
+const DEFAULT_MAX_FAILURES: u32 = 3;
+const DEFAULT_REPROBE_INTERVAL: Duration = Duration::from_secs(30);
+const STATION_SYNC_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Polled station state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StationState {
+    /// The link has not been reset yet
+    Init,
+    /// The station is polled
+    Online,
+    /// The station is taken out of the poll cycle until the next re-probe
+    Dead,
+}
+
+/// Polled station counters
+#[derive(Debug, Clone, Default)]
+pub struct StationStats {
+    /// Completed exchanges
+    pub requests: u64,
+    /// Repeated transmissions
+    pub retries: u64,
+    /// Failed exchanges
+    pub failures: u64,
+    /// Failed exchanges in a row
+    pub consecutive_failures: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum SyncState {
+    Idle,
+    // waiting for the delay acquisition confirmation
+    Delay,
+    // waiting for the clock synchronization confirmation, the transmission delay
+    Sync(Duration),
+}
+
+/// Clock synchronization of a single station with delay acquisition
+#[derive(Debug, Clone)]
+struct StationSync {
+    ca: u16,
+    interval: Duration,
+    state: SyncState,
+    next: Instant,
+    deadline: Instant,
+}
+
+impl StationSync {
+    fn reschedule(&mut self, now: Instant) {
+        self.state = SyncState::Idle;
+        self.next = now + self.interval;
+    }
+}
+
+/// A secondary station in the poll cycle
+#[derive(Debug, Clone)]
+pub struct PolledStation {
+    link: StationLink,
+    class_2_cycle: Duration,
+    state: StationState,
+    acd: bool,
+    next_class_2: Instant,
+    next_probe: Instant,
+    sync: Option<StationSync>,
+    stats: StationStats,
+}
+
+impl PolledStation {
+    /// Link address
+    pub fn link_address(&self) -> u32 {
+        self.link.link_address()
+    }
+    /// Class 2 poll cycle
+    pub fn class_2_cycle(&self) -> Duration {
+        self.class_2_cycle
+    }
+    /// Station state
+    pub fn state(&self) -> StationState {
+        self.state
+    }
+    /// Has the station signalled available class 1 data (ACD bit)
+    pub fn acd(&self) -> bool {
+        self.acd
+    }
+    /// Station counters
+    pub fn stats(&self) -> StationStats {
+        let mut stats = self.stats.clone();
+        stats.retries = self.link.retries();
+        stats
+    }
+}
+
+/// Poll scheduler events
+#[derive(Debug)]
+pub enum PollEvent {
+    /// User data received from a station
+    Data {
+        /// Station link address
+        link_address: u32,
+        /// Received telegram
+        telegram: Telegram101,
+    },
+    /// The station link has been reset, the station is polled
+    Online(u32),
+    /// The station does not respond and is taken out of the poll cycle
+    Dead(u32),
+    /// An exchange with the station failed
+    Failed {
+        /// Station link address
+        link_address: u32,
+        /// The error
+        error: Error,
+    },
+    /// Broadcast clock synchronization has been sent
+    TimeSync,
+    /// The station has confirmed the clock synchronization
+    StationTimeSync {
+        /// Station link address
+        link_address: u32,
+        /// Transmission delay, acquired before the synchronization
+        delay: Duration,
+    },
+}
+
+/// Multi-drop poll scheduler for IEC 60870-5-101 unbalanced lines
+///
+/// Stations are initialized with the request status of link and reset of remote link
+/// procedures, then polled for class 2 data with their own cycle times. Stations with the ACD
+/// bit set are polled for class 1 data with priority. After several failed exchanges in a row,
+/// a station is taken out of the poll cycle and re-probed periodically.
+pub struct PollScheduler101<L: Link101> {
+    primary: Primary101<L>,
+    stations: Vec<PolledStation>,
+    next: usize,
+    max_failures: u32,
+    reprobe_interval: Duration,
+    time_sync_interval: Option<Duration>,
+    // None: due immediately
+    next_time_sync: Option<Instant>,
+    clock: Arc<dyn Clock>,
+    policy: TimePolicy,
+}
+
+impl<L: Link101> PollScheduler101<L> {
+    /// Create a new scheduler
+    pub fn new(primary: Primary101<L>) -> Self {
+        Self {
+            primary,
+            stations: <_>::default(),
+            next: 0,
+            max_failures: DEFAULT_MAX_FAILURES,
+            reprobe_interval: DEFAULT_REPROBE_INTERVAL,
+            time_sync_interval: None,
+            next_time_sync: None,
+            clock: Arc::new(SystemClock),
+            policy: TimePolicy::default(),
+        }
+    }
+    /// Set the clock (poll cycles, re-probes, time synchronization; default: system clock)
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    /// Set the time zone policy of the sent clock synchronization time (default: UTC)
+    pub fn with_policy(mut self, policy: TimePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+    /// Set the number of failed exchanges in a row after which a station is taken out of the
+    /// poll cycle (default: 3)
+    pub fn with_max_failures(mut self, max_failures: u32) -> Self {
+        self.max_failures = max_failures.max(1);
+        self
+    }
+    /// Set the re-probe interval of dead stations (default: 30 seconds)
+    pub fn with_reprobe_interval(mut self, reprobe_interval: Duration) -> Self {
+        self.reprobe_interval = reprobe_interval;
+        self
+    }
+    /// Enable periodic broadcast clock synchronization
+    pub fn with_time_sync_interval(mut self, time_sync_interval: Duration) -> Self {
+        self.time_sync_interval = Some(time_sync_interval);
+        self.next_time_sync = None;
+        self
+    }
+    /// Add a station to the poll cycle
+    pub fn add_station(&mut self, link_address: u32, class_2_cycle: Duration) -> Result<(), Error> {
+        if self.primary.link().config().is_broadcast(link_address) {
+            return Err(Error::invalid_config(
+                "broadcast link address can not be polled",
+            ));
+        }
+        if self.station(link_address).is_some() {
+            return Err(Error::invalid_config(format!(
+                "duplicate link address: {}",
+                link_address
+            )));
+        }
+        let now = self.clock.now();
+        self.stations.push(PolledStation {
+            link: StationLink::new(link_address),
+            class_2_cycle,
+            state: StationState::Init,
+            acd: false,
+            next_class_2: now,
+            next_probe: now,
+            sync: None,
+            stats: StationStats::default(),
+        });
+        Ok(())
+    }
+    /// Enable periodic clock synchronization of a station (common address), preceded by the
+    /// delay acquisition procedure (C_CD_NA_1) to compensate for the transmission delay
+    pub fn set_station_time_sync(
+        &mut self,
+        link_address: u32,
+        ca: u16,
+        interval: Duration,
+    ) -> Result<(), Error> {
+        let now = self.clock.now();
+        let Some(station) = self
+            .stations
+            .iter_mut()
+            .find(|s| s.link_address() == link_address)
+        else {
+            return Err(Error::invalid_config(format!(
+                "unknown link address: {}",
+                link_address
+            )));
+        };
+        station.sync = Some(StationSync {
+            ca,
+            interval,
+            state: SyncState::Idle,
+            next: now,
+            deadline: now,
+        });
+        Ok(())
+    }
+    /// Remove a station from the poll cycle
+    pub fn remove_station(&mut self, link_address: u32) -> Option<PolledStation> {
+        let pos = self
+            .stations
+            .iter()
+            .position(|s| s.link_address() == link_address)?;
+        Some(self.stations.remove(pos))
+    }
+    /// Get a station
+    pub fn station(&self, link_address: u32) -> Option<&PolledStation> {
+        self.stations
+            .iter()
+            .find(|s| s.link_address() == link_address)
+    }
+    /// All stations
+    pub fn stations(&self) -> &[PolledStation] {
+        &self.stations
+    }
+    /// The primary station
+    pub fn primary(&self) -> &Primary101<L> {
+        &self.primary
+    }
+    /// The primary station (mutable), e.g. to send commands between the poll cycles
+    pub fn primary_mut(&mut self) -> &mut Primary101<L> {
+        &mut self.primary
+    }
+    /// Send a clock synchronization command to all stations (broadcast link and common
+    /// addresses)
+    pub fn broadcast_time_sync(&mut self, time: CP56Time2a) -> Result<(), Error> {
+        let config = self.primary.link().config();
+        let Some(link_address) = config.broadcast_link_address() else {
+            return Err(Error::invalid_config("no broadcast link address"));
+        };
+        let mut telegram =
+            Telegram101::new(DataType::C_CS_NA_1, COT::Act, config.broadcast_ca(), config);
+        telegram.append_iou(0, C_CS_NA_1 { time });
+        self.primary.send_no_reply(link_address, telegram)
+    }
+    /// The time the next action is due (None if the scheduler has no stations)
+    pub fn next_due(&self) -> Option<Instant> {
+        let now = self.clock.now();
+        let mut due = self
+            .time_sync_interval
+            .map(|_| self.next_time_sync.unwrap_or(now));
+        for station in &self.stations {
+            let station_due = match station.state {
+                StationState::Init => now,
+                StationState::Online if station.acd => now,
+                StationState::Online => match &station.sync {
+                    Some(sync) if matches!(sync.state, SyncState::Idle) => {
+                        station.next_class_2.min(sync.next)
+                    }
+                    Some(sync) => station.next_class_2.min(sync.deadline),
+                    None => station.next_class_2,
+                },
+                StationState::Dead => station.next_probe,
+            };
+            due = Some(due.map_or(station_due, |d| d.min(station_due)));
+        }
+        due
+    }
+    /// Perform the next due action (at most a single poll exchange)
+    ///
+    /// Returns an empty vector if nothing is due.
+    pub fn run_once(&mut self) -> Vec<PollEvent> {
+        let now = self.clock.now();
+        if let Some(interval) = self.time_sync_interval
+            && self.next_time_sync.is_none_or(|t| t <= now)
+        {
+            self.next_time_sync = Some(now + interval);
+            return match CP56Time2a::now(&*self.clock, &self.policy)
+                .and_then(|time| self.broadcast_time_sync(time))
+            {
+                Ok(()) => vec![PollEvent::TimeSync],
+                Err(error) => vec![PollEvent::Failed {
+                    link_address: self
+                        .primary
+                        .link()
+                        .config()
+                        .broadcast_link_address()
+                        .unwrap_or_default(),
+                    error,
+                }],
+            };
+        }
+        if let Some(events) = self.run_station_sync(now) {
+            return events;
+        }
+        let Some((idx, action)) = self.select(now) else {
+            return Vec::new();
+        };
+        self.next = (idx + 1) % self.stations.len();
+        match action {
+            Action::Init => self.init_station(idx),
+            Action::Class1 => self.poll_station(idx, true),
+            Action::Class2 => self.poll_station(idx, false),
+        }
+    }
+    /// Starts the delay acquisition of a station if due, reports expired synchronizations
+    fn run_station_sync(&mut self, now: Instant) -> Option<Vec<PollEvent>> {
+        let config = self.primary.link().config();
+        for station in &mut self.stations {
+            if station.state != StationState::Online {
+                continue;
+            }
+            let link_address = station.link_address();
+            let Some(sync) = &mut station.sync else {
+                continue;
+            };
+            match sync.state {
+                SyncState::Idle if sync.next <= now => {
+                    let sdt = sending_delay_time(self.clock.timestamp());
+                    let mut telegram =
+                        Telegram101::new(DataType::C_CD_NA_1, COT::Act, sync.ca, config);
+                    telegram.append_iou(0, C_CD_NA_1 { time: sdt });
+                    return Some(
+                        match self.primary.send_confirm(&mut station.link, telegram) {
+                            Ok(reply) => {
+                                station.acd |= !reply.is_ack_only() && reply.fcb_acd();
+                                sync.state = SyncState::Delay;
+                                sync.deadline = now + STATION_SYNC_TIMEOUT;
+                                Vec::new()
+                            }
+                            Err(error) => {
+                                sync.reschedule(now);
+                                vec![PollEvent::Failed {
+                                    link_address,
+                                    error,
+                                }]
+                            }
+                        },
+                    );
+                }
+                SyncState::Delay | SyncState::Sync(_) if sync.deadline <= now => {
+                    sync.reschedule(now);
+                    return Some(vec![PollEvent::Failed {
+                        link_address,
+                        error: io::Error::from(io::ErrorKind::TimedOut).into(),
+                    }]);
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+    /// Handles delay acquisition and clock synchronization confirmations of a station
+    ///
+    /// Returns None if the telegram is not a part of the procedure.
+    fn station_sync_reply(&mut self, idx: usize, reply: &Telegram101) -> Option<Vec<PollEvent>> {
+        let now = self.clock.now();
+        let config = self.primary.link().config();
+        let station = &mut self.stations[idx];
+        let link_address = station.link_address();
+        let sync = station.sync.as_mut()?;
+        if reply.adsu() != sync.ca || reply.cot() != COT::ActCon {
+            return None;
+        }
+        let [object] = reply.iou() else {
+            return None;
+        };
+        match (reply.data_type(), sync.state) {
+            (DataType::C_CD_NA_1, SyncState::Delay) => {
+                if reply.is_negative() {
+                    sync.reschedule(now);
+                    return Some(vec![PollEvent::Failed {
+                        link_address,
+                        error: Error::invalid_data("delay acquisition rejected"),
+                    }]);
+                }
+                let delay = transmission_delay(
+                    C_CD_NA_1::from(object.value()).time,
+                    self.clock.timestamp(),
+                );
+                let mut report =
+                    Telegram101::new(DataType::C_CD_NA_1, COT::Spontan, sync.ca, config);
+                report.append_iou(
+                    0,
+                    C_CD_NA_1 {
+                        time: CP16Time2a::try_from(delay).unwrap_or_default(),
+                    },
+                );
+                let result = self
+                    .primary
+                    .send_confirm(&mut station.link, report)
+                    .and_then(|_| CP56Time2a::now(&*self.clock, &self.policy))
+                    .and_then(|time| {
+                        let mut telegram =
+                            Telegram101::new(DataType::C_CS_NA_1, COT::Act, sync.ca, config);
+                        telegram.append_iou(0, C_CS_NA_1 { time });
+                        self.primary.send_confirm(&mut station.link, telegram)
+                    });
+                Some(match result {
+                    Ok(ack) => {
+                        station.acd |= !ack.is_ack_only() && ack.fcb_acd();
+                        sync.state = SyncState::Sync(delay);
+                        sync.deadline = now + STATION_SYNC_TIMEOUT;
+                        Vec::new()
+                    }
+                    Err(error) => {
+                        sync.reschedule(now);
+                        vec![PollEvent::Failed {
+                            link_address,
+                            error,
+                        }]
+                    }
+                })
+            }
+            (DataType::C_CS_NA_1, SyncState::Sync(delay)) => {
+                sync.reschedule(now);
+                Some(vec![if reply.is_negative() {
+                    PollEvent::Failed {
+                        link_address,
+                        error: Error::invalid_data("clock synchronization rejected"),
+                    }
+                } else {
+                    PollEvent::StationTimeSync {
+                        link_address,
+                        delay,
+                    }
+                }])
+            }
+            _ => None,
+        }
+    }
+    fn select(&self, now: Instant) -> Option<(usize, Action)> {
+        let len = self.stations.len();
+        let order = || (0..len).map(|i| (self.next + i) % len);
+        if let Some(idx) = order()
+            .find(|&i| self.stations[i].state == StationState::Online && self.stations[i].acd)
+        {
+            return Some((idx, Action::Class1));
+        }
+        if let Some(idx) = order().find(|&i| {
+            let station = &self.stations[i];
+            station.state == StationState::Init
+                || (station.state == StationState::Dead && station.next_probe <= now)
+        }) {
+            return Some((idx, Action::Init));
+        }
+        order()
+            .find(|&i| {
+                self.stations[i].state == StationState::Online
+                    && self.stations[i].next_class_2 <= now
+            })
+            .map(|idx| (idx, Action::Class2))
+    }
+    fn init_station(&mut self, idx: usize) -> Vec<PollEvent> {
+        let station = &mut self.stations[idx];
+        let result = self
+            .primary
+            .request_link_status(&mut station.link)
+            .and_then(|_| self.primary.reset_remote_link(&mut station.link));
+        match result {
+            Ok(_) => {
+                station.stats.requests += 2;
+                station.stats.consecutive_failures = 0;
+                station.state = StationState::Online;
+                // fetch the initialization data (e.g. end of initialization) first
+                station.acd = true;
+                station.next_class_2 = self.clock.now();
+                vec![PollEvent::Online(station.link_address())]
+            }
+            Err(error) => {
+                station.stats.failures += 1;
+                station.stats.consecutive_failures += 1;
+                let link_address = station.link_address();
+                let mut events = vec![PollEvent::Failed {
+                    link_address,
+                    error,
+                }];
+                if station.state == StationState::Dead {
+                    station.next_probe = self.clock.now() + self.reprobe_interval;
+                } else if station.stats.consecutive_failures >= self.max_failures {
+                    station.state = StationState::Dead;
+                    station.next_probe = self.clock.now() + self.reprobe_interval;
+                    events.push(PollEvent::Dead(link_address));
+                }
+                events
+            }
+        }
+    }
+    fn poll_station(&mut self, idx: usize, class_1: bool) -> Vec<PollEvent> {
+        let station = &mut self.stations[idx];
+        let result = if class_1 {
+            self.primary.request_class_1(&mut station.link)
+        } else {
+            self.primary.request_class_2(&mut station.link)
+        };
+        if !class_1 {
+            station.next_class_2 = self.clock.now() + station.class_2_cycle;
+        }
+        let link_address = station.link_address();
+        match result {
+            Ok(reply) => {
+                station.stats.requests += 1;
+                station.stats.consecutive_failures = 0;
+                station.acd = !reply.is_ack_only() && reply.fcb_acd();
+                if reply.is_fixed() || reply.is_ack_only() {
+                    if !is_ack(&reply)
+                        && reply.function_code() != function_code::NACK_NO_DATA
+                        && reply.function_code() != function_code::LINK_STATUS
+                    {
+                        // the secondary station has lost the link state, reset it
+                        station.state = StationState::Init;
+                    }
+                    Vec::new()
+                } else if let Some(events) = self.station_sync_reply(idx, &reply) {
+                    events
+                } else {
+                    vec![PollEvent::Data {
+                        link_address,
+                        telegram: reply,
+                    }]
+                }
+            }
+            Err(error) => {
+                station.stats.failures += 1;
+                station.stats.consecutive_failures += 1;
+                let mut events = vec![PollEvent::Failed {
+                    link_address,
+                    error,
+                }];
+                if station.stats.consecutive_failures >= self.max_failures {
+                    station.state = StationState::Dead;
+                    station.acd = false;
+                    station.next_probe = self.clock.now() + self.reprobe_interval;
+                    events.push(PollEvent::Dead(link_address));
+                }
+                events
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Action {
+    Init,
+    Class1,
+    Class2,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::VecDeque,
+        sync::Arc,
+        time::{Duration, Instant},
+    };
+
+    use super::{PollEvent, PollScheduler101, StationState};
+    use crate::{
+        Error,
+        clock::ManualClock,
+        link101::{Link101, Primary101, function_code},
+        telegram101::{Config, Telegram101},
+        types::{
+            COT,
+            datatype::{C_CD_NA_1, C_CS_NA_1, DataType, M_SP_NA_1},
+            time::{CP16Time2a, CP56Time2a, TimePolicy},
+        },
+    };
+
+    /// Simulated secondary station
+    struct Outstation {
+        link_address: u32,
+        alive: bool,
+        expected_fcb: Option<bool>,
+        class_1: VecDeque<Telegram101>,
+        class_2: VecDeque<Telegram101>,
+        broadcasts: usize,
+        delay: Option<CP16Time2a>,
+        synced: Option<CP56Time2a>,
+    }
+
+    impl Outstation {
+        fn new(link_address: u32) -> Self {
+            Self {
+                link_address,
+                alive: true,
+                expected_fcb: None,
+                class_1: VecDeque::new(),
+                class_2: VecDeque::new(),
+                broadcasts: 0,
+                delay: None,
+                synced: None,
+            }
+        }
+        /// Clock synchronization and delay acquisition, confirmations are class 1 data
+        fn user_data(&mut self, frame: &Telegram101) {
+            match (frame.data_type(), frame.cot()) {
+                (DataType::C_CD_NA_1 | DataType::C_CS_NA_1, COT::Act) => {
+                    if frame.data_type() == DataType::C_CS_NA_1 {
+                        self.synced = Some(C_CS_NA_1::from(frame.iou()[0].value()).time);
+                    }
+                    self.class_1.push_back(
+                        Telegram101::new(
+                            frame.data_type(),
+                            COT::ActCon,
+                            frame.adsu(),
+                            Config::new(),
+                        )
+                        .with_iou(frame.iou().to_vec()),
+                    );
+                }
+                (DataType::C_CD_NA_1, COT::Spontan) => {
+                    self.delay = Some(C_CD_NA_1::from(frame.iou()[0].value()).time);
+                }
+                _ => {}
+            }
+        }
+        fn data(adsu: u16) -> Telegram101 {
+            let mut telegram =
+                Telegram101::new(DataType::M_SP_NA_1, COT::Spontan, adsu, Config::new());
+            telegram.append_iou(1, M_SP_NA_1::default());
+            telegram
+        }
+        fn reply(&mut self, frame: &Telegram101) -> Option<Telegram101> {
+            if frame.function_code() == function_code::USER_DATA_NO_REPLY {
+                self.broadcasts += 1;
+                return None;
+            }
+            if !self.alive || frame.link_address() != self.link_address {
+                return None;
+            }
+            if frame.fcv_dfc() {
+                let expected = self.expected_fcb?;
+                if frame.fcb_acd() != expected {
+                    // repeated frame, a real station would repeat the last reply
+                    return None;
+                }
+                self.expected_fcb = Some(!expected);
+            }
+            let link_address = self.link_address;
+            let fixed = |fc| {
+                Telegram101::new_fixed(Config::new())
+                    .with_function_code(fc)
+                    .with_link_address(link_address)
+            };
+            let reply = match frame.function_code() {
+                function_code::REQUEST_LINK_STATUS => fixed(function_code::LINK_STATUS),
+                function_code::RESET_REMOTE_LINK => {
+                    self.expected_fcb = Some(true);
+                    fixed(function_code::ACK)
+                }
+                function_code::USER_DATA_CONFIRM => {
+                    self.user_data(frame);
+                    fixed(function_code::ACK)
+                }
+                function_code::REQUEST_CLASS_1 => self.class_1.pop_front().map_or_else(
+                    || fixed(function_code::NACK_NO_DATA),
+                    |t| {
+                        t.with_function_code(function_code::USER_DATA)
+                            .with_link_address(self.link_address)
+                    },
+                ),
+                function_code::REQUEST_CLASS_2 => self.class_2.pop_front().map_or_else(
+                    || fixed(function_code::NACK_NO_DATA),
+                    |t| {
+                        t.with_function_code(function_code::USER_DATA)
+                            .with_link_address(self.link_address)
+                    },
+                ),
+                _ => fixed(function_code::LINK_NOT_IMPLEMENTED),
+            };
+            Some(reply.with_fcb_acd(!self.class_1.is_empty()))
+        }
+    }
+
+    /// In-memory multi-drop bus, stations reply synchronously
+    struct Bus {
+        stations: Vec<Outstation>,
+        rx: VecDeque<Telegram101>,
+        sent: usize,
+    }
+
+    impl Link101 for Bus {
+        fn config(&self) -> Config {
+            Config::new()
+        }
+        fn send(&mut self, telegram: &Telegram101) -> Result<(), Error> {
+            self.sent += 1;
+            for station in &mut self.stations {
+                if let Some(reply) = station.reply(telegram) {
+                    self.rx.push_back(reply);
+                }
+            }
+            Ok(())
+        }
+        fn recv(&mut self, _timeout: Duration) -> Result<Option<Telegram101>, Error> {
+            Ok(self.rx.pop_front())
+        }
+    }
+
+    fn scheduler(stations: Vec<Outstation>) -> PollScheduler101<Bus> {
+        let bus = Bus {
+            stations,
+            rx: VecDeque::new(),
+            sent: 0,
+        };
+        PollScheduler101::new(
+            Primary101::new(bus)
+                .with_retries(1)
+                .with_response_timeout(Duration::from_millis(1)),
+        )
+    }
+
+    fn run(scheduler: &mut PollScheduler101<Bus>, n: usize) -> Vec<PollEvent> {
+        (0..n).flat_map(|_| scheduler.run_once()).collect()
+    }
+
+    #[test]
+    fn scheduler_init_and_poll() {
+        let mut scheduler = scheduler(vec![Outstation::new(1), Outstation::new(2)]);
+        scheduler.add_station(1, Duration::ZERO).unwrap();
+        scheduler.add_station(2, Duration::ZERO).unwrap();
+        assert!(scheduler.add_station(2, Duration::ZERO).is_err());
+        assert!(scheduler.add_station(0xFF, Duration::ZERO).is_err());
+        let events = run(&mut scheduler, 4);
+        assert!(matches!(events[0], PollEvent::Online(1)));
+        assert!(events.iter().any(|e| matches!(e, PollEvent::Online(2))));
+        assert_eq!(scheduler.station(1).unwrap().state(), StationState::Online);
+        let data = Outstation::data(20);
+        scheduler.primary_mut().link_mut().stations[1]
+            .class_2
+            .push_back(data);
+        let events = run(&mut scheduler, 6);
+        assert_eq!(events.len(), 1);
+        let PollEvent::Data {
+            link_address,
+            telegram,
+        } = &events[0]
+        else {
+            panic!("data expected");
+        };
+        assert_eq!(*link_address, 2);
+        assert_eq!(telegram.adsu(), 20);
+        assert_eq!(scheduler.station(2).unwrap().stats().failures, 0);
+    }
+
+    #[test]
+    fn scheduler_class_1_priority() {
+        let mut scheduler = scheduler(vec![Outstation::new(1), Outstation::new(2)]);
+        scheduler.add_station(1, Duration::from_secs(60)).unwrap();
+        scheduler.add_station(2, Duration::from_secs(60)).unwrap();
+        run(&mut scheduler, 6);
+        // station 2 has two class 1 messages, signalled via ACD in the class 2 reply
+        let bus = scheduler.primary_mut().link_mut();
+        let (d1, d2, d3) = (
+            Outstation::data(1),
+            Outstation::data(2),
+            Outstation::data(3),
+        );
+        bus.stations[1].class_1.push_back(d1);
+        bus.stations[1].class_1.push_back(d2);
+        bus.stations[1].class_2.push_back(d3);
+        scheduler.stations[1].next_class_2 = Instant::now();
+        let events = run(&mut scheduler, 3);
+        let adsu: Vec<u16> = events
+            .iter()
+            .filter_map(|e| match e {
+                PollEvent::Data { telegram, .. } => Some(telegram.adsu()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(adsu, [3, 1, 2]);
+        assert!(!scheduler.station(2).unwrap().acd());
+        assert!(scheduler.run_once().is_empty());
+    }
+
+    #[test]
+    fn scheduler_dead_station() {
+        let clock = Arc::new(ManualClock::default());
+        let mut scheduler = scheduler(vec![Outstation::new(1), Outstation::new(2)])
+            .with_clock(clock.clone())
+            .with_max_failures(2)
+            .with_reprobe_interval(Duration::from_secs(3600));
+        scheduler.add_station(1, Duration::ZERO).unwrap();
+        scheduler.add_station(2, Duration::ZERO).unwrap();
+        run(&mut scheduler, 6);
+        scheduler.primary_mut().link_mut().stations[0].alive = false;
+        let events = run(&mut scheduler, 4);
+        assert!(events.iter().any(|e| matches!(e, PollEvent::Dead(1))));
+        let station = scheduler.station(1).unwrap();
+        assert_eq!(station.state(), StationState::Dead);
+        assert_eq!(station.stats().failures, 2);
+        // each failed exchange has been repeated once
+        assert_eq!(station.stats().retries, 2);
+        assert_eq!(scheduler.station(2).unwrap().stats().failures, 0);
+        // the station is back, re-probed
+        scheduler.primary_mut().link_mut().stations[0].alive = true;
+        assert!(
+            run(&mut scheduler, 2)
+                .iter()
+                .all(|e| !matches!(e, PollEvent::Online(1)))
+        );
+        clock.advance(Duration::from_secs(3600));
+        let events = run(&mut scheduler, 2);
+        assert!(events.iter().any(|e| matches!(e, PollEvent::Online(1))));
+        assert_eq!(
+            scheduler.station(1).unwrap().stats().consecutive_failures,
+            0
+        );
+    }
+
+    #[test]
+    fn scheduler_init_failures() {
+        let mut scheduler = scheduler(vec![Outstation::new(1)]).with_max_failures(3);
+        scheduler.primary_mut().link_mut().stations[0].alive = false;
+        scheduler.add_station(1, Duration::ZERO).unwrap();
+        for _ in 0..2 {
+            let events = scheduler.run_once();
+            assert!(matches!(events[..], [PollEvent::Failed { .. }]));
+            assert_eq!(scheduler.station(1).unwrap().state(), StationState::Init);
+        }
+        let events = scheduler.run_once();
+        assert!(matches!(
+            events[..],
+            [PollEvent::Failed { .. }, PollEvent::Dead(1)]
+        ));
+        assert_eq!(scheduler.station(1).unwrap().state(), StationState::Dead);
+        assert_eq!(scheduler.station(1).unwrap().stats().failures, 3);
+    }
+
+    #[test]
+    fn scheduler_time_sync() {
+        let mut scheduler = scheduler(vec![Outstation::new(1), Outstation::new(2)])
+            .with_time_sync_interval(Duration::from_secs(3600));
+        scheduler.add_station(1, Duration::ZERO).unwrap();
+        let events = scheduler.run_once();
+        assert!(matches!(events[0], PollEvent::TimeSync));
+        let bus = scheduler.primary().link();
+        assert_eq!(bus.stations[0].broadcasts, 1);
+        assert_eq!(bus.stations[1].broadcasts, 1);
+        assert!(matches!(scheduler.run_once()[0], PollEvent::Online(1)));
+    }
+
+    #[test]
+    fn scheduler_station_time_sync() {
+        let clock = Arc::new(ManualClock::default());
+        let mut scheduler =
+            scheduler(vec![Outstation::new(1), Outstation::new(2)]).with_clock(clock.clone());
+        scheduler.add_station(1, Duration::from_secs(60)).unwrap();
+        scheduler.add_station(2, Duration::from_secs(60)).unwrap();
+        assert!(
+            scheduler
+                .set_station_time_sync(3, 1, Duration::from_secs(3600))
+                .is_err()
+        );
+        scheduler
+            .set_station_time_sync(1, 10, Duration::from_secs(3600))
+            .unwrap();
+        let events = run(&mut scheduler, 10);
+        let synced: Vec<&PollEvent> = events
+            .iter()
+            .filter(|e| matches!(e, PollEvent::StationTimeSync { .. }))
+            .collect();
+        assert!(matches!(
+            synced[..],
+            [PollEvent::StationTimeSync {
+                link_address: 1,
+                delay: Duration::ZERO
+            }]
+        ));
+        // the confirmations have been intercepted
+        assert!(
+            events
+                .iter()
+                .all(|e| !matches!(e, PollEvent::Data { .. } | PollEvent::Failed { .. }))
+        );
+        let bus = scheduler.primary().link();
+        assert_eq!(bus.stations[0].delay, Some(CP16Time2a { ms: 0 }));
+        assert_eq!(bus.stations[1].delay, None);
+        // UTC by default, as the 104 clock synchronization
+        assert_eq!(
+            bus.stations[0].synced,
+            Some(CP56Time2a::now(&*clock, &TimePolicy::Utc).unwrap())
+        );
+        assert!(run(&mut scheduler, 4).is_empty());
+        clock.advance(Duration::from_secs(3600));
+        let events = run(&mut scheduler, 10);
+        assert!(events.iter().any(|e| matches!(
+            e,
+            PollEvent::StationTimeSync {
+                link_address: 1,
+                ..
+            }
+        )));
+    }
+}
@@ -0,0 +1,464 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Read, Write},
+    os::{fd::OwnedFd, unix::fs::OpenOptionsExt},
+    path::Path,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use nix::{
+    libc,
+    poll::{PollFd, PollFlags, poll},
+    sys::termios::{self, BaudRate, ControlFlags, SetArg, SpecialCharacterIndices},
+};
+
+use crate::{
+    Error,
+    clock::{Clock, SystemClock},
+    telegram101::{Config, Telegram101},
+};
+
+use super::Link101;
+
+/// FT1.2 minimum line idle interval between frames, in bits
+const LINE_IDLE_BITS: u32 = 33;
+
+/// Default maximum line idle time between characters of a frame
+///
+/// FT1.2 does not allow idle intervals within a frame. The default value tolerates typical OS
+/// and USB-serial adapter latencies.
+const DEFAULT_INTER_CHAR_TIMEOUT: Duration = Duration::from_millis(20);
+
+/// Serial line parity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Parity {
+    /// No parity bit
+    None,
+    /// Even parity (FT1.2 default)
+    #[default]
+    Even,
+    /// Odd parity
+    Odd,
+}
+
+/// Serial line stop bits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StopBits {
+    /// One stop bit (FT1.2 default)
+    #[default]
+    One,
+    /// Two stop bits
+    Two,
+}
+
+/// Serial line configuration
+/// Defaults: baud_rate = 9600, 8 data bits, parity = even, stop_bits = one,
+/// inter_char_timeout = 20ms, line idle interval = 33 bits
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Clone)]
+pub struct SerialConfig {
+    baud_rate: u32,
+    parity: Parity,
+    stop_bits: StopBits,
+    inter_char_timeout: Duration,
+    line_idle_bits: u32,
+}
+
+impl Default for SerialConfig {
+    fn default() -> Self {
+        Self {
+            baud_rate: 9600,
+            parity: Parity::Even,
+            stop_bits: StopBits::One,
+            inter_char_timeout: DEFAULT_INTER_CHAR_TIMEOUT,
+            line_idle_bits: LINE_IDLE_BITS,
+        }
+    }
+}
+
+impl SerialConfig {
+    /// Create a new serial line configuration
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Set the baud rate
+    pub fn with_baud_rate(mut self, baud_rate: u32) -> Self {
+        self.baud_rate = baud_rate;
+        self
+    }
+    /// Set the parity
+    pub fn with_parity(mut self, parity: Parity) -> Self {
+        self.parity = parity;
+        self
+    }
+    /// Set the stop bits
+    pub fn with_stop_bits(mut self, stop_bits: StopBits) -> Self {
+        self.stop_bits = stop_bits;
+        self
+    }
+    /// Set the maximum line idle time between characters of a frame
+    pub fn with_inter_char_timeout(mut self, inter_char_timeout: Duration) -> Self {
+        self.inter_char_timeout = inter_char_timeout;
+        self
+    }
+    /// Set the minimum line idle interval between frames, in bits
+    pub fn with_line_idle_bits(mut self, line_idle_bits: u32) -> Self {
+        self.line_idle_bits = line_idle_bits;
+        self
+    }
+    /// Baud rate
+    pub fn baud_rate(&self) -> u32 {
+        self.baud_rate
+    }
+    /// Parity
+    pub fn parity(&self) -> Parity {
+        self.parity
+    }
+    /// Stop bits
+    pub fn stop_bits(&self) -> StopBits {
+        self.stop_bits
+    }
+    /// Maximum line idle time between characters of a frame
+    pub fn inter_char_timeout(&self) -> Duration {
+        self.inter_char_timeout
+    }
+    /// Minimum line idle interval between frames
+    pub fn line_idle_interval(&self) -> Duration {
+        self.bits_duration(self.line_idle_bits)
+    }
+    /// Transmission time of a single character (start, data, parity and stop bits)
+    pub fn char_time(&self) -> Duration {
+        let mut bits = 1 + 8;
+        if self.parity != Parity::None {
+            bits += 1;
+        }
+        bits += match self.stop_bits {
+            StopBits::One => 1,
+            StopBits::Two => 2,
+        };
+        self.bits_duration(bits)
+    }
+    fn bits_duration(&self, bits: u32) -> Duration {
+        Duration::from_nanos(u64::from(bits) * 1_000_000_000 / u64::from(self.baud_rate.max(1)))
+    }
+    fn baud_rate_termios(&self) -> Result<BaudRate, Error> {
+        Ok(match self.baud_rate {
+            110 => BaudRate::B110,
+            300 => BaudRate::B300,
+            600 => BaudRate::B600,
+            1200 => BaudRate::B1200,
+            2400 => BaudRate::B2400,
+            4800 => BaudRate::B4800,
+            9600 => BaudRate::B9600,
+            19200 => BaudRate::B19200,
+            38400 => BaudRate::B38400,
+            57600 => BaudRate::B57600,
+            115_200 => BaudRate::B115200,
+            v => {
+                return Err(Error::invalid_config(format!(
+                    "unsupported baud rate: {}",
+                    v
+                )));
+            }
+        })
+    }
+}
+
+/// IEC 60870-5-101 serial line link (FT1.2 framing on a tty)
+///
+/// The link enforces the minimum line idle interval before each transmitted frame and the
+/// maximum idle time between characters of a received frame. After a reception error the
+/// receiver discards characters until the line is idle for the line idle interval.
+#[derive(Debug)]
+pub struct SerialLink101 {
+    port: File,
+    serial_config: SerialConfig,
+    config: Config,
+    last_activity: Option<Instant>,
+    clock: Arc<dyn Clock>,
+}
+
+impl SerialLink101 {
+    /// Open a serial port and configure it
+    pub fn open(
+        path: impl AsRef<Path>,
+        serial_config: SerialConfig,
+        config: Config,
+    ) -> Result<Self, Error> {
+        let port = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_NOCTTY)
+            .open(path)?;
+        Self::from_fd(port.into(), serial_config, config)
+    }
+    /// Create a link from an already opened tty file descriptor and configure it
+    pub fn from_fd(
+        fd: OwnedFd,
+        serial_config: SerialConfig,
+        config: Config,
+    ) -> Result<Self, Error> {
+        let port = File::from(fd);
+        let mut tio = termios::tcgetattr(&port).map_err(io::Error::from)?;
+        termios::cfmakeraw(&mut tio);
+        termios::cfsetspeed(&mut tio, serial_config.baud_rate_termios()?)
+            .map_err(io::Error::from)?;
+        tio.control_flags &= !(ControlFlags::CSIZE
+            | ControlFlags::PARENB
+            | ControlFlags::PARODD
+            | ControlFlags::CSTOPB
+            | ControlFlags::CRTSCTS);
+        tio.control_flags |= ControlFlags::CS8 | ControlFlags::CLOCAL | ControlFlags::CREAD;
+        match serial_config.parity {
+            Parity::None => {}
+            Parity::Even => tio.control_flags |= ControlFlags::PARENB,
+            Parity::Odd => tio.control_flags |= ControlFlags::PARENB | ControlFlags::PARODD,
+        }
+        if serial_config.stop_bits == StopBits::Two {
+            tio.control_flags |= ControlFlags::CSTOPB;
+        }
+        tio.control_chars[SpecialCharacterIndices::VMIN as usize] = 1;
+        tio.control_chars[SpecialCharacterIndices::VTIME as usize] = 0;
+        termios::tcsetattr(&port, SetArg::TCSANOW, &tio).map_err(io::Error::from)?;
+        termios::tcflush(&port, termios::FlushArg::TCIOFLUSH).map_err(io::Error::from)?;
+        Ok(Self {
+            port,
+            serial_config,
+            config,
+            last_activity: None,
+            clock: Arc::new(SystemClock),
+        })
+    }
+    /// Set the clock (line idle interval, default: system clock)
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    /// Serial line configuration
+    pub fn serial_config(&self) -> &SerialConfig {
+        &self.serial_config
+    }
+    /// Discard incoming characters until the line is idle for the line idle interval
+    fn resync(&mut self) -> Result<(), Error> {
+        let idle = self.serial_config.line_idle_interval();
+        let mut buf = [0u8; 64];
+        while wait_readable(&self.port, idle)? {
+            if (&self.port).read(&mut buf)? == 0 {
+                break;
+            }
+            self.last_activity = Some(self.clock.now());
+        }
+        Ok(())
+    }
+}
+
+impl Link101 for SerialLink101 {
+    fn config(&self) -> Config {
+        self.config
+    }
+    fn send(&mut self, telegram: &Telegram101) -> Result<(), Error> {
+        let mut buf = Vec::with_capacity(usize::from(self.config.max_frame_len()) + 6);
+        telegram.write(&mut buf)?;
+        if let Some(last_activity) = self.last_activity {
+            let idle = self.serial_config.line_idle_interval();
+            let elapsed = self.clock.now().saturating_duration_since(last_activity);
+            if elapsed < idle {
+                thread::sleep(idle - elapsed);
+            }
+        }
+        (&self.port).write_all(&buf)?;
+        termios::tcdrain(&self.port).map_err(io::Error::from)?;
+        self.last_activity = Some(self.clock.now());
+        Ok(())
+    }
+    fn recv(&mut self, timeout: Duration) -> Result<Option<Telegram101>, Error> {
+        let mut reader = FrameReader {
+            port: &self.port,
+            timeout,
+            inter_char_timeout: self.serial_config.inter_char_timeout,
+            started: false,
+            last_activity: None,
+            clock: &*self.clock,
+        };
+        let result = Telegram101::read(&mut reader, self.config);
+        let started = reader.started;
+        if let Some(last_activity) = reader.last_activity {
+            self.last_activity = Some(last_activity);
+        }
+        match result {
+            Ok(telegram) => Ok(Some(telegram)),
+            Err(Error::Io(e)) if e.kind() == io::ErrorKind::TimedOut => {
+                if started {
+                    self.resync()?;
+                    Err(Error::invalid_data("inter-character timeout"))
+                } else {
+                    Ok(None)
+                }
+            }
+            Err(e) => {
+                if started {
+                    self.resync()?;
+                }
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Reads a single frame, the first character is awaited up to `timeout`, the next ones up to
+/// `inter_char_timeout`
+struct FrameReader<'a> {
+    port: &'a File,
+    timeout: Duration,
+    inter_char_timeout: Duration,
+    started: bool,
+    last_activity: Option<Instant>,
+    clock: &'a dyn Clock,
+}
+
+impl Read for FrameReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let timeout = if self.started {
+            self.inter_char_timeout
+        } else {
+            self.timeout
+        };
+        if !wait_readable(self.port, timeout)? {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        let n = self.port.read(buf)?;
+        if n > 0 {
+            self.started = true;
+            self.last_activity = Some(self.clock.now());
+        }
+        Ok(n)
+    }
+}
+
+fn wait_readable(port: &File, timeout: Duration) -> io::Result<bool> {
+    let timeout_ms = i32::try_from(timeout.as_nanos().div_ceil(1_000_000)).unwrap_or(i32::MAX);
+    let mut fds = [PollFd::new(&port, PollFlags::POLLIN)];
+    loop {
+        match poll(&mut fds, timeout_ms) {
+            Ok(n) => return Ok(n > 0),
+            Err(nix::errno::Errno::EINTR) => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs::File,
+        io::{Read, Write},
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use nix::pty::openpty;
+
+    use super::{SerialConfig, SerialLink101};
+    use crate::{
+        link101::Link101,
+        telegram101::{Config, Telegram101},
+        types::{COT, datatype::DataType},
+    };
+
+    fn pair(serial_config: SerialConfig) -> (SerialLink101, File) {
+        let pty = openpty(None, None).unwrap();
+        let link = SerialLink101::from_fd(pty.slave, serial_config, Config::new()).unwrap();
+        (link, File::from(pty.master))
+    }
+
+    fn frame() -> Vec<u8> {
+        let mut telegram = Telegram101::new(DataType::M_SP_NA_1, COT::Spontan, 7, Config::new())
+            .with_link_address(3);
+        telegram.append_iou(100, [1u8; 12]);
+        let mut buf = Vec::new();
+        telegram.write(&mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn serial_recv() {
+        let (mut link, mut master) = pair(SerialConfig::new());
+        assert!(link.recv(Duration::from_millis(10)).unwrap().is_none());
+        master.write_all(&frame()).unwrap();
+        let telegram = link.recv(Duration::from_secs(1)).unwrap().unwrap();
+        assert_eq!(telegram.link_address(), 3);
+        assert_eq!(telegram.adsu(), 7);
+        assert_eq!(telegram.iou()[0].address(), 100);
+    }
+
+    #[test]
+    fn serial_send() {
+        let (mut link, mut master) = pair(SerialConfig::new());
+        let telegram = Telegram101::new_fixed(Config::new())
+            .with_prm(true)
+            .with_function_code(9)
+            .with_link_address(3);
+        link.send(&telegram).unwrap();
+        let mut buf = [0u8; 5];
+        master.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [0x10, 0x49, 0x03, 0x4C, 0x16]);
+    }
+
+    #[test]
+    fn serial_inter_char_timeout() {
+        let (mut link, mut master) = pair(
+            SerialConfig::new()
+                .with_baud_rate(115_200)
+                .with_inter_char_timeout(Duration::from_millis(10)),
+        );
+        let frame = frame();
+        let (head, tail) = frame.split_at(5);
+        let head = head.to_vec();
+        let tail = tail.to_vec();
+        let mut writer = master.try_clone().unwrap();
+        let handle = thread::spawn(move || {
+            writer.write_all(&head).unwrap();
+            thread::sleep(Duration::from_millis(50));
+            writer.write_all(&tail).unwrap();
+        });
+        assert!(link.recv(Duration::from_secs(1)).is_err());
+        handle.join().unwrap();
+        // the late tail of the broken frame is rejected and discarded
+        assert!(link.recv(Duration::from_secs(1)).is_err());
+        master.write_all(&frame).unwrap();
+        let telegram = link.recv(Duration::from_secs(1)).unwrap().unwrap();
+        assert_eq!(telegram.adsu(), 7);
+    }
+
+    #[test]
+    fn serial_line_idle_interval() {
+        let serial_config = SerialConfig::new().with_baud_rate(1200);
+        let idle = serial_config.line_idle_interval();
+        assert_eq!(idle, Duration::from_micros(27_500));
+        let (mut link, mut master) = pair(serial_config);
+        let telegram = Telegram101::new_ack(true);
+        let start = Instant::now();
+        link.send(&telegram).unwrap();
+        link.send(&telegram).unwrap();
+        link.send(&telegram).unwrap();
+        assert!(start.elapsed() >= idle * 2);
+        let mut buf = [0u8; 3];
+        master.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [0xE5; 3]);
+    }
+
+    #[test]
+    fn serial_config_unsupported_baud_rate() {
+        let pty = openpty(None, None).unwrap();
+        assert!(
+            SerialLink101::from_fd(
+                pty.slave,
+                SerialConfig::new().with_baud_rate(12345),
+                Config::new()
+            )
+            .is_err()
+        );
+    }
+}
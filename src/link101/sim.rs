@@ -0,0 +1,546 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    Error,
+    clock::{Clock, SystemClock},
+    telegram101::{Config, Telegram101},
+};
+
+use super::{FrameBuffer, Link101};
+
+const DEFAULT_SEED: u64 = 1;
+
+/// Fault injection settings of a simulated bus
+///
+/// Probabilities are in range 0.0..=1.0, all faults are disabled by default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SimFaults {
+    bit_error_probability: f64,
+    drop_probability: f64,
+    delay_probability: f64,
+    max_delay: Duration,
+    collision_probability: f64,
+}
+
+impl SimFaults {
+    /// No faults
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Probability of a single bit error in a transmitted byte
+    pub fn with_bit_error_probability(mut self, probability: f64) -> Self {
+        self.bit_error_probability = probability;
+        self
+    }
+    /// Probability of a transmitted byte to be lost
+    pub fn with_drop_probability(mut self, probability: f64) -> Self {
+        self.drop_probability = probability;
+        self
+    }
+    /// Probability of a transmission to be delayed by a random time up to `max_delay`
+    pub fn with_delay(mut self, probability: f64, max_delay: Duration) -> Self {
+        self.delay_probability = probability;
+        self.max_delay = max_delay;
+        self
+    }
+    /// Probability of a transmission to collide with a (simulated) foreign transmitter
+    pub fn with_collision_probability(mut self, probability: f64) -> Self {
+        self.collision_probability = probability;
+        self
+    }
+    /// Bit error probability
+    pub fn bit_error_probability(&self) -> f64 {
+        self.bit_error_probability
+    }
+    /// Byte drop probability
+    pub fn drop_probability(&self) -> f64 {
+        self.drop_probability
+    }
+    /// Delay probability
+    pub fn delay_probability(&self) -> f64 {
+        self.delay_probability
+    }
+    /// Max delay
+    pub fn max_delay(&self) -> Duration {
+        self.max_delay
+    }
+    /// Collision probability
+    pub fn collision_probability(&self) -> f64 {
+        self.collision_probability
+    }
+}
+
+/// Simulated bus counters
+#[derive(Debug, Clone, Default)]
+pub struct SimStats {
+    /// Transmitted frames
+    pub frames: u64,
+    /// Injected bit errors
+    pub bit_errors: u64,
+    /// Dropped bytes
+    pub dropped_bytes: u64,
+    /// Delayed frames
+    pub delayed_frames: u64,
+    /// Collided frames
+    pub collisions: u64,
+}
+
+/// xorshift64* generator, good enough for fault injection and fully reproducible
+#[derive(Debug)]
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        let state = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        Self(if state == 0 { 1 } else { state })
+    }
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+    fn next_f64(&mut self) -> f64 {
+        f64::from(u32::try_from(self.next_u64() >> 32).unwrap()) / f64::from(u32::MAX)
+    }
+    fn next_u8(&mut self) -> u8 {
+        self.next_u64().to_be_bytes()[0]
+    }
+    fn chance(&mut self, probability: f64) -> bool {
+        probability > 0.0 && self.next_f64() < probability
+    }
+}
+
+#[derive(Debug)]
+struct Chunk {
+    tx: u64,
+    deliver_at: Instant,
+    data: Vec<u8>,
+}
+
+#[derive(Debug)]
+struct BusState {
+    rng: Rng,
+    faults: SimFaults,
+    stats: SimStats,
+    // receive queues of the attached endpoints, None for detached ones
+    queues: Vec<Option<VecDeque<Chunk>>>,
+    tx: u64,
+    busy_until: Option<Instant>,
+}
+
+fn garble(rng: &mut Rng, data: &mut [u8]) {
+    for b in data {
+        *b ^= rng.next_u8() | 1;
+    }
+}
+
+impl BusState {
+    fn transmit(&mut self, sender: usize, mut data: Vec<u8>, char_time: Duration, now: Instant) {
+        self.stats.frames += 1;
+        self.tx += 1;
+        let tx = self.tx;
+        // physical collision: the previous transmission is still on the line
+        let collided = self.busy_until.is_some_and(|busy_until| busy_until > now);
+        if collided {
+            for chunk in self
+                .queues
+                .iter_mut()
+                .flatten()
+                .flat_map(|q| q.iter_mut())
+                .filter(|c| c.tx == tx - 1)
+            {
+                garble(&mut self.rng, &mut chunk.data);
+            }
+            self.stats.collisions += 1;
+        }
+        if collided || self.rng.chance(self.faults.collision_probability) {
+            garble(&mut self.rng, &mut data);
+            self.stats.collisions += 1;
+        }
+        let mut received = Vec::with_capacity(data.len());
+        for mut b in data {
+            if self.rng.chance(self.faults.drop_probability) {
+                self.stats.dropped_bytes += 1;
+                continue;
+            }
+            if self.rng.chance(self.faults.bit_error_probability) {
+                b ^= 1 << (self.rng.next_u64() % 8);
+                self.stats.bit_errors += 1;
+            }
+            received.push(b);
+        }
+        let tx_time = char_time * u32::try_from(received.len()).unwrap_or(u32::MAX);
+        let delay = if self.rng.chance(self.faults.delay_probability) {
+            self.stats.delayed_frames += 1;
+            self.faults.max_delay.mul_f64(self.rng.next_f64())
+        } else {
+            Duration::ZERO
+        };
+        self.busy_until = Some(now + tx_time);
+        for (i, queue) in self.queues.iter_mut().enumerate() {
+            let Some(queue) = queue else {
+                continue;
+            };
+            if i == sender {
+                continue;
+            }
+            let mut deliver_at = now + tx_time + delay;
+            if let Some(last) = queue.back() {
+                deliver_at = deliver_at.max(last.deliver_at);
+            }
+            queue.push_back(Chunk {
+                tx,
+                deliver_at,
+                data: received.clone(),
+            });
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<BusState>,
+    cond: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, BusState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// In-memory half-duplex bus for IEC 60870-5-101 testing
+///
+/// Any number of [`SimLink101`] endpoints can be attached, a frame sent by an endpoint is
+/// received by all the others. Bit errors, dropped bytes, delays and collisions are injected
+/// according to [`SimFaults`], using a pseudo-random generator with a fixed seed, so a
+/// single-threaded test gets the same faults on every run.
+///
+/// With a non-zero character time, a transmission occupies the line for the time required to
+/// transmit its bytes. A transmission started while the line is busy collides with the
+/// previous one and both are received corrupted.
+///
+/// Delays and the line occupation follow the clock of the bus, with a [`ManualClock`] a test
+/// advances the time instead of sleeping.
+///
+/// [`ManualClock`]: crate::clock::ManualClock
+#[derive(Debug, Clone)]
+pub struct SimBus {
+    shared: Arc<Shared>,
+    config: Config,
+    char_time: Duration,
+    clock: Arc<dyn Clock>,
+}
+
+impl SimBus {
+    /// Create a new bus
+    pub fn new(config: Config) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                state: Mutex::new(BusState {
+                    rng: Rng::new(DEFAULT_SEED),
+                    faults: SimFaults::default(),
+                    stats: SimStats::default(),
+                    queues: Vec::new(),
+                    tx: 0,
+                    busy_until: None,
+                }),
+                cond: Condvar::new(),
+            }),
+            config,
+            char_time: Duration::ZERO,
+            clock: Arc::new(SystemClock),
+        }
+    }
+    /// Set the clock (default: system clock), must be set before endpoints are attached
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    /// Set the random generator seed
+    pub fn with_seed(self, seed: u64) -> Self {
+        self.shared.lock().rng = Rng::new(seed);
+        self
+    }
+    /// Set the fault injection settings
+    pub fn with_faults(self, faults: SimFaults) -> Self {
+        self.set_faults(faults);
+        self
+    }
+    /// Set the transmission time of a single character (default: zero, no physical collisions)
+    pub fn with_char_time(mut self, char_time: Duration) -> Self {
+        self.char_time = char_time;
+        self
+    }
+    /// Change the fault injection settings of a running bus
+    pub fn set_faults(&self, faults: SimFaults) {
+        self.shared.lock().faults = faults;
+    }
+    /// Bus counters
+    pub fn stats(&self) -> SimStats {
+        self.shared.lock().stats.clone()
+    }
+    /// Attach a new endpoint
+    pub fn attach(&self) -> SimLink101 {
+        let mut state = self.shared.lock();
+        state.queues.push(Some(VecDeque::new()));
+        SimLink101 {
+            bus: self.clone(),
+            id: state.queues.len() - 1,
+            rx: FrameBuffer::default(),
+        }
+    }
+}
+
+/// An endpoint of [`SimBus`]
+#[derive(Debug)]
+pub struct SimLink101 {
+    bus: SimBus,
+    id: usize,
+    rx: FrameBuffer,
+}
+
+impl SimLink101 {
+    /// The bus the endpoint is attached to
+    pub fn bus(&self) -> &SimBus {
+        &self.bus
+    }
+    fn next_chunk(&self, deadline: Instant) -> Option<Vec<u8>> {
+        let mut state = self.bus.shared.lock();
+        loop {
+            let now = self.bus.clock.now();
+            let queue = state.queues[self.id].as_mut().unwrap();
+            let mut wait_until = deadline;
+            if let Some(chunk) = queue.front() {
+                if chunk.deliver_at <= now {
+                    return queue.pop_front().map(|c| c.data);
+                }
+                wait_until = wait_until.min(chunk.deliver_at);
+            }
+            if now >= deadline {
+                return None;
+            }
+            let timed_out;
+            (state, timed_out) = self
+                .bus
+                .shared
+                .cond
+                .wait_timeout(state, wait_until.saturating_duration_since(now))
+                .unwrap_or_else(PoisonError::into_inner);
+            // a clock which does not advance by itself (manual) ends the wait as well
+            if timed_out.timed_out() && self.bus.clock.now() == now {
+                return None;
+            }
+        }
+    }
+}
+
+impl Link101 for SimLink101 {
+    fn config(&self) -> Config {
+        self.bus.config
+    }
+    fn send(&mut self, telegram: &Telegram101) -> Result<(), Error> {
+        let mut buf = Vec::with_capacity(261);
+        telegram.write(&mut buf)?;
+        self.bus
+            .shared
+            .lock()
+            .transmit(self.id, buf, self.bus.char_time, self.bus.clock.now());
+        self.bus.shared.cond.notify_all();
+        Ok(())
+    }
+    fn recv(&mut self, timeout: Duration) -> Result<Option<Telegram101>, Error> {
+        let deadline = self.bus.clock.now() + timeout;
+        loop {
+            if let Some(telegram) = self.rx.next_frame(self.bus.config)? {
+                return Ok(Some(telegram));
+            }
+            let chunk = self.next_chunk(deadline);
+            // the rest of an incomplete frame is never received, as a real receiver detects
+            // the line idle interval
+            if !self.rx.is_empty() {
+                self.rx = FrameBuffer::default();
+                if let Some(data) = chunk {
+                    self.rx.extend(&data);
+                }
+                return Err(Error::invalid_data("incomplete frame"));
+            }
+            let Some(data) = chunk else {
+                return Ok(None);
+            };
+            self.rx.extend(&data);
+        }
+    }
+}
+
+impl Drop for SimLink101 {
+    fn drop(&mut self) {
+        self.bus.shared.lock().queues[self.id] = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicBool, Ordering},
+        },
+        thread,
+        time::Duration,
+    };
+
+    use super::{SimBus, SimFaults, SimLink101};
+    use crate::{
+        clock::ManualClock,
+        link101::{Link101, Primary101, StationLink, function_code},
+        telegram101::{Config, Telegram101},
+    };
+
+    fn frame(link_address: u32) -> Telegram101 {
+        Telegram101::new_fixed(Config::new())
+            .with_prm(true)
+            .with_function_code(function_code::REQUEST_LINK_STATUS)
+            .with_link_address(link_address)
+    }
+
+    /// Receive results as (frame link address or None on error) until the line is silent
+    fn receive_all(link: &mut SimLink101) -> Vec<Option<u32>> {
+        let mut result = Vec::new();
+        loop {
+            match link.recv(Duration::from_millis(20)) {
+                Ok(Some(telegram)) => result.push(Some(telegram.link_address())),
+                Ok(None) => break,
+                Err(_) => result.push(None),
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn sim_bus_delivery() {
+        let bus = SimBus::new(Config::new());
+        let mut a = bus.attach();
+        let mut b = bus.attach();
+        let mut c = bus.attach();
+        a.send(&frame(1)).unwrap();
+        assert_eq!(receive_all(&mut b), [Some(1)]);
+        assert_eq!(receive_all(&mut c), [Some(1)]);
+        // half-duplex, the sender does not receive its own frames
+        assert!(receive_all(&mut a).is_empty());
+        drop(c);
+        b.send(&frame(2)).unwrap();
+        assert_eq!(receive_all(&mut a), [Some(2)]);
+        assert_eq!(bus.stats().frames, 2);
+    }
+
+    #[test]
+    fn sim_bus_deterministic() {
+        let run = || {
+            let bus = SimBus::new(Config::new()).with_seed(42).with_faults(
+                SimFaults::new()
+                    .with_bit_error_probability(0.05)
+                    .with_drop_probability(0.05),
+            );
+            let mut a = bus.attach();
+            let mut b = bus.attach();
+            for i in 0..50 {
+                a.send(&frame(i)).unwrap();
+            }
+            let stats = bus.stats();
+            assert!(stats.bit_errors > 0);
+            assert!(stats.dropped_bytes > 0);
+            receive_all(&mut b)
+        };
+        let first = run();
+        assert!(first.contains(&None));
+        assert!(first.iter().filter(|r| r.is_some()).count() > 10);
+        assert_eq!(first, run());
+    }
+
+    #[test]
+    fn sim_bus_delay() {
+        let clock = Arc::new(ManualClock::default());
+        let bus = SimBus::new(Config::new())
+            .with_clock(clock.clone())
+            .with_faults(SimFaults::new().with_delay(1.0, Duration::from_millis(200)));
+        let mut a = bus.attach();
+        let mut b = bus.attach();
+        // the first random delay with the default seed is long enough
+        a.send(&frame(1)).unwrap();
+        assert!(b.recv(Duration::from_millis(10)).unwrap().is_none());
+        clock.advance(Duration::from_millis(200));
+        let telegram = b.recv(Duration::ZERO).unwrap().unwrap();
+        assert_eq!(telegram.link_address(), 1);
+        assert_eq!(bus.stats().delayed_frames, 1);
+    }
+
+    #[test]
+    fn sim_bus_collision() {
+        let clock = Arc::new(ManualClock::default());
+        let bus = SimBus::new(Config::new())
+            .with_clock(clock.clone())
+            .with_char_time(Duration::from_millis(1));
+        let mut a = bus.attach();
+        let mut b = bus.attach();
+        let mut c = bus.attach();
+        a.send(&frame(1)).unwrap();
+        b.send(&frame(2)).unwrap();
+        clock.advance(Duration::from_millis(50));
+        let received = receive_all(&mut c);
+        assert!(!received.is_empty());
+        assert!(received.iter().all(Option::is_none));
+        assert_eq!(bus.stats().collisions, 2);
+        // the line is free again
+        a.send(&frame(3)).unwrap();
+        clock.advance(Duration::from_millis(50));
+        assert_eq!(receive_all(&mut c), [Some(3)]);
+    }
+
+    #[test]
+    fn sim_bus_primary_retries() {
+        let bus = SimBus::new(Config::new()).with_seed(7).with_faults(
+            SimFaults::new()
+                .with_drop_probability(0.02)
+                .with_bit_error_probability(0.02)
+                .with_collision_probability(0.05),
+        );
+        let mut secondary = bus.attach();
+        let stop = Arc::new(AtomicBool::new(false));
+        let secondary_stop = stop.clone();
+        let handle = thread::spawn(move || {
+            while !secondary_stop.load(Ordering::Relaxed) {
+                let Ok(Some(frame)) = secondary.recv(Duration::from_millis(10)) else {
+                    continue;
+                };
+                let fc = match frame.function_code() {
+                    function_code::REQUEST_LINK_STATUS => function_code::LINK_STATUS,
+                    function_code::REQUEST_CLASS_2 => function_code::NACK_NO_DATA,
+                    _ => function_code::ACK,
+                };
+                let reply = Telegram101::new_fixed(Config::new())
+                    .with_function_code(fc)
+                    .with_link_address(frame.link_address());
+                secondary.send(&reply).unwrap();
+            }
+        });
+        let mut primary = Primary101::new(bus.attach())
+            .with_retries(10)
+            .with_response_timeout(Duration::from_millis(20));
+        let mut station = StationLink::new(5);
+        primary.request_link_status(&mut station).unwrap();
+        primary.reset_remote_link(&mut station).unwrap();
+        for _ in 0..50 {
+            let reply = primary.request_class_2(&mut station).unwrap();
+            assert_eq!(reply.link_address(), 5);
+        }
+        stop.store(true, Ordering::Relaxed);
+        handle.join().unwrap();
+        assert!(station.retries() > 0);
+    }
+}
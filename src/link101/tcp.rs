@@ -0,0 +1,428 @@
+use std::{
+    io::{self, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    Error,
+    clock::{Clock, SystemClock},
+    telegram101::{Config, Telegram101},
+};
+
+use super::{FrameBuffer, Link101};
+
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// An established TCP connection with its receive buffer
+#[derive(Debug)]
+struct Connection {
+    stream: TcpStream,
+    rx: FrameBuffer,
+}
+
+impl Connection {
+    fn new(stream: TcpStream) -> Result<Self, Error> {
+        stream.set_nodelay(true)?;
+        Ok(Self {
+            stream,
+            rx: FrameBuffer::default(),
+        })
+    }
+    fn send(&mut self, telegram: &Telegram101) -> Result<(), Error> {
+        let mut buf = Vec::with_capacity(261);
+        telegram.write(&mut buf)?;
+        self.stream.write_all(&buf)?;
+        Ok(())
+    }
+    /// Receives the next frame, waiting for data up to `deadline`
+    fn recv(
+        &mut self,
+        config: Config,
+        deadline: Instant,
+        clock: &dyn Clock,
+    ) -> Result<Option<Telegram101>, Error> {
+        let mut buf = [0u8; 512];
+        loop {
+            if let Some(telegram) = self.rx.next_frame(config)? {
+                return Ok(Some(telegram));
+            }
+            let now = clock.now();
+            if now >= deadline {
+                return Ok(None);
+            }
+            self.stream.set_read_timeout(Some(deadline - now))?;
+            match self.stream.read(&mut buf) {
+                Ok(0) => return Err(io::Error::from(io::ErrorKind::ConnectionReset).into()),
+                Ok(n) => self.rx.extend(&buf[..n]),
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    return Ok(None);
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+}
+
+/// Drops the connection if the error is fatal for it
+fn check_connection(connection: &mut Option<Connection>, result: &Result<impl Sized, Error>) {
+    if let Err(Error::Io(_)) = result {
+        *connection = None;
+    }
+}
+
+/// IEC 60870-5-101 over TCP, connecting side (e.g. a primary station talking to a
+/// serial-to-Ethernet terminal server)
+///
+/// FT1.2 frames are transferred unchanged in the TCP stream, segment boundaries are ignored. The
+/// link connects on demand and reconnects after I/O errors, not more often than the reconnect
+/// delay.
+#[derive(Debug)]
+pub struct TcpLink101 {
+    addrs: Vec<SocketAddr>,
+    config: Config,
+    connect_timeout: Duration,
+    reconnect_delay: Duration,
+    connection: Option<Connection>,
+    last_connect_attempt: Option<Instant>,
+    clock: Arc<dyn Clock>,
+}
+
+impl TcpLink101 {
+    /// Create a new link (the connection is established on demand)
+    pub fn new(addr: impl ToSocketAddrs, config: Config) -> Result<Self, Error> {
+        let addrs: Vec<SocketAddr> = addr.to_socket_addrs()?.collect();
+        if addrs.is_empty() {
+            return Err(Error::invalid_config("no socket address"));
+        }
+        Ok(Self {
+            addrs,
+            config,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            reconnect_delay: DEFAULT_RECONNECT_DELAY,
+            connection: None,
+            last_connect_attempt: None,
+            clock: Arc::new(SystemClock),
+        })
+    }
+    /// Set the clock (receive timeouts and reconnect delay, default: system clock)
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    /// Set the connect timeout (default: 5 seconds)
+    pub fn with_connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+    /// Set the minimum delay between connection attempts (default: 1 second)
+    pub fn with_reconnect_delay(mut self, reconnect_delay: Duration) -> Self {
+        self.reconnect_delay = reconnect_delay;
+        self
+    }
+    /// Is the link connected
+    pub fn is_connected(&self) -> bool {
+        self.connection.is_some()
+    }
+    /// Close the connection (it is re-established on the next operation)
+    pub fn disconnect(&mut self) {
+        self.connection = None;
+    }
+    /// Connect if not connected
+    pub fn connect(&mut self) -> Result<(), Error> {
+        if self.connection.is_some() {
+            return Ok(());
+        }
+        if let Some(last_attempt) = self.last_connect_attempt
+            && self.clock.now().saturating_duration_since(last_attempt) < self.reconnect_delay
+        {
+            return Err(io::Error::from(io::ErrorKind::NotConnected).into());
+        }
+        self.last_connect_attempt = Some(self.clock.now());
+        let mut last_error = None;
+        for addr in &self.addrs {
+            match TcpStream::connect_timeout(addr, self.connect_timeout) {
+                Ok(stream) => {
+                    self.connection = Some(Connection::new(stream)?);
+                    return Ok(());
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(last_error
+            .unwrap_or_else(|| io::ErrorKind::NotConnected.into())
+            .into())
+    }
+}
+
+impl Link101 for TcpLink101 {
+    fn config(&self) -> Config {
+        self.config
+    }
+    fn send(&mut self, telegram: &Telegram101) -> Result<(), Error> {
+        self.connect()?;
+        let connection = self.connection.as_mut().unwrap();
+        let result = connection.send(telegram);
+        check_connection(&mut self.connection, &result);
+        result
+    }
+    fn recv(&mut self, timeout: Duration) -> Result<Option<Telegram101>, Error> {
+        self.connect()?;
+        let connection = self.connection.as_mut().unwrap();
+        let result = connection.recv(self.config, self.clock.now() + timeout, &*self.clock);
+        check_connection(&mut self.connection, &result);
+        result
+    }
+}
+
+/// IEC 60870-5-101 over TCP, listening side (e.g. a secondary station, connected by a
+/// serial-to-Ethernet terminal server)
+///
+/// A single peer is served at a time. A newly accepted connection replaces the current one, so
+/// the peer can reconnect at any time.
+#[derive(Debug)]
+pub struct TcpServerLink101 {
+    listener: TcpListener,
+    config: Config,
+    connection: Option<Connection>,
+    clock: Arc<dyn Clock>,
+}
+
+impl TcpServerLink101 {
+    /// Bind the listener
+    pub fn bind(addr: impl ToSocketAddrs, config: Config) -> Result<Self, Error> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            listener,
+            config,
+            connection: None,
+            clock: Arc::new(SystemClock),
+        })
+    }
+    /// Set the clock (receive timeouts, default: system clock)
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    /// Local listener address
+    pub fn local_addr(&self) -> Result<SocketAddr, Error> {
+        self.listener.local_addr().map_err(Into::into)
+    }
+    /// Is a peer connected
+    pub fn is_connected(&self) -> bool {
+        self.connection.is_some()
+    }
+    /// Close the current peer connection
+    pub fn disconnect(&mut self) {
+        self.connection = None;
+    }
+    /// Accept pending connections, the last one replaces the current peer
+    fn accept_pending(&mut self) -> Result<(), Error> {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) => {
+                    stream.set_nonblocking(false)?;
+                    self.connection = Some(Connection::new(stream)?);
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+}
+
+impl Link101 for TcpServerLink101 {
+    fn config(&self) -> Config {
+        self.config
+    }
+    fn send(&mut self, telegram: &Telegram101) -> Result<(), Error> {
+        self.accept_pending()?;
+        let Some(connection) = self.connection.as_mut() else {
+            return Err(io::Error::from(io::ErrorKind::NotConnected).into());
+        };
+        let result = connection.send(telegram);
+        check_connection(&mut self.connection, &result);
+        result
+    }
+    fn recv(&mut self, timeout: Duration) -> Result<Option<Telegram101>, Error> {
+        let deadline = self.clock.now() + timeout;
+        loop {
+            self.accept_pending()?;
+            let now = self.clock.now();
+            let step_deadline = deadline.min(now + ACCEPT_POLL_INTERVAL);
+            if let Some(connection) = self.connection.as_mut() {
+                let result = connection.recv(self.config, step_deadline, &*self.clock);
+                check_connection(&mut self.connection, &result);
+                if !matches!(result, Ok(None)) {
+                    return result;
+                }
+            } else {
+                thread::sleep(step_deadline - now);
+            }
+            // a clock which does not advance by itself (manual) ends the wait as well
+            let after = self.clock.now();
+            if after >= deadline || after == now {
+                return Ok(None);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::{TcpListener, TcpStream},
+        sync::Arc,
+        thread,
+        time::Duration,
+    };
+
+    use super::{TcpLink101, TcpServerLink101};
+    use crate::{
+        Error,
+        clock::ManualClock,
+        link101::Link101,
+        telegram101::{Config, Telegram101},
+        types::{COT, datatype::DataType},
+    };
+
+    const TIMEOUT: Duration = Duration::from_secs(2);
+
+    fn frame(adsu: u16) -> Vec<u8> {
+        let mut telegram = Telegram101::new(DataType::M_SP_NA_1, COT::Spontan, adsu, Config::new())
+            .with_link_address(3);
+        telegram.append_iou(100, [1u8; 12]);
+        let mut buf = Vec::new();
+        telegram.write(&mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn tcp_segment_boundaries() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut link = TcpLink101::new(listener.local_addr().unwrap(), Config::new()).unwrap();
+        link.connect().unwrap();
+        let (mut peer, _) = listener.accept().unwrap();
+        let handle = thread::spawn(move || {
+            // a frame split into single byte segments
+            for b in frame(1) {
+                peer.write_all(&[b]).unwrap();
+                peer.flush().unwrap();
+                thread::sleep(Duration::from_millis(1));
+            }
+            // two frames and a garbage byte in a single segment
+            let mut buf = frame(2);
+            buf.push(0x00);
+            buf.extend(frame(3));
+            peer.write_all(&buf).unwrap();
+            peer
+        });
+        let telegram = link.recv(TIMEOUT).unwrap().unwrap();
+        assert_eq!(telegram.adsu(), 1);
+        let telegram = link.recv(TIMEOUT).unwrap().unwrap();
+        assert_eq!(telegram.adsu(), 2);
+        assert!(link.recv(TIMEOUT).is_err());
+        let telegram = link.recv(TIMEOUT).unwrap().unwrap();
+        assert_eq!(telegram.adsu(), 3);
+        let _peer = handle.join().unwrap();
+        assert!(link.recv(Duration::from_millis(10)).unwrap().is_none());
+    }
+
+    #[test]
+    fn tcp_short_asdu() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut link = TcpLink101::new(listener.local_addr().unwrap(), Config::new()).unwrap();
+        link.connect().unwrap();
+        let (mut peer, _) = listener.accept().unwrap();
+        // a valid checksum, but the ASDU is shorter than announced by the header
+        let mut bad = frame(1);
+        bad.truncate(bad.len() - 3);
+        bad[1] -= 1;
+        bad[2] -= 1;
+        let checksum = bad[4..].iter().fold(0u8, |sum, b| sum.wrapping_add(*b));
+        bad.extend([checksum, 0x16]);
+        bad.extend(frame(2));
+        peer.write_all(&bad).unwrap();
+        assert!(matches!(link.recv(TIMEOUT), Err(Error::InvalidData(_))));
+        assert!(link.is_connected());
+        let telegram = (0..bad.len())
+            .find_map(|_| link.recv(TIMEOUT).ok().flatten())
+            .expect("the valid frame is received after the bad one");
+        assert_eq!(telegram.adsu(), 2);
+        assert!(link.is_connected());
+    }
+
+    #[test]
+    fn tcp_reconnect() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut link = TcpLink101::new(listener.local_addr().unwrap(), Config::new())
+            .unwrap()
+            .with_reconnect_delay(Duration::ZERO);
+        let telegram = Telegram101::new_ack(true);
+        link.send(&telegram).unwrap();
+        let (peer, _) = listener.accept().unwrap();
+        drop(peer);
+        assert!(link.recv(TIMEOUT).is_err());
+        assert!(!link.is_connected());
+        link.send(&telegram).unwrap();
+        let (mut peer, _) = listener.accept().unwrap();
+        let mut buf = [0u8; 1];
+        peer.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [0xE5]);
+    }
+
+    #[test]
+    fn tcp_reconnect_delay() {
+        let clock = Arc::new(ManualClock::default());
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut link = TcpLink101::new(listener.local_addr().unwrap(), Config::new())
+            .unwrap()
+            .with_reconnect_delay(Duration::from_secs(60))
+            .with_clock(clock.clone());
+        let telegram = Telegram101::new_ack(true);
+        link.send(&telegram).unwrap();
+        let (peer, _) = listener.accept().unwrap();
+        drop(peer);
+        assert!(link.recv(TIMEOUT).is_err());
+        assert!(link.send(&telegram).is_err());
+        clock.advance(Duration::from_secs(59));
+        assert!(link.send(&telegram).is_err());
+        clock.advance(Duration::from_secs(1));
+        link.send(&telegram).unwrap();
+        assert!(link.is_connected());
+    }
+
+    #[test]
+    fn tcp_server_link() {
+        let mut server = TcpServerLink101::bind("127.0.0.1:0", Config::new()).unwrap();
+        let addr = server.local_addr().unwrap();
+        assert!(server.recv(Duration::from_millis(10)).unwrap().is_none());
+        assert!(server.send(&Telegram101::new_ack(true)).is_err());
+        let mut client = TcpLink101::new(addr, Config::new()).unwrap();
+        client.send(&Telegram101::new_ack(false)).unwrap();
+        let telegram = server.recv(TIMEOUT).unwrap().unwrap();
+        assert!(telegram.is_ack_only());
+        assert!(telegram.is_negative());
+        server.send(&Telegram101::new_ack(true)).unwrap();
+        let telegram = client.recv(TIMEOUT).unwrap().unwrap();
+        assert!(!telegram.is_negative());
+        // the peer reconnects, the new connection replaces the old one
+        let mut peer = TcpStream::connect(addr).unwrap();
+        peer.write_all(&frame(5)).unwrap();
+        let telegram = server.recv(TIMEOUT).unwrap().unwrap();
+        assert_eq!(telegram.adsu(), 5);
+    }
+}
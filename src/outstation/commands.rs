@@ -0,0 +1,670 @@
+use std::{collections::BTreeSet, sync::Arc};
+
+use crate::{
+    Error,
+    clock::{Clock, SystemClock},
+    telegram104::Telegram104_I,
+    types::{
+        COT, DataBuffer,
+        datatype::{
+            C_BO_NA_1, C_BO_TB_1, C_DC_NA_1, C_DC_TA_1, C_RC_NA_1, C_RC_TA_1, C_SC_NA_1, C_SC_TA_1,
+            C_SE_NA_1, C_SE_NB_1, C_SE_NC_1, C_SE_TA_1, C_SE_TB_1, C_SE_TC_1, DataType,
+            SelectExecute,
+        },
+        time::CP56Time2a,
+    },
+};
+
+use super::{ControlMode, SboCheck, SboTracker, TimeTagCheck, TimeTagValidator};
+
+/// Process command
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Single command (C_SC_NA_1)
+    Single(C_SC_NA_1),
+    /// Single command with time tag (C_SC_TA_1)
+    SingleTime(C_SC_TA_1),
+    /// Double command (C_DC_NA_1)
+    Double(C_DC_NA_1),
+    /// Double command with time tag (C_DC_TA_1)
+    DoubleTime(C_DC_TA_1),
+    /// Regulating step command (C_RC_NA_1)
+    Regulating(C_RC_NA_1),
+    /// Regulating step command with time tag (C_RC_TA_1)
+    RegulatingTime(C_RC_TA_1),
+    /// Set point command, normalized value (C_SE_NA_1)
+    Normalized(C_SE_NA_1),
+    /// Set point command, normalized value with time tag (C_SE_TA_1)
+    NormalizedTime(C_SE_TA_1),
+    /// Set point command, scaled value (C_SE_NB_1)
+    Scaled(C_SE_NB_1),
+    /// Set point command, scaled value with time tag (C_SE_TB_1)
+    ScaledTime(C_SE_TB_1),
+    /// Set point command, short floating point value (C_SE_NC_1)
+    Float(C_SE_NC_1),
+    /// Set point command, short floating point value with time tag (C_SE_TC_1)
+    FloatTime(C_SE_TC_1),
+    /// Bit string command (C_BO_NA_1)
+    Bitstring(C_BO_NA_1),
+    /// Bit string command with time tag (C_BO_TA_1)
+    BitstringTime(C_BO_TB_1),
+}
+
+impl Command {
+    /// Decode a command information object, `None` if the data type is not a process command
+    pub fn decode(data_type: DataType, value: DataBuffer) -> Option<Self> {
+        Some(match data_type {
+            DataType::C_SC_NA_1 => Command::Single(value.into()),
+            DataType::C_SC_TA_1 => Command::SingleTime(value.into()),
+            DataType::C_DC_NA_1 => Command::Double(value.into()),
+            DataType::C_DC_TA_1 => Command::DoubleTime(value.into()),
+            DataType::C_RC_NA_1 => Command::Regulating(value.into()),
+            DataType::C_RC_TA_1 => Command::RegulatingTime(value.into()),
+            DataType::C_SE_NA_1 => Command::Normalized(value.into()),
+            DataType::C_SE_TA_1 => Command::NormalizedTime(value.into()),
+            DataType::C_SE_NB_1 => Command::Scaled(value.into()),
+            DataType::C_SE_TB_1 => Command::ScaledTime(value.into()),
+            DataType::C_SE_NC_1 => Command::Float(value.into()),
+            DataType::C_SE_TC_1 => Command::FloatTime(value.into()),
+            DataType::C_BO_NA_1 => Command::Bitstring(value.into()),
+            DataType::C_BO_TA_1 => Command::BitstringTime(value.into()),
+            _ => return None,
+        })
+    }
+    /// Data type
+    pub fn data_type(&self) -> DataType {
+        match self {
+            Command::Single(_) => DataType::C_SC_NA_1,
+            Command::SingleTime(_) => DataType::C_SC_TA_1,
+            Command::Double(_) => DataType::C_DC_NA_1,
+            Command::DoubleTime(_) => DataType::C_DC_TA_1,
+            Command::Regulating(_) => DataType::C_RC_NA_1,
+            Command::RegulatingTime(_) => DataType::C_RC_TA_1,
+            Command::Normalized(_) => DataType::C_SE_NA_1,
+            Command::NormalizedTime(_) => DataType::C_SE_TA_1,
+            Command::Scaled(_) => DataType::C_SE_NB_1,
+            Command::ScaledTime(_) => DataType::C_SE_TB_1,
+            Command::Float(_) => DataType::C_SE_NC_1,
+            Command::FloatTime(_) => DataType::C_SE_TC_1,
+            Command::Bitstring(_) => DataType::C_BO_NA_1,
+            Command::BitstringTime(_) => DataType::C_BO_TA_1,
+        }
+    }
+    /// Select/execute qualifier (bit string commands are always executed directly)
+    pub fn select_execute(&self) -> SelectExecute {
+        match self {
+            Command::Single(c) => c.sco.se,
+            Command::SingleTime(c) => c.sco.se,
+            Command::Double(c) => c.dco.se,
+            Command::DoubleTime(c) => c.dco.se,
+            Command::Regulating(c) => c.rco.se,
+            Command::RegulatingTime(c) => c.rco.se,
+            Command::Normalized(c) => c.qos.se,
+            Command::NormalizedTime(c) => c.qos.se,
+            Command::Scaled(c) => c.qos.se,
+            Command::ScaledTime(c) => c.qos.se,
+            Command::Float(c) => c.qos.se,
+            Command::FloatTime(c) => c.qos.se,
+            Command::Bitstring(_) | Command::BitstringTime(_) => SelectExecute::Execute,
+        }
+    }
+    /// Is the command a selection
+    pub fn is_select(&self) -> bool {
+        self.select_execute() == SelectExecute::Select
+    }
+    /// The command with the execute qualifier and without the time tag value, to compare
+    /// selections with execute commands
+    pub(crate) fn operation(&self) -> Command {
+        let mut command = self.clone();
+        match &mut command {
+            Command::Single(c) => c.sco.se = SelectExecute::Execute,
+            Command::SingleTime(c) => {
+                c.sco.se = SelectExecute::Execute;
+                c.time = CP56Time2a::default();
+            }
+            Command::Double(c) => c.dco.se = SelectExecute::Execute,
+            Command::DoubleTime(c) => {
+                c.dco.se = SelectExecute::Execute;
+                c.time = CP56Time2a::default();
+            }
+            Command::Regulating(c) => c.rco.se = SelectExecute::Execute,
+            Command::RegulatingTime(c) => {
+                c.rco.se = SelectExecute::Execute;
+                c.time = CP56Time2a::default();
+            }
+            Command::Normalized(c) => c.qos.se = SelectExecute::Execute,
+            Command::NormalizedTime(c) => {
+                c.qos.se = SelectExecute::Execute;
+                c.time = CP56Time2a::default();
+            }
+            Command::Scaled(c) => c.qos.se = SelectExecute::Execute,
+            Command::ScaledTime(c) => {
+                c.qos.se = SelectExecute::Execute;
+                c.time = CP56Time2a::default();
+            }
+            Command::Float(c) => c.qos.se = SelectExecute::Execute,
+            Command::FloatTime(c) => {
+                c.qos.se = SelectExecute::Execute;
+                c.time = CP56Time2a::default();
+            }
+            Command::Bitstring(_) => {}
+            Command::BitstringTime(c) => c.time = CP56Time2a::default(),
+        }
+        command
+    }
+    /// Time tag
+    pub fn time(&self) -> Option<&CP56Time2a> {
+        match self {
+            Command::SingleTime(c) => Some(&c.time),
+            Command::DoubleTime(c) => Some(&c.time),
+            Command::RegulatingTime(c) => Some(&c.time),
+            Command::NormalizedTime(c) => Some(&c.time),
+            Command::ScaledTime(c) => Some(&c.time),
+            Command::FloatTime(c) => Some(&c.time),
+            Command::BitstringTime(c) => Some(&c.time),
+            _ => None,
+        }
+    }
+}
+
+/// Command handler result
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum CommandResult {
+    /// The command is executed: positive ActCon, then ActTerm (selections are confirmed with
+    /// ActCon only)
+    Completed,
+    /// The command is accepted and being executed: positive ActCon, ActTerm is sent later (see
+    /// [`CommandDispatcher::terminate`])
+    Accepted,
+    /// The command is rejected: negative ActCon
+    Rejected,
+}
+
+/// Process command handler
+pub trait CommandHandler {
+    /// Handle a command, `originator` is the originator address of the master
+    fn handle_command(
+        &mut self,
+        ca: u16,
+        ioa: u32,
+        command: &Command,
+        originator: u8,
+    ) -> CommandResult;
+}
+
+impl<F> CommandHandler for F
+where
+    F: FnMut(u16, u32, &Command, u8) -> CommandResult,
+{
+    fn handle_command(
+        &mut self,
+        ca: u16,
+        ioa: u32,
+        command: &Command,
+        originator: u8,
+    ) -> CommandResult {
+        self(ca, ioa, command, originator)
+    }
+}
+
+/// Process command dispatcher
+///
+/// Decodes process commands (C_SC, C_DC, C_RC, C_SE, C_BO with and without time tags), calls
+/// the handler for registered points and builds the replies. Commands for unknown common
+/// addresses, unknown information object addresses and with unsupported causes of transmission
+/// are mirrored with the corresponding negative COT (46, 47, 45).
+///
+/// Select-before-operate is enforced by the [`SboTracker`] according to the point control
+/// modes: commands which violate the select/execute sequence are rejected without calling the
+/// handler, deactivation cancels the selection.
+///
+/// If a [`TimeTagValidator`] is set, time-tagged commands with stale, future or invalid time
+/// tags are discarded with a negative confirmation as well.
+#[derive(Debug)]
+pub struct CommandDispatcher {
+    points: BTreeSet<(u16, u32)>,
+    sbo: SboTracker,
+    time_tags: Option<TimeTagValidator>,
+    clock: Arc<dyn Clock>,
+    // accepted commands, waiting for termination
+    pending: Vec<Telegram104_I>,
+}
+
+impl Default for CommandDispatcher {
+    fn default() -> Self {
+        Self {
+            points: BTreeSet::new(),
+            sbo: SboTracker::default(),
+            time_tags: None,
+            clock: Arc::new(SystemClock),
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl CommandDispatcher {
+    /// Create a new dispatcher
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Set the clock (selection timeouts and time tags, default: system clock), a clock set
+    /// explicitly for the time tag validator takes precedence
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.sbo = std::mem::take(&mut self.sbo).with_clock(clock.clone());
+        if let Some(validator) = self.time_tags.as_mut() {
+            validator.inherit_clock(&clock);
+        }
+        self.clock = clock;
+        self
+    }
+    /// Validate time tags of time-tagged commands
+    pub fn with_time_tag_validator(mut self, mut validator: TimeTagValidator) -> Self {
+        validator.inherit_clock(&self.clock);
+        self.time_tags = Some(validator);
+        self
+    }
+    /// Register a command point
+    pub fn add_point(&mut self, ca: u16, ioa: u32) -> Result<(), Error> {
+        if !self.points.insert((ca, ioa)) {
+            return Err(Error::invalid_config(format!(
+                "command point {}/{} already exists",
+                ca, ioa
+            )));
+        }
+        Ok(())
+    }
+    /// Register a command point with the control mode
+    pub fn add_point_with_mode(
+        &mut self,
+        ca: u16,
+        ioa: u32,
+        mode: ControlMode,
+    ) -> Result<(), Error> {
+        self.add_point(ca, ioa)?;
+        self.sbo.set_mode(ca, ioa, mode);
+        Ok(())
+    }
+    /// Select-before-operate tracker
+    pub fn sbo(&self) -> &SboTracker {
+        &self.sbo
+    }
+    /// Select-before-operate tracker (mutable)
+    pub fn sbo_mut(&mut self) -> &mut SboTracker {
+        &mut self.sbo
+    }
+    /// Handle a command telegram, returns the replies
+    pub fn handle<H>(
+        &mut self,
+        handler: &mut H,
+        telegram: &Telegram104_I,
+    ) -> Result<Vec<Telegram104_I>, Error>
+    where
+        H: CommandHandler + ?Sized,
+    {
+        let [object] = telegram.iou() else {
+            return Err(Error::invalid_data(
+                "commands must have a single information object",
+            ));
+        };
+        let Some(command) = Command::decode(telegram.data_type(), object.value()) else {
+            return Err(Error::invalid_data(format!(
+                "not a process command: {:?}",
+                telegram.data_type()
+            )));
+        };
+        let reply = |cot| telegram.clone().with_cot(cot);
+        let (ca, ioa) = (telegram.adsu(), object.address());
+        if !self.points.iter().any(|(c, _)| *c == ca) {
+            return Ok(vec![reply(COT::UnknownAsduAddress).with_negative()]);
+        }
+        if !self.points.contains(&(ca, ioa)) {
+            return Ok(vec![reply(COT::UnknownObjectAddress).with_negative()]);
+        }
+        match telegram.cot() {
+            COT::Act => {}
+            COT::Deact => {
+                let confirmation = reply(COT::DeactCon);
+                return Ok(vec![if self.sbo.cancel(ca, ioa) {
+                    confirmation
+                } else {
+                    confirmation.with_negative()
+                }]);
+            }
+            _ => return Ok(vec![reply(COT::UnknownCause).with_negative()]),
+        }
+        if let (Some(validator), Some(time)) = (&self.time_tags, command.time())
+            && validator.check(time) != TimeTagCheck::Valid
+        {
+            return Ok(vec![reply(COT::ActCon).with_negative()]);
+        }
+        if self.sbo.check(ca, ioa, &command, telegram.originator()) != SboCheck::Allowed {
+            return Ok(vec![reply(COT::ActCon).with_negative()]);
+        }
+        let result = handler.handle_command(ca, ioa, &command, telegram.originator());
+        if command.is_select() && result == CommandResult::Rejected {
+            self.sbo.cancel(ca, ioa);
+        }
+        Ok(match result {
+            CommandResult::Completed if command.is_select() => vec![reply(COT::ActCon)],
+            CommandResult::Completed => vec![reply(COT::ActCon), reply(COT::ActTerm)],
+            CommandResult::Accepted => {
+                self.pending.retain(|t| !same_point(t, ca, ioa));
+                if !command.is_select() {
+                    self.pending.push(telegram.clone());
+                }
+                vec![reply(COT::ActCon)]
+            }
+            CommandResult::Rejected => vec![reply(COT::ActCon).with_negative()],
+        })
+    }
+    /// Terminate an accepted command, returns ActTerm if a command is being executed for the
+    /// point
+    pub fn terminate(&mut self, ca: u16, ioa: u32) -> Option<Telegram104_I> {
+        let pos = self.pending.iter().position(|t| same_point(t, ca, ioa))?;
+        Some(self.pending.remove(pos).with_cot(COT::ActTerm))
+    }
+}
+
+fn same_point(telegram: &Telegram104_I, ca: u16, ioa: u32) -> bool {
+    telegram.adsu() == ca && telegram.iou().first().is_some_and(|i| i.address() == ioa)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use bma_ts::Timestamp;
+    use chrono::{TimeZone, Utc};
+
+    use super::{Command, CommandDispatcher, CommandResult};
+    use crate::{
+        clock::ManualClock,
+        outstation::{ControlMode, TimeTagValidator},
+        telegram104::Telegram104_I,
+        types::{
+            COT, DataBuffer,
+            datatype::{
+                C_BO_NA_1, C_DC_NA_1, C_IC_NA_1, C_SC_NA_1, C_SC_TA_1, DCO, DPI, DataType, SCO,
+                SPI, SelectExecute,
+            },
+            time::{CP56Time2a, TimePolicy},
+        },
+    };
+
+    fn command(
+        data_type: DataType,
+        cot: COT,
+        ioa: u32,
+        value: impl Into<DataBuffer>,
+    ) -> Telegram104_I {
+        let mut telegram = Telegram104_I::new(data_type, cot, 1).with_originator(3);
+        telegram.append_iou(ioa, value);
+        telegram
+    }
+
+    fn cots(reply: &[Telegram104_I]) -> Vec<(COT, bool)> {
+        reply.iter().map(|t| (t.cot(), t.is_negative())).collect()
+    }
+
+    fn execute() -> C_SC_NA_1 {
+        C_SC_NA_1 {
+            sco: SCO {
+                se: SelectExecute::Execute,
+                scs: SPI::On,
+                ..SCO::default()
+            },
+        }
+    }
+
+    #[test]
+    fn command_dispatch() {
+        let mut dispatcher = CommandDispatcher::new();
+        dispatcher.add_point(1, 100).unwrap();
+        dispatcher.add_point(1, 101).unwrap();
+        dispatcher.add_point(1, 102).unwrap();
+        assert!(dispatcher.add_point(1, 100).is_err());
+        let mut received = Vec::new();
+        let mut handler = |ca, ioa, command: &Command, originator| {
+            received.push((ca, ioa, command.clone(), originator));
+            match ioa {
+                100 => CommandResult::Completed,
+                101 => CommandResult::Accepted,
+                _ => CommandResult::Rejected,
+            }
+        };
+        let reply = dispatcher
+            .handle(
+                &mut handler,
+                &command(DataType::C_SC_NA_1, COT::Act, 100, execute()),
+            )
+            .unwrap();
+        assert_eq!(cots(&reply), [(COT::ActCon, false), (COT::ActTerm, false)]);
+        assert_eq!(reply[0].originator(), 3);
+        let dco = C_DC_NA_1 {
+            dco: DCO {
+                se: SelectExecute::Execute,
+                dcs: DPI::Off,
+                ..DCO::default()
+            },
+        };
+        let reply = dispatcher
+            .handle(
+                &mut handler,
+                &command(DataType::C_DC_NA_1, COT::Act, 101, dco.clone()),
+            )
+            .unwrap();
+        assert_eq!(cots(&reply), [(COT::ActCon, false)]);
+        let term = dispatcher.terminate(1, 101).unwrap();
+        assert_eq!(term.cot(), COT::ActTerm);
+        assert_eq!(term.data_type(), DataType::C_DC_NA_1);
+        assert!(dispatcher.terminate(1, 101).is_none());
+        let reply = dispatcher
+            .handle(
+                &mut handler,
+                &command(DataType::C_BO_NA_1, COT::Act, 102, C_BO_NA_1::default()),
+            )
+            .unwrap();
+        assert_eq!(cots(&reply), [(COT::ActCon, true)]);
+        // selections are confirmed only
+        let reply = dispatcher
+            .handle(
+                &mut handler,
+                &command(DataType::C_SC_NA_1, COT::Act, 100, C_SC_NA_1::default()),
+            )
+            .unwrap();
+        assert_eq!(cots(&reply), [(COT::ActCon, false)]);
+        let reply = dispatcher
+            .handle(
+                &mut handler,
+                &command(DataType::C_SC_TA_1, COT::Act, 100, C_SC_TA_1::default()),
+            )
+            .unwrap();
+        assert_eq!(cots(&reply), [(COT::ActCon, false)]);
+        assert_eq!(received.len(), 5);
+        assert_eq!(received[1], (1, 101, Command::Double(dco), 3));
+        assert!(received[4].2.time().is_some());
+    }
+
+    #[test]
+    fn command_errors() {
+        let mut dispatcher = CommandDispatcher::new();
+        dispatcher.add_point(1, 100).unwrap();
+        let mut handler = |_, _, _: &Command, _| -> CommandResult { panic!("not expected") };
+        let reply = dispatcher
+            .handle(
+                &mut handler,
+                &command(DataType::C_SC_NA_1, COT::Act, 200, execute()),
+            )
+            .unwrap();
+        assert_eq!(cots(&reply), [(COT::UnknownObjectAddress, true)]);
+        assert_eq!(reply[0].iou()[0].address(), 200);
+        let reply = dispatcher
+            .handle(
+                &mut handler,
+                &command(DataType::C_SC_NA_1, COT::Act, 100, execute()).with_adsu(2),
+            )
+            .unwrap();
+        assert_eq!(cots(&reply), [(COT::UnknownAsduAddress, true)]);
+        let reply = dispatcher
+            .handle(
+                &mut handler,
+                &command(DataType::C_SC_NA_1, COT::Spontan, 100, execute()),
+            )
+            .unwrap();
+        assert_eq!(cots(&reply), [(COT::UnknownCause, true)]);
+        assert!(
+            dispatcher
+                .handle(
+                    &mut handler,
+                    &command(DataType::C_IC_NA_1, COT::Act, 0, C_IC_NA_1::default())
+                )
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn command_select_before_operate() {
+        let clock = Arc::new(ManualClock::default());
+        let mut dispatcher = CommandDispatcher::new().with_clock(clock.clone());
+        dispatcher
+            .add_point_with_mode(
+                1,
+                100,
+                ControlMode::SelectBeforeOperate(Duration::from_secs(5)),
+            )
+            .unwrap();
+        let mut executed = 0;
+        let mut handler = |_, _, command: &Command, _| {
+            if !command.is_select() {
+                executed += 1;
+            }
+            CommandResult::Completed
+        };
+        let select = C_SC_NA_1 {
+            sco: SCO {
+                se: SelectExecute::Select,
+                scs: SPI::On,
+                ..SCO::default()
+            },
+        };
+        // execute without selection
+        let reply = dispatcher
+            .handle(
+                &mut handler,
+                &command(DataType::C_SC_NA_1, COT::Act, 100, execute()),
+            )
+            .unwrap();
+        assert_eq!(cots(&reply), [(COT::ActCon, true)]);
+        let reply = dispatcher
+            .handle(
+                &mut handler,
+                &command(DataType::C_SC_NA_1, COT::Act, 100, select.clone()),
+            )
+            .unwrap();
+        assert_eq!(cots(&reply), [(COT::ActCon, false)]);
+        assert!(dispatcher.sbo().is_selected(1, 100));
+        clock.advance(Duration::from_secs(1));
+        let reply = dispatcher
+            .handle(
+                &mut handler,
+                &command(DataType::C_SC_NA_1, COT::Act, 100, execute()),
+            )
+            .unwrap();
+        assert_eq!(cots(&reply), [(COT::ActCon, false), (COT::ActTerm, false)]);
+        // deactivation cancels the selection
+        dispatcher
+            .handle(
+                &mut handler,
+                &command(DataType::C_SC_NA_1, COT::Act, 100, select.clone()),
+            )
+            .unwrap();
+        let reply = dispatcher
+            .handle(
+                &mut handler,
+                &command(DataType::C_SC_NA_1, COT::Deact, 100, select.clone()),
+            )
+            .unwrap();
+        assert_eq!(cots(&reply), [(COT::DeactCon, false)]);
+        let reply = dispatcher
+            .handle(
+                &mut handler,
+                &command(DataType::C_SC_NA_1, COT::Deact, 100, select),
+            )
+            .unwrap();
+        assert_eq!(cots(&reply), [(COT::DeactCon, true)]);
+        let reply = dispatcher
+            .handle(
+                &mut handler,
+                &command(DataType::C_SC_NA_1, COT::Act, 100, execute()),
+            )
+            .unwrap();
+        assert_eq!(cots(&reply), [(COT::ActCon, true)]);
+        assert_eq!(executed, 1);
+    }
+
+    #[test]
+    fn command_time_tags() {
+        let now: Timestamp = Utc
+            .with_ymd_and_hms(2024, 7, 1, 12, 0, 0)
+            .unwrap()
+            .try_into()
+            .unwrap();
+        // the validator uses the clock of the dispatcher
+        let mut dispatcher = CommandDispatcher::new()
+            .with_time_tag_validator(TimeTagValidator::new(Duration::from_secs(5)))
+            .with_clock(Arc::new(ManualClock::new(now)));
+        dispatcher.add_point(1, 100).unwrap();
+        let mut handler = |_, _, _: &Command, _| CommandResult::Completed;
+        let timed = |t: Timestamp| C_SC_TA_1 {
+            sco: execute().sco,
+            time: CP56Time2a::from_timestamp(t, &TimePolicy::Utc).unwrap(),
+        };
+        let reply = dispatcher
+            .handle(
+                &mut handler,
+                &command(
+                    DataType::C_SC_TA_1,
+                    COT::Act,
+                    100,
+                    timed(now - Duration::from_secs(1)),
+                ),
+            )
+            .unwrap();
+        assert_eq!(cots(&reply), [(COT::ActCon, false), (COT::ActTerm, false)]);
+        let reply = dispatcher
+            .handle(
+                &mut handler,
+                &command(
+                    DataType::C_SC_TA_1,
+                    COT::Act,
+                    100,
+                    timed(now - Duration::from_secs(30)),
+                ),
+            )
+            .unwrap();
+        assert_eq!(cots(&reply), [(COT::ActCon, true)]);
+        // commands without time tags are not checked
+        let reply = dispatcher
+            .handle(
+                &mut handler,
+                &command(DataType::C_SC_NA_1, COT::Act, 100, execute()),
+            )
+            .unwrap();
+        assert_eq!(cots(&reply), [(COT::ActCon, false), (COT::ActTerm, false)]);
+        // unless it has got its own one
+        let mut dispatcher = CommandDispatcher::new()
+            .with_clock(Arc::new(ManualClock::new(now)))
+            .with_time_tag_validator(
+                TimeTagValidator::new(Duration::from_secs(5))
+                    .with_clock(Arc::new(ManualClock::new(now + Duration::from_secs(60)))),
+            );
+        dispatcher.add_point(1, 100).unwrap();
+        let reply = dispatcher
+            .handle(
+                &mut handler,
+                &command(DataType::C_SC_TA_1, COT::Act, 100, timed(now)),
+            )
+            .unwrap();
+        assert_eq!(cots(&reply), [(COT::ActCon, true)]);
+    }
+}
@@ -0,0 +1,464 @@
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    Error,
+    clock::{Clock, SystemClock},
+    events::Event,
+    telegram104::{CA_BROADCAST, MAX_IOU_LEN, Telegram104_I},
+    types::{
+        COT,
+        datatype::{BCR, C_CI_NA_1, DataType, FRZ, M_IT_NA_1, RQT, SeqQD},
+    },
+};
+
+/// The sequence number is 5 bits
+const SEQ_MODULO: u8 = 32;
+
+/// Counter group (1..=4) for RQT::ReqCo1..ReqCo4, 0 for the general request
+fn rqt_group(rqt: &RQT) -> Option<u8> {
+    Some(match rqt {
+        RQT::ReqCoGen => 0,
+        RQT::ReqCo1 => 1,
+        RQT::ReqCo2 => 2,
+        RQT::ReqCo3 => 3,
+        RQT::ReqCo4 => 4,
+        RQT::None | RQT::Other(_) => return None,
+    })
+}
+
+/// Integrated totals (counter) point
+#[derive(Debug, Clone, Default)]
+pub struct Counter {
+    group: Option<u8>,
+    value: u32,
+    carry: bool,
+    adjusted: bool,
+    invalid: bool,
+    seq: u8,
+    frozen: M_IT_NA_1,
+}
+
+impl Counter {
+    /// Counter group (1..=4)
+    pub fn group(&self) -> Option<u8> {
+        self.group
+    }
+    /// Running value
+    pub fn value(&self) -> u32 {
+        self.value
+    }
+    /// Has the running value overflowed since the last freeze
+    pub fn carry(&self) -> bool {
+        self.carry
+    }
+    /// Has the running value been adjusted since the last freeze
+    pub fn adjusted(&self) -> bool {
+        self.adjusted
+    }
+    /// Is the running value invalid
+    pub fn invalid(&self) -> bool {
+        self.invalid
+    }
+    /// Frozen value with the sequence number and the flags at the moment of the freeze
+    pub fn frozen(&self) -> &M_IT_NA_1 {
+        &self.frozen
+    }
+    fn freeze(&mut self) {
+        self.seq = (self.seq + 1) % SEQ_MODULO;
+        self.frozen = M_IT_NA_1 {
+            bcr: BCR { value: self.value },
+            seq_qd: SeqQD {
+                iv: self.invalid,
+                ca: self.adjusted,
+                cy: self.carry,
+                seq: self.seq,
+            },
+        };
+        self.carry = false;
+        self.adjusted = false;
+    }
+    fn reset(&mut self) {
+        self.value = 0;
+    }
+}
+
+/// Periodic local freeze
+#[derive(Debug, Clone)]
+struct PeriodicFreeze {
+    interval: Duration,
+    reset: bool,
+    next: Option<Instant>,
+}
+
+/// Counter interrogation responder (C_CI_NA_1)
+///
+/// Counters are frozen, reset or read by requests of the master. A freeze copies the running
+/// value into the frozen one, advances the sequence number and reports the carry and adjusted
+/// flags accumulated since the previous freeze. Read requests report the frozen values with COT
+/// ReqCoGen or ReqCo1..ReqCo4. Optionally the counters are frozen periodically by a local timer
+/// and reported as spontaneous events.
+#[derive(Debug)]
+pub struct CounterResponder {
+    counters: BTreeMap<(u16, u32), Counter>,
+    periodic_freeze: Option<PeriodicFreeze>,
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for CounterResponder {
+    fn default() -> Self {
+        Self {
+            counters: BTreeMap::new(),
+            periodic_freeze: None,
+            clock: Arc::new(SystemClock),
+        }
+    }
+}
+
+impl CounterResponder {
+    /// Create a new responder
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Set the clock (periodic freeze, default: system clock)
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    /// Freeze all counters periodically (and reset the running values if `reset` is true)
+    pub fn with_periodic_freeze(mut self, interval: Duration, reset: bool) -> Self {
+        self.periodic_freeze = Some(PeriodicFreeze {
+            interval,
+            reset,
+            next: None,
+        });
+        self
+    }
+    /// Add a counter, `group` is the counter group (1..=4)
+    pub fn add_counter(&mut self, ca: u16, ioa: u32, group: Option<u8>) -> Result<(), Error> {
+        if let Some(group) = group
+            && !(1..=4).contains(&group)
+        {
+            return Err(Error::invalid_config(format!(
+                "invalid counter group: {}",
+                group
+            )));
+        }
+        if self.counters.contains_key(&(ca, ioa)) {
+            return Err(Error::invalid_config(format!(
+                "counter {}/{} already exists",
+                ca, ioa
+            )));
+        }
+        self.counters.insert(
+            (ca, ioa),
+            Counter {
+                group,
+                ..Counter::default()
+            },
+        );
+        Ok(())
+    }
+    /// Get a counter
+    pub fn counter(&self, ca: u16, ioa: u32) -> Option<&Counter> {
+        self.counters.get(&(ca, ioa))
+    }
+    fn counter_mut(&mut self, ca: u16, ioa: u32) -> Result<&mut Counter, Error> {
+        self.counters
+            .get_mut(&(ca, ioa))
+            .ok_or_else(|| Error::invalid_data(format!("counter {}/{} not found", ca, ioa)))
+    }
+    /// Add to the running value, the carry flag is set on overflow
+    pub fn increment(&mut self, ca: u16, ioa: u32, delta: u32) -> Result<(), Error> {
+        let counter = self.counter_mut(ca, ioa)?;
+        let (value, overflow) = counter.value.overflowing_add(delta);
+        counter.value = value;
+        counter.carry |= overflow;
+        Ok(())
+    }
+    /// Set the running value (e.g. read from a meter)
+    pub fn set(&mut self, ca: u16, ioa: u32, value: u32) -> Result<(), Error> {
+        self.counter_mut(ca, ioa)?.value = value;
+        Ok(())
+    }
+    /// Adjust the running value, the adjusted flag is reported with the next freeze
+    pub fn adjust(&mut self, ca: u16, ioa: u32, value: u32) -> Result<(), Error> {
+        let counter = self.counter_mut(ca, ioa)?;
+        counter.value = value;
+        counter.adjusted = true;
+        Ok(())
+    }
+    /// Set the invalid flag
+    pub fn set_invalid(&mut self, ca: u16, ioa: u32, invalid: bool) -> Result<(), Error> {
+        self.counter_mut(ca, ioa)?.invalid = invalid;
+        Ok(())
+    }
+    /// Handle a C_CI_NA_1 request, returns the confirmations and the counter readings
+    pub fn handle(&mut self, telegram: &Telegram104_I) -> Result<Vec<Telegram104_I>, Error> {
+        if telegram.data_type() != DataType::C_CI_NA_1 {
+            return Err(Error::invalid_data(format!(
+                "unexpected data type: {:?}",
+                telegram.data_type()
+            )));
+        }
+        let reply = |cot| telegram.clone().with_cot(cot);
+        let Some(iou) = telegram.iou().first() else {
+            return Err(Error::invalid_data("no information objects"));
+        };
+        let common_addresses: Vec<u16> = if telegram.adsu() == CA_BROADCAST {
+            let mut cas: Vec<u16> = self.counters.keys().map(|(ca, _)| *ca).collect();
+            cas.dedup();
+            cas
+        } else if self.counters.keys().any(|(ca, _)| *ca == telegram.adsu()) {
+            vec![telegram.adsu()]
+        } else {
+            return Ok(vec![reply(COT::UnknownAsduAddress).with_negative()]);
+        };
+        if iou.address() != 0 {
+            return Ok(vec![reply(COT::UnknownObjectAddress).with_negative()]);
+        }
+        // counter interrogation can not be deactivated
+        if telegram.cot() != COT::Act {
+            return Ok(vec![reply(COT::UnknownCause).with_negative()]);
+        }
+        let request = C_CI_NA_1::from(iou.value());
+        let Some(group) = rqt_group(&request.rqt) else {
+            return Ok(vec![reply(COT::ActCon).with_negative()]);
+        };
+        let mut result = Vec::new();
+        for ca in common_addresses {
+            let request_ca = telegram.clone().with_adsu(ca);
+            result.push(request_ca.clone().with_cot(COT::ActCon));
+            let counters = self
+                .counters
+                .range_mut((ca, 0)..=(ca, u32::MAX))
+                .filter(|(_, c)| group == 0 || c.group == Some(group));
+            let mut readings = Vec::new();
+            for ((_, ioa), counter) in counters {
+                match request.frz {
+                    FRZ::Read => readings.push((*ioa, counter.frozen.clone())),
+                    FRZ::Freeze => counter.freeze(),
+                    FRZ::FreezeAndReset => {
+                        counter.freeze();
+                        counter.reset();
+                    }
+                    FRZ::Reset => counter.reset(),
+                }
+            }
+            let max_iou = MAX_IOU_LEN / (3 + DataType::M_IT_NA_1.size());
+            for chunk in readings.chunks(max_iou) {
+                let mut data =
+                    Telegram104_I::new(DataType::M_IT_NA_1, COT::counter_interrogated(group), ca)
+                        .with_originator(telegram.originator());
+                for (ioa, reading) in chunk {
+                    data.append_iou(*ioa, reading.clone());
+                }
+                result.push(data);
+            }
+            result.push(request_ca.with_cot(COT::ActTerm));
+        }
+        Ok(result)
+    }
+    /// Next time the counters are frozen by the local timer
+    pub fn next_freeze(&self) -> Option<Instant> {
+        self.periodic_freeze.as_ref().and_then(|p| p.next)
+    }
+    /// Process the periodic freeze, returns the frozen values as spontaneous events
+    ///
+    /// The timer starts on the first call.
+    pub fn poll(&mut self) -> Vec<Event> {
+        let Some(periodic) = self.periodic_freeze.as_mut() else {
+            return Vec::new();
+        };
+        let now = self.clock.now();
+        let Some(next) = periodic.next else {
+            periodic.next = Some(now + periodic.interval);
+            return Vec::new();
+        };
+        if now < next {
+            return Vec::new();
+        }
+        // missed periods are skipped
+        let mut next = next + periodic.interval;
+        while next <= now && !periodic.interval.is_zero() {
+            next += periodic.interval;
+        }
+        periodic.next = Some(next);
+        let reset = periodic.reset;
+        self.counters
+            .iter_mut()
+            .map(|((ca, ioa), counter)| {
+                counter.freeze();
+                if reset {
+                    counter.reset();
+                }
+                Event::new(
+                    *ca,
+                    *ioa,
+                    DataType::M_IT_NA_1,
+                    COT::Spontan,
+                    counter.frozen.clone(),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use super::CounterResponder;
+    use crate::{
+        clock::{Clock, ManualClock},
+        telegram104::Telegram104_I,
+        types::{
+            COT,
+            datatype::{C_CI_NA_1, DataType, FRZ, M_IT_NA_1, RQT},
+        },
+    };
+
+    fn request(ca: u16, frz: FRZ, rqt: RQT) -> Telegram104_I {
+        let mut telegram = Telegram104_I::new(DataType::C_CI_NA_1, COT::Act, ca);
+        telegram.append_iou(0, C_CI_NA_1 { frz, rqt });
+        telegram
+    }
+
+    fn readings_of(reply: &[Telegram104_I]) -> Vec<(COT, u32, M_IT_NA_1)> {
+        reply
+            .iter()
+            .filter(|t| t.data_type() == DataType::M_IT_NA_1)
+            .flat_map(|t| {
+                t.iou()
+                    .iter()
+                    .map(|i| (t.cot(), i.address(), M_IT_NA_1::from(i.value())))
+            })
+            .collect()
+    }
+
+    fn responder() -> CounterResponder {
+        let mut responder = CounterResponder::new();
+        responder.add_counter(1, 10, Some(1)).unwrap();
+        responder.add_counter(1, 11, Some(2)).unwrap();
+        responder.add_counter(1, 12, None).unwrap();
+        responder
+    }
+
+    #[test]
+    fn counter_freeze_and_read() {
+        let mut responder = responder();
+        assert!(responder.add_counter(1, 13, Some(5)).is_err());
+        assert!(responder.add_counter(1, 12, None).is_err());
+        responder.set(1, 10, 100).unwrap();
+        responder.set(1, 11, u32::MAX).unwrap();
+        responder.increment(1, 11, 2).unwrap();
+        responder.adjust(1, 12, 7).unwrap();
+        let reply = responder
+            .handle(&request(1, FRZ::Freeze, RQT::ReqCoGen))
+            .unwrap();
+        let cots: Vec<COT> = reply.iter().map(Telegram104_I::cot).collect();
+        assert_eq!(cots, [COT::ActCon, COT::ActTerm]);
+        responder.increment(1, 10, 5).unwrap();
+        let reply = responder
+            .handle(&request(1, FRZ::Read, RQT::ReqCoGen))
+            .unwrap();
+        assert_eq!(reply.first().unwrap().cot(), COT::ActCon);
+        assert_eq!(reply.last().unwrap().cot(), COT::ActTerm);
+        let readings = readings_of(&reply);
+        assert_eq!(readings.len(), 3);
+        let (cot, ioa, reading) = &readings[0];
+        assert_eq!((*cot, *ioa, reading.bcr.value), (COT::ReqCoGen, 10, 100));
+        assert_eq!(reading.seq_qd.seq, 1);
+        let (_, _, reading) = &readings[1];
+        assert_eq!(reading.bcr.value, 1);
+        assert!(reading.seq_qd.cy);
+        let (_, _, reading) = &readings[2];
+        assert_eq!(reading.bcr.value, 7);
+        assert!(reading.seq_qd.ca && !reading.seq_qd.cy);
+        // the flags are cleared by the freeze, the sequence number advances
+        responder
+            .handle(&request(1, FRZ::FreezeAndReset, RQT::ReqCo1))
+            .unwrap();
+        assert_eq!(responder.counter(1, 10).unwrap().value(), 0);
+        assert_eq!(responder.counter(1, 11).unwrap().value(), 1);
+        let reply = responder
+            .handle(&request(1, FRZ::Read, RQT::ReqCo1))
+            .unwrap();
+        let readings = readings_of(&reply);
+        assert_eq!(readings.len(), 1);
+        let (cot, ioa, reading) = &readings[0];
+        assert_eq!((*cot, *ioa, reading.bcr.value), (COT::ReqCo1, 10, 105));
+        assert_eq!(reading.seq_qd.seq, 2);
+        let reply = responder
+            .handle(&request(1, FRZ::Read, RQT::ReqCo2))
+            .unwrap();
+        let readings = readings_of(&reply);
+        assert_eq!(readings[0].2.seq_qd.seq, 1);
+        assert!(readings[0].2.seq_qd.cy);
+        responder
+            .handle(&request(1, FRZ::Reset, RQT::ReqCo2))
+            .unwrap();
+        assert_eq!(responder.counter(1, 11).unwrap().value(), 0);
+        assert!(responder.counter(1, 11).unwrap().frozen().seq_qd.cy);
+    }
+
+    #[test]
+    fn counter_errors() {
+        let mut responder = responder();
+        let reply = responder
+            .handle(&request(2, FRZ::Read, RQT::ReqCoGen))
+            .unwrap();
+        assert_eq!(reply[0].cot(), COT::UnknownAsduAddress);
+        let reply = responder.handle(&request(1, FRZ::Read, RQT::None)).unwrap();
+        assert_eq!(reply.len(), 1);
+        assert_eq!(reply[0].cot(), COT::ActCon);
+        assert!(reply[0].is_negative());
+        let reply = responder
+            .handle(&request(0xFFFF, FRZ::Read, RQT::ReqCoGen))
+            .unwrap();
+        assert_eq!(reply.len(), 3);
+        assert_eq!(reply[1].adsu(), 1);
+        let reply = responder
+            .handle(&request(1, FRZ::Read, RQT::ReqCoGen).with_cot(COT::Deact))
+            .unwrap();
+        assert_eq!(reply.len(), 1);
+        assert_eq!(reply[0].cot(), COT::UnknownCause);
+        assert!(reply[0].is_negative());
+        assert!(
+            reply[0]
+                .clone()
+                .into_telegram104()
+                .write(Vec::new())
+                .is_ok()
+        );
+        assert!(responder.increment(1, 99, 1).is_err());
+    }
+
+    #[test]
+    fn counter_periodic_freeze() {
+        let clock = Arc::new(ManualClock::default());
+        let mut responder = responder()
+            .with_clock(clock.clone())
+            .with_periodic_freeze(Duration::from_secs(60), true);
+        responder.set(1, 12, 42).unwrap();
+        let now = clock.now();
+        assert!(responder.poll().is_empty());
+        assert_eq!(responder.next_freeze(), Some(now + Duration::from_secs(60)));
+        clock.advance(Duration::from_secs(59));
+        assert!(responder.poll().is_empty());
+        clock.advance(Duration::from_secs(91));
+        let events = responder.poll();
+        assert_eq!(events.len(), 3);
+        assert!(events.iter().all(|e| e.cot() == COT::Spontan));
+        let frozen = M_IT_NA_1::from(events[2].data());
+        assert_eq!((frozen.bcr.value, frozen.seq_qd.seq), (42, 1));
+        assert_eq!(responder.counter(1, 12).unwrap().value(), 0);
+        assert_eq!(
+            responder.next_freeze(),
+            Some(now + Duration::from_secs(180))
+        );
+    }
+}
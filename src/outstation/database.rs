@@ -0,0 +1,527 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    Error,
+    events::Event,
+    types::{
+        COT, DataBuffer,
+        datatype::{
+            BSI, DIQ, DPI, DataType, M_BO_NA_1, M_BO_TB_1, M_DP_NA_1, M_DP_TB_1, M_ME_NA_1,
+            M_ME_NB_1, M_ME_NC_1, M_ME_TD_1, M_ME_TE_1, M_ME_TF_1, M_SP_NA_1, M_SP_TB_1, M_ST_NA_1,
+            M_ST_TB_1, NVA, QDS, R32, SIQ, SPI, SVA, VTI,
+        },
+        scaling::Scaling,
+        time::CP56Time2a,
+    },
+};
+
+/// Point value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// Single point (M_SP_*)
+    Single(SPI),
+    /// Double point (M_DP_*)
+    Double(DPI),
+    /// Step position (M_ST_*)
+    Step {
+        /// Position (signed 7 bits, -64..=63)
+        value: i8,
+        /// Transient state
+        transient: bool,
+    },
+    /// Bit string of 32 bits (M_BO_*)
+    Bitstring(u32),
+    /// Measured value, normalized (M_ME_NA_1, M_ME_TD_1)
+    Normalized(NVA),
+    /// Measured value, scaled (M_ME_NB_1, M_ME_TE_1)
+    Scaled(SVA),
+    /// Measured value, short floating point (M_ME_NC_1, M_ME_TF_1)
+    Float(f32),
+}
+
+impl Value {
+    /// Value as a number, for measured values only
+    fn analog(&self) -> Option<f32> {
+        match self {
+            Value::Normalized(nva) => Some(nva.to_f32()),
+            Value::Scaled(sva) => Some(sva.to_f32()),
+            Value::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+    fn same_kind(&self, other: &Value) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+    /// Has the value changed compared to the reported one, measured values are compared with
+    /// the deadband
+    fn changed(&self, reported: &Value, deadband: f32) -> bool {
+        match (self.analog(), reported.analog()) {
+            (Some(value), Some(reported)) if deadband > 0.0 => (value - reported).abs() >= deadband,
+            _ => self != reported,
+        }
+    }
+}
+
+/// Point configuration
+#[derive(Debug, Clone)]
+pub struct PointConfig {
+    value: Value,
+    qds: QDS,
+    deadband: f32,
+    scaling: Scaling,
+}
+
+impl PointConfig {
+    /// Create a new point configuration with the initial value (quality: valid)
+    pub fn new(value: Value) -> Self {
+        Self {
+            value,
+            qds: QDS::default(),
+            deadband: 0.0,
+            scaling: Scaling::default(),
+        }
+    }
+    /// Set the initial quality (e.g. not topical until the first update)
+    pub fn with_qds(mut self, qds: QDS) -> Self {
+        self.qds = qds;
+        self
+    }
+    /// Set the absolute deadband for measured values (normalized values in fractions of 1,
+    /// scaled values in raw units). Changes below the deadband are stored but not reported.
+    pub fn with_deadband(mut self, deadband: f32) -> Self {
+        self.deadband = deadband;
+        self
+    }
+    /// Set the engineering unit scaling of normalized and scaled values (default: identity)
+    pub fn with_scaling(mut self, scaling: Scaling) -> Self {
+        self.scaling = scaling;
+        self
+    }
+}
+
+/// Process image point
+#[derive(Debug, Clone)]
+pub struct Point {
+    value: Value,
+    qds: QDS,
+    time: Option<CP56Time2a>,
+    deadband: f32,
+    scaling: Scaling,
+    reported: Value,
+    reported_qds: QDS,
+}
+
+impl Point {
+    /// Current value
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+    /// Quality descriptor
+    pub fn qds(&self) -> &QDS {
+        &self.qds
+    }
+    /// Time of the last update
+    pub fn time(&self) -> Option<&CP56Time2a> {
+        self.time.as_ref()
+    }
+    /// Deadband
+    pub fn deadband(&self) -> f32 {
+        self.deadband
+    }
+    /// Engineering unit scaling
+    pub fn scaling(&self) -> &Scaling {
+        &self.scaling
+    }
+    /// Value in engineering units, for measured values only (short floating point values are
+    /// not scaled)
+    pub fn engineering(&self) -> Option<f32> {
+        match &self.value {
+            Value::Normalized(nva) => Some(self.scaling.read(nva)),
+            Value::Scaled(sva) => Some(self.scaling.read(sva)),
+            Value::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+    /// Untimed data type of the point
+    pub fn data_type(&self) -> DataType {
+        match self.value {
+            Value::Single(_) => DataType::M_SP_NA_1,
+            Value::Double(_) => DataType::M_DP_NA_1,
+            Value::Step { .. } => DataType::M_ST_NA_1,
+            Value::Bitstring(_) => DataType::M_BO_NA_1,
+            Value::Normalized(_) => DataType::M_ME_NA_1,
+            Value::Scaled(_) => DataType::M_ME_NB_1,
+            Value::Float(_) => DataType::M_ME_NC_1,
+        }
+    }
+    /// Encode the point, with a CP56Time2a time tag if requested and the point has the time
+    pub fn encode(&self, time_tag: bool) -> (DataType, DataBuffer) {
+        let qds = self.qds.clone();
+        let siq = |spi| SIQ {
+            iv: qds.iv,
+            nt: qds.nt,
+            sb: qds.sb,
+            bl: qds.bl,
+            spi,
+        };
+        let diq = |dpi| DIQ {
+            iv: qds.iv,
+            nt: qds.nt,
+            sb: qds.sb,
+            bl: qds.bl,
+            dpi,
+        };
+        let time = if time_tag { self.time.clone() } else { None };
+        match (self.value.clone(), time) {
+            (Value::Single(spi), None) => (DataType::M_SP_NA_1, M_SP_NA_1 { siq: siq(spi) }.into()),
+            (Value::Single(spi), Some(time)) => (
+                DataType::M_SP_TB_1,
+                M_SP_TB_1 {
+                    siq: siq(spi),
+                    time,
+                }
+                .into(),
+            ),
+            (Value::Double(dpi), None) => (DataType::M_DP_NA_1, M_DP_NA_1 { diq: diq(dpi) }.into()),
+            (Value::Double(dpi), Some(time)) => (
+                DataType::M_DP_TB_1,
+                M_DP_TB_1 {
+                    diq: diq(dpi),
+                    time,
+                }
+                .into(),
+            ),
+            (Value::Step { value, transient }, time) => {
+                let vti = VTI {
+                    value,
+                    transient,
+                    qds,
+                };
+                match time {
+                    None => (DataType::M_ST_NA_1, M_ST_NA_1 { vti }.into()),
+                    Some(time) => (DataType::M_ST_TB_1, M_ST_TB_1 { vti, time }.into()),
+                }
+            }
+            (Value::Bitstring(value), None) => (
+                DataType::M_BO_NA_1,
+                M_BO_NA_1 {
+                    bsi: BSI { value },
+                    qds,
+                }
+                .into(),
+            ),
+            (Value::Bitstring(value), Some(time)) => (
+                DataType::M_BO_TB_1,
+                M_BO_TB_1 {
+                    bsi: BSI { value },
+                    qds,
+                    time,
+                }
+                .into(),
+            ),
+            (Value::Normalized(nva), None) => (DataType::M_ME_NA_1, M_ME_NA_1 { nva, qds }.into()),
+            (Value::Normalized(nva), Some(time)) => {
+                (DataType::M_ME_TD_1, M_ME_TD_1 { nva, qds, time }.into())
+            }
+            (Value::Scaled(sva), None) => (DataType::M_ME_NB_1, M_ME_NB_1 { sva, qds }.into()),
+            (Value::Scaled(sva), Some(time)) => {
+                (DataType::M_ME_TE_1, M_ME_TE_1 { sva, qds, time }.into())
+            }
+            (Value::Float(value), None) => (
+                DataType::M_ME_NC_1,
+                M_ME_NC_1 {
+                    r32: R32 { value },
+                    qds,
+                }
+                .into(),
+            ),
+            (Value::Float(value), Some(time)) => (
+                DataType::M_ME_TF_1,
+                M_ME_TF_1 {
+                    r32: R32 { value },
+                    qds,
+                    time,
+                }
+                .into(),
+            ),
+        }
+    }
+}
+
+/// Outstation process image database
+///
+/// Points are keyed by (common address, information object address). Updates are compared with
+/// the last reported state: a change of the value (beyond the deadband for measured values) or
+/// of the quality produces a spontaneous event. Events carry a CP56Time2a time tag if the
+/// update has the time.
+#[derive(Debug, Default)]
+pub struct Database {
+    points: BTreeMap<(u16, u32), Point>,
+}
+
+impl Database {
+    /// Create a new database
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Add a point
+    pub fn add_point(&mut self, ca: u16, ioa: u32, config: PointConfig) -> Result<(), Error> {
+        if self.points.contains_key(&(ca, ioa)) {
+            return Err(Error::invalid_config(format!(
+                "point {}/{} already exists",
+                ca, ioa
+            )));
+        }
+        self.points.insert(
+            (ca, ioa),
+            Point {
+                reported: config.value.clone(),
+                reported_qds: config.qds.clone(),
+                value: config.value,
+                qds: config.qds,
+                time: None,
+                deadband: config.deadband,
+                scaling: config.scaling,
+            },
+        );
+        Ok(())
+    }
+    /// Remove a point
+    pub fn remove_point(&mut self, ca: u16, ioa: u32) -> Option<Point> {
+        self.points.remove(&(ca, ioa))
+    }
+    /// Get a point
+    pub fn point(&self, ca: u16, ioa: u32) -> Option<&Point> {
+        self.points.get(&(ca, ioa))
+    }
+    /// All points, ordered by common address and information object address
+    pub fn points(&self) -> impl Iterator<Item = ((u16, u32), &Point)> {
+        self.points.iter().map(|(k, v)| (*k, v))
+    }
+    /// Points of a common address, ordered by information object address
+    pub fn points_ca(&self, ca: u16) -> impl Iterator<Item = (u32, &Point)> {
+        self.points
+            .range((ca, 0)..=(ca, u32::MAX))
+            .map(|((_, ioa), v)| (*ioa, v))
+    }
+    /// Common addresses
+    pub fn common_addresses(&self) -> Vec<u16> {
+        let mut result: Vec<u16> = self.points.keys().map(|(ca, _)| *ca).collect();
+        result.dedup();
+        result
+    }
+    /// Update a point, returns a spontaneous event if the change must be reported
+    pub fn update(
+        &mut self,
+        ca: u16,
+        ioa: u32,
+        value: Value,
+        qds: QDS,
+        time: Option<CP56Time2a>,
+    ) -> Result<Option<Event>, Error> {
+        let point = self
+            .points
+            .get_mut(&(ca, ioa))
+            .ok_or_else(|| Error::invalid_data(format!("point {}/{} not found", ca, ioa)))?;
+        if !point.value.same_kind(&value) {
+            return Err(Error::invalid_data(format!(
+                "point {}/{} value type mismatch",
+                ca, ioa
+            )));
+        }
+        point.value = value;
+        point.qds = qds;
+        point.time = time;
+        if point.qds == point.reported_qds && !point.value.changed(&point.reported, point.deadband)
+        {
+            return Ok(None);
+        }
+        point.reported = point.value.clone();
+        point.reported_qds = point.qds.clone();
+        let (data_type, data) = point.encode(true);
+        Ok(Some(Event::new(ca, ioa, data_type, COT::Spontan, data)))
+    }
+    /// Update a measured value point with a value in engineering units, converted to the raw
+    /// value with the point scaling
+    pub fn update_engineering(
+        &mut self,
+        ca: u16,
+        ioa: u32,
+        value: f32,
+        qds: QDS,
+        time: Option<CP56Time2a>,
+    ) -> Result<Option<Event>, Error> {
+        let point = self
+            .point(ca, ioa)
+            .ok_or_else(|| Error::invalid_data(format!("point {}/{} not found", ca, ioa)))?;
+        let value = match &point.value {
+            Value::Normalized(nva) => {
+                let mut nva = nva.clone();
+                point.scaling.write(&mut nva, value);
+                Value::Normalized(nva)
+            }
+            Value::Scaled(sva) => {
+                let mut sva = sva.clone();
+                point.scaling.write(&mut sva, value);
+                Value::Scaled(sva)
+            }
+            Value::Float(_) => Value::Float(value),
+            _ => {
+                return Err(Error::invalid_data(format!(
+                    "point {}/{} is not a measured value",
+                    ca, ioa
+                )));
+            }
+        };
+        self.update(ca, ioa, value, qds, time)
+    }
+    /// Update the quality of a point, keeping the value (e.g. invalidate on a source failure)
+    pub fn update_qds(
+        &mut self,
+        ca: u16,
+        ioa: u32,
+        qds: QDS,
+        time: Option<CP56Time2a>,
+    ) -> Result<Option<Event>, Error> {
+        let value = self
+            .point(ca, ioa)
+            .ok_or_else(|| Error::invalid_data(format!("point {}/{} not found", ca, ioa)))?
+            .value
+            .clone();
+        self.update(ca, ioa, value, qds, time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Database, PointConfig, Value};
+    use crate::types::{
+        COT,
+        datatype::{DPI, DataType, M_DP_TB_1, M_ME_NA_1, M_ME_NB_1, M_SP_NA_1, NVA, QDS, SPI, SVA},
+        scaling::Scaling,
+        time::CP56Time2a,
+    };
+
+    #[test]
+    fn database_change_detection() {
+        let mut db = Database::new();
+        db.add_point(1, 100, PointConfig::new(Value::Single(SPI::Off)))
+            .unwrap();
+        db.add_point(1, 101, PointConfig::new(Value::Double(DPI::Off)))
+            .unwrap();
+        assert!(
+            db.add_point(1, 100, PointConfig::new(Value::Single(SPI::Off)))
+                .is_err()
+        );
+        assert!(
+            db.update(1, 100, Value::Single(SPI::Off), QDS::default(), None)
+                .unwrap()
+                .is_none()
+        );
+        let event = db
+            .update(1, 100, Value::Single(SPI::On), QDS::default(), None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(event.cot(), COT::Spontan);
+        assert_eq!((event.adsu(), event.iou_addr()), (1, 100));
+        assert_eq!(event.tid(), DataType::M_SP_NA_1);
+        assert_eq!(M_SP_NA_1::from(event.data()).siq.spi, SPI::On);
+        // quality changes are reported
+        let event = db
+            .update_qds(
+                1,
+                100,
+                QDS {
+                    iv: true,
+                    ..QDS::default()
+                },
+                None,
+            )
+            .unwrap()
+            .unwrap();
+        assert!(M_SP_NA_1::from(event.data()).siq.iv);
+        // time tagged updates produce time tagged events
+        let time = CP56Time2a::default();
+        let event = db
+            .update(1, 101, Value::Double(DPI::On), QDS::default(), Some(time))
+            .unwrap()
+            .unwrap();
+        assert_eq!(event.tid(), DataType::M_DP_TB_1);
+        assert_eq!(M_DP_TB_1::from(event.data()).diq.dpi, DPI::On);
+        assert!(
+            db.update(1, 101, Value::Single(SPI::On), QDS::default(), None)
+                .is_err()
+        );
+        assert!(
+            db.update(2, 101, Value::Double(DPI::On), QDS::default(), None)
+                .is_err()
+        );
+        assert_eq!(db.points_ca(1).count(), 2);
+        assert_eq!(db.common_addresses(), [1]);
+    }
+
+    #[test]
+    fn database_deadband() {
+        let mut db = Database::new();
+        db.add_point(
+            1,
+            200,
+            PointConfig::new(Value::Scaled(SVA { value: 100 })).with_deadband(10.0),
+        )
+        .unwrap();
+        let scaled = |value: i16| Value::Scaled(SVA { value });
+        // the change is accumulated against the last reported value
+        for value in [105, 109, 95] {
+            assert!(
+                db.update(1, 200, scaled(value), QDS::default(), None)
+                    .unwrap()
+                    .is_none()
+            );
+            assert_eq!(db.point(1, 200).unwrap().value(), &scaled(value));
+        }
+        let event = db
+            .update(1, 200, scaled(110), QDS::default(), None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(M_ME_NB_1::from(event.data()).sva.value, 110);
+        let event = db
+            .update(1, 200, scaled(-5), QDS::default(), None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(M_ME_NB_1::from(event.data()).sva.value, -5);
+        assert!(
+            db.update(1, 200, scaled(0), QDS::default(), None)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn database_scaling() {
+        let mut db = Database::new();
+        db.add_point(
+            1,
+            300,
+            PointConfig::new(Value::Normalized(NVA::default())).with_scaling(
+                Scaling::new(-1.0..=1.0, -100.0..=100.0)
+                    .unwrap()
+                    .with_unit("A"),
+            ),
+        )
+        .unwrap();
+        db.add_point(1, 301, PointConfig::new(Value::Single(SPI::Off)))
+            .unwrap();
+        let event = db
+            .update_engineering(1, 300, -50.0, QDS::default(), None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(M_ME_NA_1::from(event.data()).nva.value, -16384);
+        let point = db.point(1, 300).unwrap();
+        assert_eq!(point.scaling().unit(), Some("A"));
+        assert!((point.engineering().unwrap() + 50.0).abs() < f32::EPSILON);
+        assert!(
+            db.update_engineering(1, 301, 1.0, QDS::default(), None)
+                .is_err()
+        );
+    }
+}
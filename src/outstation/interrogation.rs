@@ -0,0 +1,370 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::{
+    Error,
+    telegram104::{CA_BROADCAST, MAX_IOU_LEN, Telegram104_I},
+    types::{
+        COT,
+        datatype::{C_IC_NA_1, DataType, QOI},
+    },
+};
+
+use super::Database;
+
+/// Interrogation group (1..=16) for QOI::Inro1..Inro16, 0 for the station interrogation
+fn qoi_group(qoi: &QOI) -> Option<u8> {
+    Some(match qoi {
+        QOI::Inrogen => 0,
+        QOI::Inro1 => 1,
+        QOI::Inro2 => 2,
+        QOI::Inro3 => 3,
+        QOI::Inro4 => 4,
+        QOI::Inro5 => 5,
+        QOI::Inro6 => 6,
+        QOI::Inro7 => 7,
+        QOI::Inro8 => 8,
+        QOI::Inro9 => 9,
+        QOI::Inro10 => 10,
+        QOI::Inro11 => 11,
+        QOI::Inro12 => 12,
+        QOI::Inro13 => 13,
+        QOI::Inro14 => 14,
+        QOI::Inro15 => 15,
+        QOI::Inro16 => 16,
+        QOI::Unused | QOI::Other(_) => return None,
+    })
+}
+
+/// An interrogation in progress
+#[derive(Debug)]
+struct Interrogation {
+    request: Telegram104_I,
+    // 0 for the station interrogation
+    group: u8,
+    // point addresses, ordered by data type and address
+    points: VecDeque<(DataType, u32)>,
+}
+
+/// General and group interrogation responder (C_IC_NA_1)
+///
+/// An accepted request is confirmed with ActCon, the points are then returned by
+/// [`InterrogationResponder::next`] in packed telegrams (COT Inrogen or Inro1..Inro16) and the
+/// interrogation is completed with ActTerm.
+///
+/// The point values are read from the database when the telegrams are built, not when the
+/// request is received. Spontaneous events which occur during the interrogation must be sent
+/// before the following interrogation telegrams: so a point interrogated after a change is
+/// reported with the changed value, and a point interrogated before a change is followed by the
+/// spontaneous event, the master never sees an older value after a newer one.
+#[derive(Debug, Default)]
+pub struct InterrogationResponder {
+    // group membership bitmask, bit 0 = group 1
+    groups: BTreeMap<(u16, u32), u16>,
+    active: VecDeque<Interrogation>,
+}
+
+impl InterrogationResponder {
+    /// Create a new responder
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Set interrogation groups (1..=16) of a point. All points are included into the station
+    /// (general) interrogation, group interrogations include group members only.
+    pub fn set_groups(&mut self, ca: u16, ioa: u32, groups: &[u8]) -> Result<(), Error> {
+        let mut mask = 0;
+        for group in groups {
+            if !(1..=16).contains(group) {
+                return Err(Error::invalid_config(format!(
+                    "invalid interrogation group: {}",
+                    group
+                )));
+            }
+            mask |= 1 << (group - 1);
+        }
+        if mask == 0 {
+            self.groups.remove(&(ca, ioa));
+        } else {
+            self.groups.insert((ca, ioa), mask);
+        }
+        Ok(())
+    }
+    /// Interrogation groups of a point
+    pub fn groups(&self, ca: u16, ioa: u32) -> Vec<u8> {
+        let mask = self.groups.get(&(ca, ioa)).copied().unwrap_or_default();
+        (1..=16).filter(|g| mask & (1 << (g - 1)) != 0).collect()
+    }
+    /// Is an interrogation in progress
+    pub fn is_active(&self) -> bool {
+        !self.active.is_empty()
+    }
+    /// Handle a C_IC_NA_1 request, returns the confirmations
+    ///
+    /// A request for the broadcast address is confirmed and processed for each common address
+    /// of the database. A new request for a common address restarts the interrogation in
+    /// progress.
+    pub fn handle(
+        &mut self,
+        db: &Database,
+        telegram: &Telegram104_I,
+    ) -> Result<Vec<Telegram104_I>, Error> {
+        if telegram.data_type() != DataType::C_IC_NA_1 {
+            return Err(Error::invalid_data(format!(
+                "unexpected data type: {:?}",
+                telegram.data_type()
+            )));
+        }
+        let reply = |cot| telegram.clone().with_cot(cot);
+        let Some(iou) = telegram.iou().first() else {
+            return Err(Error::invalid_data("no information objects"));
+        };
+        let common_addresses = if telegram.adsu() == CA_BROADCAST {
+            db.common_addresses()
+        } else {
+            let ca = telegram.adsu();
+            if db.points_ca(ca).next().is_none() {
+                return Ok(vec![reply(COT::UnknownAsduAddress).with_negative()]);
+            }
+            vec![ca]
+        };
+        if iou.address() != 0 {
+            return Ok(vec![reply(COT::UnknownObjectAddress).with_negative()]);
+        }
+        let qoi = C_IC_NA_1::from(iou.value()).qoi;
+        match telegram.cot() {
+            COT::Act => {
+                let Some(group) = qoi_group(&qoi) else {
+                    return Ok(vec![reply(COT::ActCon).with_negative()]);
+                };
+                let mut result = Vec::with_capacity(common_addresses.len());
+                for ca in common_addresses {
+                    self.active.retain(|i| i.request.adsu() != ca);
+                    let request = telegram.clone().with_adsu(ca);
+                    result.push(request.clone().with_cot(COT::ActCon));
+                    let mut points: Vec<(DataType, u32)> = db
+                        .points_ca(ca)
+                        .filter(|(ioa, _)| {
+                            group == 0
+                                || self.groups.get(&(ca, *ioa)).copied().unwrap_or_default()
+                                    & (1 << (group - 1))
+                                    != 0
+                        })
+                        .map(|(ioa, point)| (point.data_type(), ioa))
+                        .collect();
+                    points.sort_by_key(|(data_type, ioa)| (*data_type as u8, *ioa));
+                    self.active.push_back(Interrogation {
+                        request,
+                        group,
+                        points: points.into(),
+                    });
+                }
+                Ok(result)
+            }
+            COT::Deact => {
+                let mut result = Vec::with_capacity(common_addresses.len());
+                for ca in common_addresses {
+                    let len = self.active.len();
+                    self.active.retain(|i| i.request.adsu() != ca);
+                    let confirmation = reply(COT::DeactCon).with_adsu(ca);
+                    result.push(if self.active.len() == len {
+                        confirmation.with_negative()
+                    } else {
+                        confirmation
+                    });
+                }
+                Ok(result)
+            }
+            _ => Ok(vec![reply(COT::UnknownCause).with_negative()]),
+        }
+    }
+    /// Build the next interrogation telegram, `None` if there is no interrogation in progress
+    pub fn next(&mut self, db: &Database) -> Option<Telegram104_I> {
+        let interrogation = self.active.front_mut()?;
+        let ca = interrogation.request.adsu();
+        let mut telegram: Option<Telegram104_I> = None;
+        while let Some(&(data_type, ioa)) = interrogation.points.front() {
+            let max_iou = MAX_IOU_LEN / (3 + data_type.size());
+            if let Some(ref t) = telegram
+                && (t.data_type() != data_type || t.iou().len() >= max_iou)
+            {
+                break;
+            }
+            interrogation.points.pop_front();
+            // the point may have been removed or changed since the request
+            let Some(point) = db.point(ca, ioa) else {
+                continue;
+            };
+            let (point_type, value) = point.encode(false);
+            if point_type != data_type {
+                continue;
+            }
+            telegram
+                .get_or_insert_with(|| {
+                    Telegram104_I::new(data_type, COT::interrogated(interrogation.group), ca)
+                        .with_originator(interrogation.request.originator())
+                })
+                .append_iou(ioa, value);
+        }
+        if telegram.is_some() {
+            return telegram;
+        }
+        self.active
+            .pop_front()
+            .map(|i| i.request.with_cot(COT::ActTerm))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InterrogationResponder;
+    use crate::{
+        outstation::{Database, PointConfig, Value},
+        telegram104::Telegram104_I,
+        types::{
+            COT,
+            datatype::{C_IC_NA_1, DPI, DataType, M_SP_NA_1, QDS, QOI, SPI, SVA},
+        },
+    };
+
+    fn database() -> Database {
+        let mut db = Database::new();
+        for ioa in 1..=3 {
+            db.add_point(1, ioa, PointConfig::new(Value::Single(SPI::Off)))
+                .unwrap();
+        }
+        db.add_point(1, 10, PointConfig::new(Value::Double(DPI::On)))
+            .unwrap();
+        for ioa in 100..250 {
+            db.add_point(1, ioa, PointConfig::new(Value::Scaled(SVA::default())))
+                .unwrap();
+        }
+        db.add_point(2, 1, PointConfig::new(Value::Single(SPI::On)))
+            .unwrap();
+        db
+    }
+
+    fn request(ca: u16, cot: COT, qoi: QOI) -> Telegram104_I {
+        let mut telegram = Telegram104_I::new(DataType::C_IC_NA_1, cot, ca).with_originator(5);
+        telegram.append_iou(0, C_IC_NA_1 { qoi });
+        telegram
+    }
+
+    fn run(responder: &mut InterrogationResponder, db: &Database) -> Vec<Telegram104_I> {
+        std::iter::from_fn(|| responder.next(db)).collect()
+    }
+
+    #[test]
+    fn interrogation_general() {
+        let mut db = database();
+        let mut responder = InterrogationResponder::new();
+        let reply = responder
+            .handle(&db, &request(1, COT::Act, QOI::Inrogen))
+            .unwrap();
+        assert_eq!(reply.len(), 1);
+        assert_eq!(reply[0].cot(), COT::ActCon);
+        assert!(!reply[0].is_negative());
+        assert!(responder.is_active());
+        // the first telegram is built before the change, the following ones after it
+        let first = responder.next(&db).unwrap();
+        assert_eq!(first.data_type(), DataType::M_SP_NA_1);
+        assert_eq!(first.cot(), COT::Inrogen);
+        assert_eq!(first.originator(), 5);
+        assert_eq!(first.iou().len(), 3);
+        db.update(1, 10, Value::Double(DPI::Off), QDS::default(), None)
+            .unwrap();
+        let rest = run(&mut responder, &db);
+        let summary: Vec<(DataType, COT, usize)> = rest
+            .iter()
+            .map(|t| (t.data_type(), t.cot(), t.iou().len()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (DataType::M_DP_NA_1, COT::Inrogen, 1),
+                (DataType::M_ME_NB_1, COT::Inrogen, 40),
+                (DataType::M_ME_NB_1, COT::Inrogen, 40),
+                (DataType::M_ME_NB_1, COT::Inrogen, 40),
+                (DataType::M_ME_NB_1, COT::Inrogen, 30),
+                (DataType::C_IC_NA_1, COT::ActTerm, 1),
+            ]
+        );
+        assert_eq!(rest[0].iou()[0].value()[0] & 0b11, DPI::Off as u8);
+        assert!(!responder.is_active());
+        assert!(responder.next(&db).is_none());
+    }
+
+    #[test]
+    fn interrogation_group() {
+        let db = database();
+        let mut responder = InterrogationResponder::new();
+        responder.set_groups(1, 2, &[3, 16]).unwrap();
+        responder.set_groups(1, 10, &[3]).unwrap();
+        assert!(responder.set_groups(1, 10, &[17]).is_err());
+        assert_eq!(responder.groups(1, 2), [3, 16]);
+        responder
+            .handle(&db, &request(1, COT::Act, QOI::Inro3))
+            .unwrap();
+        let telegrams = run(&mut responder, &db);
+        let summary: Vec<(DataType, COT, Vec<u32>)> = telegrams
+            .iter()
+            .map(|t| {
+                (
+                    t.data_type(),
+                    t.cot(),
+                    t.iou().iter().map(crate::types::Iou::address).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (DataType::M_SP_NA_1, COT::Inro3, vec![2]),
+                (DataType::M_DP_NA_1, COT::Inro3, vec![10]),
+                (DataType::C_IC_NA_1, COT::ActTerm, vec![0]),
+            ]
+        );
+    }
+
+    #[test]
+    fn interrogation_broadcast_and_errors() {
+        let db = database();
+        let mut responder = InterrogationResponder::new();
+        let reply = responder
+            .handle(&db, &request(0xFFFF, COT::Act, QOI::Inrogen))
+            .unwrap();
+        let cas: Vec<u16> = reply.iter().map(Telegram104_I::adsu).collect();
+        assert_eq!(cas, [1, 2]);
+        let telegrams = run(&mut responder, &db);
+        let last = telegrams.last().unwrap();
+        assert_eq!((last.cot(), last.adsu()), (COT::ActTerm, 2));
+        assert_eq!(
+            M_SP_NA_1::from(telegrams[telegrams.len() - 2].iou()[0].value())
+                .siq
+                .spi,
+            SPI::On
+        );
+        let reply = responder
+            .handle(&db, &request(3, COT::Act, QOI::Inrogen))
+            .unwrap();
+        assert_eq!(reply[0].cot(), COT::UnknownAsduAddress);
+        assert!(reply[0].is_negative());
+        let reply = responder
+            .handle(&db, &request(1, COT::Act, QOI::Other(50)))
+            .unwrap();
+        assert_eq!(reply[0].cot(), COT::ActCon);
+        assert!(reply[0].is_negative());
+        // deactivation
+        responder
+            .handle(&db, &request(1, COT::Act, QOI::Inrogen))
+            .unwrap();
+        let reply = responder
+            .handle(&db, &request(1, COT::Deact, QOI::Inrogen))
+            .unwrap();
+        assert_eq!(reply[0].cot(), COT::DeactCon);
+        assert!(!reply[0].is_negative());
+        assert!(!responder.is_active());
+        let reply = responder
+            .handle(&db, &request(1, COT::Deact, QOI::Inrogen))
+            .unwrap();
+        assert!(reply[0].is_negative());
+    }
+}
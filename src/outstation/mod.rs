@@ -0,0 +1,15 @@
+mod commands;
+mod counters;
+mod database;
+mod interrogation;
+mod sbo;
+mod time_sync;
+mod time_tag;
+
+pub use commands::{Command, CommandDispatcher, CommandHandler, CommandResult};
+pub use counters::{Counter, CounterResponder};
+pub use database::{Database, Point, PointConfig, Value};
+pub use interrogation::InterrogationResponder;
+pub use sbo::{ControlMode, SboCheck, SboTracker};
+pub use time_sync::ClockSyncHandler;
+pub use time_tag::{TimeTagCheck, TimeTagValidator};
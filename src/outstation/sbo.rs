@@ -0,0 +1,281 @@
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::clock::{Clock, SystemClock};
+
+use super::Command;
+
+/// Default selection timeout
+const DEFAULT_SELECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Command point control mode
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum ControlMode {
+    /// Commands are passed to the handler without selection tracking
+    #[default]
+    Direct,
+    /// Execute commands require a prior selection by the same originator with the same
+    /// parameters within the timeout
+    SelectBeforeOperate(Duration),
+}
+
+impl ControlMode {
+    /// Select-before-operate with the default timeout (10 seconds)
+    pub fn sbo() -> Self {
+        ControlMode::SelectBeforeOperate(DEFAULT_SELECT_TIMEOUT)
+    }
+}
+
+/// SBO check result
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SboCheck {
+    /// The command may be passed to the handler
+    Allowed,
+    /// Execute without a valid selection (none, expired, another originator or other
+    /// parameters)
+    NotSelected,
+    /// Select while the point is selected by another originator
+    SelectedByOther,
+}
+
+#[derive(Debug, Clone)]
+struct Selection {
+    originator: u8,
+    command: Command,
+    expires: Instant,
+}
+
+/// Per-point select-before-operate tracker
+///
+/// Points are direct-execute unless configured otherwise. A selection is kept until the
+/// matching execute command, a new selection by the same originator, cancellation
+/// (deactivation) or the timeout. A failed execute attempt cancels the selection as well. Other
+/// originators can not select the point until the selection is released.
+#[derive(Debug)]
+pub struct SboTracker {
+    modes: BTreeMap<(u16, u32), ControlMode>,
+    selections: BTreeMap<(u16, u32), Selection>,
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for SboTracker {
+    fn default() -> Self {
+        Self {
+            modes: BTreeMap::new(),
+            selections: BTreeMap::new(),
+            clock: Arc::new(SystemClock),
+        }
+    }
+}
+
+impl SboTracker {
+    /// Create a new tracker
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Set the clock (selection timeouts, default: system clock)
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    /// Set the control mode of a point
+    pub fn set_mode(&mut self, ca: u16, ioa: u32, mode: ControlMode) {
+        self.selections.remove(&(ca, ioa));
+        if mode == ControlMode::Direct {
+            self.modes.remove(&(ca, ioa));
+        } else {
+            self.modes.insert((ca, ioa), mode);
+        }
+    }
+    /// Control mode of a point
+    pub fn mode(&self, ca: u16, ioa: u32) -> ControlMode {
+        self.modes.get(&(ca, ioa)).copied().unwrap_or_default()
+    }
+    /// Is the point selected
+    pub fn is_selected(&self, ca: u16, ioa: u32) -> bool {
+        let now = self.clock.now();
+        self.selections
+            .get(&(ca, ioa))
+            .is_some_and(|s| s.expires > now)
+    }
+    /// Check a command (activation), registers selections and consumes them on execute
+    pub fn check(&mut self, ca: u16, ioa: u32, command: &Command, originator: u8) -> SboCheck {
+        let ControlMode::SelectBeforeOperate(timeout) = self.mode(ca, ioa) else {
+            return SboCheck::Allowed;
+        };
+        let now = self.clock.now();
+        if command.is_select() {
+            if self
+                .selections
+                .get(&(ca, ioa))
+                .is_some_and(|s| s.expires > now && s.originator != originator)
+            {
+                return SboCheck::SelectedByOther;
+            }
+            self.selections.insert(
+                (ca, ioa),
+                Selection {
+                    originator,
+                    command: command.operation(),
+                    expires: now + timeout,
+                },
+            );
+            return SboCheck::Allowed;
+        }
+        match self.selections.remove(&(ca, ioa)) {
+            Some(s)
+                if s.expires > now
+                    && s.originator == originator
+                    && s.command == command.operation() =>
+            {
+                SboCheck::Allowed
+            }
+            _ => SboCheck::NotSelected,
+        }
+    }
+    /// Cancel the selection of a point (deactivation), returns `false` if the point is not
+    /// selected
+    pub fn cancel(&mut self, ca: u16, ioa: u32) -> bool {
+        let now = self.clock.now();
+        self.selections
+            .remove(&(ca, ioa))
+            .is_some_and(|s| s.expires > now)
+    }
+    /// Remove expired selections
+    pub fn cleanup(&mut self) {
+        let now = self.clock.now();
+        self.selections.retain(|_, s| s.expires > now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use super::{ControlMode, SboCheck, SboTracker};
+    use crate::{
+        clock::ManualClock,
+        outstation::Command,
+        types::{
+            datatype::{C_DC_NA_1, C_DC_TA_1, C_SC_NA_1, DCO, DPI, SCO, SPI, SelectExecute},
+            time::CP56Time2a,
+        },
+    };
+
+    fn double(se: SelectExecute, dcs: DPI) -> Command {
+        Command::Double(C_DC_NA_1 {
+            dco: DCO {
+                se,
+                dcs,
+                ..DCO::default()
+            },
+        })
+    }
+
+    #[test]
+    fn sbo_select_execute() {
+        let mut sbo = SboTracker::new();
+        sbo.set_mode(
+            1,
+            10,
+            ControlMode::SelectBeforeOperate(Duration::from_secs(5)),
+        );
+        assert_eq!(
+            sbo.mode(1, 10),
+            ControlMode::SelectBeforeOperate(Duration::from_secs(5))
+        );
+        assert_eq!(sbo.mode(1, 11), ControlMode::Direct);
+        let select = double(SelectExecute::Select, DPI::On);
+        let execute = double(SelectExecute::Execute, DPI::On);
+        // execute without selection
+        assert_eq!(sbo.check(1, 10, &execute, 0), SboCheck::NotSelected);
+        assert_eq!(sbo.check(1, 10, &select, 0), SboCheck::Allowed);
+        assert!(sbo.is_selected(1, 10));
+        assert_eq!(sbo.check(1, 10, &execute, 0), SboCheck::Allowed);
+        // the selection is consumed
+        assert!(!sbo.is_selected(1, 10));
+        assert_eq!(sbo.check(1, 10, &execute, 0), SboCheck::NotSelected);
+        // other originator
+        sbo.check(1, 10, &select, 0);
+        assert_eq!(sbo.check(1, 10, &execute, 1), SboCheck::NotSelected);
+        // the selection is locked for other originators
+        sbo.check(1, 10, &select, 0);
+        assert_eq!(sbo.check(1, 10, &select, 1), SboCheck::SelectedByOther);
+        assert_eq!(sbo.check(1, 10, &execute, 0), SboCheck::Allowed);
+        // the same originator may select again
+        sbo.check(1, 10, &select, 0);
+        assert_eq!(sbo.check(1, 10, &select, 0), SboCheck::Allowed);
+        assert_eq!(sbo.check(1, 10, &execute, 0), SboCheck::Allowed);
+        // other parameters
+        sbo.check(1, 10, &select, 0);
+        assert_eq!(
+            sbo.check(1, 10, &double(SelectExecute::Execute, DPI::Off), 0),
+            SboCheck::NotSelected
+        );
+        // time tags are not compared
+        let timed = |se, time| {
+            Command::DoubleTime(C_DC_TA_1 {
+                dco: DCO {
+                    se,
+                    dcs: DPI::On,
+                    ..DCO::default()
+                },
+                time,
+            })
+        };
+        let mut time = CP56Time2a::default();
+        sbo.check(1, 10, &timed(SelectExecute::Select, time.clone()), 0);
+        time.ms = 1000;
+        assert_eq!(
+            sbo.check(1, 10, &timed(SelectExecute::Execute, time), 0),
+            SboCheck::Allowed
+        );
+        // other type
+        sbo.check(1, 10, &select, 0);
+        let single = Command::Single(C_SC_NA_1 {
+            sco: SCO {
+                se: SelectExecute::Execute,
+                scs: SPI::On,
+                ..SCO::default()
+            },
+        });
+        assert_eq!(sbo.check(1, 10, &single, 0), SboCheck::NotSelected);
+        // direct execute points
+        assert_eq!(sbo.check(1, 11, &select, 0), SboCheck::Allowed);
+        assert_eq!(sbo.check(1, 11, &execute, 0), SboCheck::Allowed);
+    }
+
+    #[test]
+    fn sbo_timeout_and_cancel() {
+        let clock = Arc::new(ManualClock::default());
+        let mut sbo = SboTracker::new().with_clock(clock.clone());
+        sbo.set_mode(
+            1,
+            10,
+            ControlMode::SelectBeforeOperate(Duration::from_secs(5)),
+        );
+        let select = double(SelectExecute::Select, DPI::On);
+        let execute = double(SelectExecute::Execute, DPI::On);
+        sbo.check(1, 10, &select, 0);
+        clock.advance(Duration::from_secs(4));
+        assert_eq!(sbo.check(1, 10, &select, 1), SboCheck::SelectedByOther);
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(sbo.check(1, 10, &execute, 0), SboCheck::NotSelected);
+        // expired selections may be replaced by other originators
+        sbo.check(1, 10, &select, 0);
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(sbo.check(1, 10, &select, 1), SboCheck::Allowed);
+        assert_eq!(sbo.check(1, 10, &execute, 1), SboCheck::Allowed);
+        sbo.check(1, 10, &select, 0);
+        assert!(sbo.cancel(1, 10));
+        assert!(!sbo.cancel(1, 10));
+        assert_eq!(sbo.check(1, 10, &execute, 0), SboCheck::NotSelected);
+        sbo.check(1, 10, &select, 0);
+        clock.advance(Duration::from_secs(6));
+        sbo.cleanup();
+        assert!(sbo.selections.is_empty());
+    }
+}
@@ -0,0 +1,378 @@
+use std::{
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use chrono::TimeDelta;
+
+use crate::{
+    Error,
+    clock::{self, Clock, VirtualClock},
+    telegram101::Telegram101,
+    telegram104::{CA_BROADCAST, Telegram104_I},
+    types::{
+        COT, DataBuffer, Iou,
+        datatype::{C_CD_NA_1, C_CS_NA_1, DataType},
+        time::{CP16Time2a, TimePolicy},
+    },
+};
+
+type AdjustHook = Box<dyn FnMut(TimeDelta) -> bool + Send>;
+
+enum Adjust {
+    Virtual(Arc<VirtualClock>),
+    Hook(AdjustHook),
+}
+
+impl fmt::Debug for Adjust {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Adjust::Virtual(clock) => f.debug_tuple("Virtual").field(clock).finish(),
+            Adjust::Hook(_) => f.write_str("Hook"),
+        }
+    }
+}
+
+/// Clock synchronization handler (C_CS_NA_1, C_CD_NA_1)
+///
+/// The offset between the received time (plus the transmission delay) and the station clock
+/// is applied to an adjustable virtual clock or passed to a user hook. The transmission delay
+/// is set by the master with the delay acquisition procedure of IEC 60870-5-101 (see
+/// [`ClockSyncHandler::handle_101`]): activations of C_CD_NA_1 are confirmed with the received
+/// sending delay time plus the processing time of the station, the calculated delay is
+/// received with COT Spontan.
+#[derive(Debug)]
+pub struct ClockSyncHandler {
+    ca: u16,
+    clock: Arc<dyn Clock>,
+    adjust: Adjust,
+    policy: TimePolicy,
+    delay: Duration,
+    last_offset: Option<TimeDelta>,
+}
+
+/// Reply to a clock synchronization or delay acquisition telegram
+struct Reply {
+    cot: COT,
+    ca: u16,
+    negative: bool,
+    // replaced information object value
+    value: Option<DataBuffer>,
+}
+
+impl ClockSyncHandler {
+    /// Create a new handler which adjusts the virtual clock
+    pub fn new(ca: u16, clock: Arc<VirtualClock>) -> Self {
+        Self {
+            ca,
+            clock: clock.clone(),
+            adjust: Adjust::Virtual(clock),
+            policy: TimePolicy::default(),
+            delay: Duration::ZERO,
+            last_offset: None,
+        }
+    }
+    /// Create a new handler which passes the offsets from the clock to the hook, the hook
+    /// returns `false` if the time can not be set
+    pub fn with_hook<F>(ca: u16, clock: Arc<dyn Clock>, hook: F) -> Self
+    where
+        F: FnMut(TimeDelta) -> bool + Send + 'static,
+    {
+        Self {
+            ca,
+            clock,
+            adjust: Adjust::Hook(Box::new(hook)),
+            policy: TimePolicy::default(),
+            delay: Duration::ZERO,
+            last_offset: None,
+        }
+    }
+    /// Set the time zone policy of the received time (default: UTC)
+    pub fn with_policy(mut self, policy: TimePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+    /// Transmission delay set by the master
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+    /// The offset of the last accepted synchronization
+    pub fn last_offset(&self) -> Option<TimeDelta> {
+        self.last_offset
+    }
+    /// Handle an IEC 60870-5-104 clock synchronization telegram, returns the replies
+    pub fn handle(&mut self, telegram: &Telegram104_I) -> Result<Vec<Telegram104_I>, Error> {
+        if telegram.data_type() != DataType::C_CS_NA_1 {
+            return Err(Error::invalid_data(format!(
+                "not a clock synchronization telegram: {:?}",
+                telegram.data_type()
+            )));
+        }
+        let reply = self.process(
+            telegram.data_type(),
+            telegram.cot(),
+            telegram.adsu() == CA_BROADCAST,
+            telegram.adsu(),
+            telegram.iou(),
+            self.clock.now(),
+        )?;
+        Ok(reply
+            .map(|r| {
+                let mut t = telegram.clone().with_cot(r.cot).with_adsu(r.ca);
+                if r.negative {
+                    t = t.with_negative();
+                }
+                t
+            })
+            .into_iter()
+            .collect())
+    }
+    /// Handle an IEC 60870-5-101 clock synchronization or delay acquisition telegram, received
+    /// at the instant of the handler clock, returns the replies
+    pub fn handle_101(
+        &mut self,
+        telegram: &Telegram101,
+        received: Instant,
+    ) -> Result<Vec<Telegram101>, Error> {
+        let data_type = telegram.data_type();
+        if data_type != DataType::C_CS_NA_1 && data_type != DataType::C_CD_NA_1 {
+            return Err(Error::invalid_data(format!(
+                "not a clock synchronization telegram: {:?}",
+                data_type
+            )));
+        }
+        let broadcast = telegram
+            .config()
+            .is_some_and(|config| telegram.adsu() == config.broadcast_ca());
+        let reply = self.process(
+            data_type,
+            telegram.cot(),
+            broadcast,
+            telegram.adsu(),
+            telegram.iou(),
+            received,
+        )?;
+        Ok(reply
+            .map(|r| {
+                let mut t = telegram.clone().with_cot(r.cot).with_adsu(r.ca);
+                if let Some(value) = r.value {
+                    t = t.with_iou(vec![Iou::new(0, value)]);
+                }
+                if r.negative {
+                    t = t.with_negative();
+                }
+                t
+            })
+            .into_iter()
+            .collect())
+    }
+    fn process(
+        &mut self,
+        data_type: DataType,
+        cot: COT,
+        broadcast: bool,
+        ca: u16,
+        iou: &[Iou],
+        received: Instant,
+    ) -> Result<Option<Reply>, Error> {
+        let [object] = iou else {
+            return Err(Error::invalid_data(
+                "clock synchronization must have a single information object",
+            ));
+        };
+        if ca != self.ca && !broadcast {
+            return Ok(Some(Reply {
+                cot: COT::UnknownAsduAddress,
+                ca,
+                negative: true,
+                value: None,
+            }));
+        }
+        let reply = |cot, negative| {
+            Some(Reply {
+                cot,
+                ca: self.ca,
+                negative,
+                value: None,
+            })
+        };
+        if object.address() != 0 {
+            return Ok(reply(COT::UnknownObjectAddress, true));
+        }
+        Ok(match (data_type, cot) {
+            (DataType::C_CS_NA_1, COT::Act) => {
+                let time = C_CS_NA_1::from(object.value()).time;
+                let accepted = !time.iv
+                    && time.to_timestamp(&self.policy).is_ok_and(|t| {
+                        // the station time at reception, the handling latency is not an offset
+                        let elapsed = self.clock.now().saturating_duration_since(received);
+                        let offset =
+                            clock::difference(t + self.delay, self.clock.timestamp() - elapsed);
+                        let accepted = match &mut self.adjust {
+                            Adjust::Virtual(clock) => {
+                                clock.adjust(offset);
+                                true
+                            }
+                            Adjust::Hook(hook) => hook(offset),
+                        };
+                        if accepted {
+                            self.last_offset = Some(offset);
+                        }
+                        accepted
+                    });
+                reply(COT::ActCon, !accepted)
+            }
+            // the sending delay time is returned with the processing time of the station added
+            (DataType::C_CD_NA_1, COT::Act) => {
+                let sdt = C_CD_NA_1::from(object.value()).time;
+                let processing = self.clock.now().saturating_duration_since(received);
+                let ms = (u128::from(sdt.ms) + processing.as_millis()) % 60_000;
+                let time = CP16Time2a {
+                    ms: u16::try_from(ms).unwrap_or_default(),
+                };
+                Some(Reply {
+                    value: Some(C_CD_NA_1 { time }.into()),
+                    ..reply(COT::ActCon, false).unwrap()
+                })
+            }
+            (DataType::C_CD_NA_1, COT::Spontan) => {
+                self.delay = C_CD_NA_1::from(object.value()).time.into();
+                None
+            }
+            _ => reply(COT::UnknownCause, true),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use bma_ts::Timestamp;
+    use chrono::TimeDelta;
+
+    use super::ClockSyncHandler;
+    use crate::{
+        clock::{Clock, ManualClock, VirtualClock},
+        telegram101::{Config, Telegram101},
+        telegram104::Telegram104_I,
+        types::{
+            COT,
+            datatype::{C_CD_NA_1, C_CS_NA_1, DataType},
+            time::{CP16Time2a, CP56Time2a, TimePolicy},
+        },
+    };
+
+    fn delay(cot: COT, ms: u16) -> Telegram101 {
+        let mut telegram = Telegram101::new(DataType::C_CD_NA_1, cot, 1, Config::new());
+        telegram.append_iou(
+            0,
+            C_CD_NA_1 {
+                time: CP16Time2a { ms },
+            },
+        );
+        telegram
+    }
+
+    fn sync(ca: u16, time: Timestamp) -> Telegram104_I {
+        let mut telegram = Telegram104_I::new(DataType::C_CS_NA_1, COT::Act, ca);
+        telegram.append_iou(
+            0,
+            C_CS_NA_1 {
+                time: CP56Time2a::from_timestamp(time, &TimePolicy::Utc).unwrap(),
+            },
+        );
+        telegram
+    }
+
+    #[test]
+    fn clock_sync_virtual_clock() {
+        let base = Arc::new(ManualClock::new(Timestamp::from_secs(1_700_000_000)));
+        let clock = Arc::new(VirtualClock::new(base.clone()));
+        let mut handler = ClockSyncHandler::new(1, clock.clone());
+        let reply = handler
+            .handle(&sync(0xFFFF, Timestamp::from_secs(1_700_000_005)))
+            .unwrap();
+        assert_eq!(reply.len(), 1);
+        assert_eq!((reply[0].cot(), reply[0].adsu()), (COT::ActCon, 1));
+        assert!(!reply[0].is_negative());
+        assert_eq!(handler.last_offset(), Some(TimeDelta::seconds(5)));
+        assert_eq!(clock.timestamp(), Timestamp::from_secs(1_700_000_005));
+        // delay acquisition is a 101 procedure
+        let mut telegram = Telegram104_I::new(DataType::C_CD_NA_1, COT::Act, 1);
+        telegram.append_iou(0, C_CD_NA_1::default());
+        assert!(handler.handle(&telegram).is_err());
+        let received = base.now();
+        base.advance(Duration::from_millis(20));
+        let reply = handler
+            .handle_101(&delay(COT::Act, 59_990), received)
+            .unwrap();
+        assert_eq!(reply[0].cot(), COT::ActCon);
+        // the processing time is added
+        assert_eq!(C_CD_NA_1::from(reply[0].iou()[0].value()).time.ms, 10);
+        assert!(
+            handler
+                .handle_101(&delay(COT::Spontan, 250), base.now())
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(handler.delay(), Duration::from_millis(250));
+        handler
+            .handle(&sync(1, Timestamp::from_secs(1_700_000_000)))
+            .unwrap();
+        assert_eq!(clock.timestamp(), Timestamp::from_millis(1_700_000_000_250));
+        // other stations
+        let reply = handler
+            .handle(&sync(2, Timestamp::from_secs(1_700_000_000)))
+            .unwrap();
+        assert_eq!(reply[0].cot(), COT::UnknownAsduAddress);
+        assert!(reply[0].is_negative());
+    }
+
+    #[test]
+    fn clock_sync_101_latency() {
+        let base = Arc::new(ManualClock::new(Timestamp::from_secs(1_700_000_000)));
+        let clock = Arc::new(VirtualClock::new(base.clone()));
+        let mut handler = ClockSyncHandler::new(1, clock.clone());
+        let mut telegram = Telegram101::new(DataType::C_CS_NA_1, COT::Act, 1, Config::new());
+        telegram.append_iou(
+            0,
+            C_CS_NA_1 {
+                time: CP56Time2a::from_timestamp(
+                    Timestamp::from_secs(1_700_000_005),
+                    &TimePolicy::Utc,
+                )
+                .unwrap(),
+            },
+        );
+        let received = base.now();
+        base.advance(Duration::from_millis(300));
+        let reply = handler.handle_101(&telegram, received).unwrap();
+        assert!(!reply[0].is_negative());
+        // the time spent between reception and handling is not part of the offset
+        assert_eq!(handler.last_offset(), Some(TimeDelta::seconds(5)));
+        assert_eq!(clock.timestamp(), Timestamp::from_millis(1_700_000_005_300));
+    }
+
+    #[test]
+    fn clock_sync_hook() {
+        let clock = Arc::new(ManualClock::new(Timestamp::from_secs(1_700_000_000)));
+        let mut handler =
+            ClockSyncHandler::with_hook(1, clock, |offset| offset.abs() < TimeDelta::seconds(60));
+        let reply = handler
+            .handle(&sync(1, Timestamp::from_secs(1_699_999_990)))
+            .unwrap();
+        assert!(!reply[0].is_negative());
+        assert_eq!(handler.last_offset(), Some(TimeDelta::seconds(-10)));
+        let reply = handler
+            .handle(&sync(1, Timestamp::from_secs(1_700_003_600)))
+            .unwrap();
+        assert!(reply[0].is_negative());
+        assert_eq!(handler.last_offset(), Some(TimeDelta::seconds(-10)));
+        let reply = handler
+            .handle(&sync(1, Timestamp::from_secs(1_700_000_000)).with_cot(COT::Deact))
+            .unwrap();
+        assert_eq!(reply[0].cot(), COT::UnknownCause);
+    }
+}
@@ -0,0 +1,147 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    clock::{Clock, SystemClock},
+    types::time::{CP56Time2a, TimePolicy},
+};
+
+/// Default maximum command age
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(10);
+
+/// Command time tag check result
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum TimeTagCheck {
+    /// The time tag is within the window
+    Valid,
+    /// The time tag is older than the maximum age
+    Stale,
+    /// The time tag is ahead of the clock more than allowed
+    Future,
+    /// The invalid flag is set or the time tag can not be converted
+    Invalid,
+}
+
+/// Time-tagged command validator
+///
+/// Protects against delayed or replayed commands: the CP56Time2a tag of a command is compared
+/// with the clock, commands older than the maximum age (or too far in the future) are
+/// discarded. The clock is pluggable, by default the one of the [`CommandDispatcher`] (or the
+/// system time) is used.
+///
+/// [`CommandDispatcher`]: super::CommandDispatcher
+#[derive(Debug)]
+pub struct TimeTagValidator {
+    max_age: Duration,
+    max_ahead: Duration,
+    policy: TimePolicy,
+    clock: Arc<dyn Clock>,
+    // the clock is set explicitly, not inherited from the dispatcher
+    own_clock: bool,
+}
+
+impl Default for TimeTagValidator {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_AGE)
+    }
+}
+
+impl TimeTagValidator {
+    /// Create a new validator, the allowed clock deviation into the future is the same as the
+    /// maximum age
+    pub fn new(max_age: Duration) -> Self {
+        Self {
+            max_age,
+            max_ahead: max_age,
+            policy: TimePolicy::default(),
+            clock: Arc::new(SystemClock),
+            own_clock: false,
+        }
+    }
+    /// Set the allowed deviation into the future (the master clock is ahead)
+    pub fn with_max_ahead(mut self, max_ahead: Duration) -> Self {
+        self.max_ahead = max_ahead;
+        self
+    }
+    /// Set the time zone policy of the command time tags (default: UTC)
+    pub fn with_policy(mut self, policy: TimePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+    /// Set the clock (overrides the one of the dispatcher)
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self.own_clock = true;
+        self
+    }
+    /// Use the clock of the dispatcher unless the clock is set explicitly
+    pub(crate) fn inherit_clock(&mut self, clock: &Arc<dyn Clock>) {
+        if !self.own_clock {
+            self.clock = clock.clone();
+        }
+    }
+    /// Maximum command age
+    pub fn max_age(&self) -> Duration {
+        self.max_age
+    }
+    /// Check a command time tag
+    pub fn check(&self, time: &CP56Time2a) -> TimeTagCheck {
+        if time.iv {
+            return TimeTagCheck::Invalid;
+        }
+        let Ok(t) = time.to_timestamp(&self.policy) else {
+            return TimeTagCheck::Invalid;
+        };
+        let now = self.clock.timestamp();
+        if t <= now {
+            if now.abs_diff(t) > self.max_age {
+                return TimeTagCheck::Stale;
+            }
+        } else if t.abs_diff(now) > self.max_ahead {
+            return TimeTagCheck::Future;
+        }
+        TimeTagCheck::Valid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use bma_ts::Timestamp;
+    use chrono::{TimeZone, Utc};
+
+    use super::{TimeTagCheck, TimeTagValidator};
+    use crate::{
+        clock::ManualClock,
+        types::time::{CP56Time2a, TimePolicy},
+    };
+
+    #[test]
+    fn time_tag_check() {
+        let now: Timestamp = Utc
+            .with_ymd_and_hms(2024, 7, 1, 12, 0, 0)
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let validator = TimeTagValidator::new(Duration::from_secs(10))
+            .with_max_ahead(Duration::from_secs(1))
+            .with_clock(Arc::new(ManualClock::new(now)));
+        let tag = |t: Timestamp| CP56Time2a::from_timestamp(t, &TimePolicy::Utc).unwrap();
+        assert_eq!(validator.check(&tag(now)), TimeTagCheck::Valid);
+        assert_eq!(
+            validator.check(&tag(now - Duration::from_secs(10))),
+            TimeTagCheck::Valid
+        );
+        assert_eq!(
+            validator.check(&tag(now - Duration::from_secs(11))),
+            TimeTagCheck::Stale
+        );
+        assert_eq!(
+            validator.check(&tag(now + Duration::from_secs(2))),
+            TimeTagCheck::Future
+        );
+        let mut invalid = tag(now);
+        invalid.iv = true;
+        assert_eq!(validator.check(&invalid), TimeTagCheck::Invalid);
+    }
+}
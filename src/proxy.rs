@@ -0,0 +1,1054 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    io::{self, BufReader},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{Arc, mpsc},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    Error,
+    clock::{Clock, SystemClock},
+    telegram104::{MAX_IOU_LEN, Telegram104, Telegram104_I, Telegram104_S},
+    types::{COT, Iou, datatype::DataType},
+};
+
+/// Sequence numbers are counted modulo 2^15
+const SEQ_MODULO: u16 = 32768;
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Max wait in the session loop when no frames are delayed
+const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+fn seq_next(sn: u16) -> u16 {
+    (sn + 1) % SEQ_MODULO
+}
+
+fn seq_distance(from: u16, to: u16) -> u16 {
+    (to + SEQ_MODULO - from) % SEQ_MODULO
+}
+
+/// Frame direction
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Direction {
+    /// From the master to the outstation
+    ToOutstation,
+    /// From the outstation to the master
+    ToMaster,
+}
+
+impl Direction {
+    fn reverse(self) -> Self {
+        match self {
+            Direction::ToOutstation => Direction::ToMaster,
+            Direction::ToMaster => Direction::ToOutstation,
+        }
+    }
+    fn index(self) -> usize {
+        match self {
+            Direction::ToOutstation => 0,
+            Direction::ToMaster => 1,
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Direction::ToOutstation => write!(f, "master -> outstation"),
+            Direction::ToMaster => write!(f, "outstation -> master"),
+        }
+    }
+}
+
+/// Rewrite rule action
+#[derive(Debug, Clone)]
+pub enum RewriteAction {
+    /// Change the cause of transmission
+    SetCot(COT),
+    /// Drop the frame
+    Drop,
+    /// Change the information object address
+    RemapIoa {
+        /// Original address
+        from: u32,
+        /// New address
+        to: u32,
+    },
+    /// Delay the frame (the following frames in the same direction are delayed as well to
+    /// keep the order)
+    Delay(Duration),
+}
+
+/// Rewrite rule, applied to I-frames
+///
+/// A rule without filters matches all I-frames. Rules are applied in the order they are added.
+#[derive(Debug, Clone)]
+pub struct RewriteRule {
+    action: RewriteAction,
+    direction: Option<Direction>,
+    data_type: Option<DataType>,
+    ca: Option<u16>,
+}
+
+impl RewriteRule {
+    /// Create a new rule
+    pub fn new(action: RewriteAction) -> Self {
+        Self {
+            action,
+            direction: None,
+            data_type: None,
+            ca: None,
+        }
+    }
+    /// Apply the rule to frames in the given direction only
+    pub fn with_direction(mut self, direction: Direction) -> Self {
+        self.direction = Some(direction);
+        self
+    }
+    /// Apply the rule to frames of the given type only
+    pub fn with_data_type(mut self, data_type: DataType) -> Self {
+        self.data_type = Some(data_type);
+        self
+    }
+    /// Apply the rule to frames with the given common address only
+    pub fn with_ca(mut self, ca: u16) -> Self {
+        self.ca = Some(ca);
+        self
+    }
+    /// Rule action
+    pub fn action(&self) -> &RewriteAction {
+        &self.action
+    }
+    fn matches(&self, direction: Direction, telegram: &Telegram104_I) -> bool {
+        self.direction.is_none_or(|d| d == direction)
+            && self.data_type.is_none_or(|t| t == telegram.data_type())
+            && self.ca.is_none_or(|ca| ca == telegram.adsu())
+    }
+}
+
+/// What the proxy has done with a frame
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum FrameAction {
+    /// Forwarded unchanged (except sequence numbers)
+    Forwarded,
+    /// Forwarded with rewritten contents
+    Rewritten,
+    /// Dropped by a rule
+    Dropped,
+    /// Injected by the proxy
+    Injected,
+}
+
+/// Frame log record
+#[derive(Debug, Clone)]
+pub struct FrameRecord {
+    direction: Direction,
+    telegram: Telegram104,
+    action: FrameAction,
+    delay: Duration,
+}
+
+impl FrameRecord {
+    /// Frame direction
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+    /// Frame as received (as injected for injected frames)
+    pub fn telegram(&self) -> &Telegram104 {
+        &self.telegram
+    }
+    /// Action
+    pub fn action(&self) -> FrameAction {
+        self.action
+    }
+    /// Delay applied by the rules
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+}
+
+impl fmt::Display for FrameRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {:?}", self.direction, self.action)?;
+        if !self.delay.is_zero() {
+            write!(f, " (delay {:?})", self.delay)?;
+        }
+        match &self.telegram {
+            Telegram104::I(i) => {
+                write!(
+                    f,
+                    ": I({}/{}) {:?} {:?}{}{} CA={} OA={}",
+                    i.send_sn(),
+                    i.recv_sn(),
+                    i.data_type(),
+                    i.cot(),
+                    if i.is_negative() { " negative" } else { "" },
+                    if i.is_test() { " test" } else { "" },
+                    i.adsu(),
+                    i.originator()
+                )?;
+                for iou in i.iou() {
+                    write!(
+                        f,
+                        " [{}: {:02x?}]",
+                        iou.address(),
+                        &iou.value()[..i.data_type().size()]
+                    )?;
+                }
+                Ok(())
+            }
+            Telegram104::S(s) => write!(f, ": S({})", s.recv_sn()),
+            Telegram104::U(u) => write!(f, ": {:?}", u),
+        }
+    }
+}
+
+/// Proxy output
+#[derive(Debug, Clone)]
+pub enum ProxyOutput {
+    /// Send the frame
+    Send {
+        /// Direction (the peer to send the frame to)
+        direction: Direction,
+        /// Frame
+        telegram: Telegram104,
+    },
+    /// Log record
+    Log(FrameRecord),
+}
+
+/// A frame, waiting to be forwarded (`None` for dropped frames)
+#[derive(Debug)]
+struct Queued {
+    due: Instant,
+    rx: u16,
+    telegram: Option<Telegram104_I>,
+}
+
+/// Sequence number mapping of a single direction
+///
+/// The source and the destination peers number I-frames independently, as the proxy drops and
+/// injects frames. Each forwarded (or dropped) frame records the source and the destination
+/// counters, so the acknowledges of the destination can be translated to the source numbering.
+#[derive(Debug, Default)]
+struct SequenceMap {
+    // I-frames received from the source
+    rx: u16,
+    // I-frames sent to the destination
+    tx: u16,
+    // (source counter, destination counter) after each frame, not acknowledged yet
+    sent: VecDeque<(u16, u16)>,
+    acked_rx: u16,
+    acked_tx: u16,
+    queue: VecDeque<Queued>,
+}
+
+impl SequenceMap {
+    /// Translates the destination acknowledge to the source numbering
+    fn acknowledge(&mut self, recv_sn: u16) -> u16 {
+        let acked = seq_distance(self.acked_tx, recv_sn);
+        if acked <= seq_distance(self.acked_tx, self.tx) {
+            while let Some(&(rx, tx)) = self.sent.front() {
+                if seq_distance(self.acked_tx, tx) > acked {
+                    break;
+                }
+                self.acked_rx = rx;
+                self.sent.pop_front();
+            }
+            self.acked_tx = recv_sn;
+        }
+        self.acked_rx
+    }
+}
+
+/// IEC 60870-5-104 transparent proxy (sans-IO)
+///
+/// The proxy sits between a master and an outstation, decodes and logs frames in both
+/// directions and applies the rewrite rules to I-frames. Send and receive sequence numbers are
+/// fixed up, so both peers see consistent sequences when frames are dropped or injected.
+/// U-frames are forwarded unchanged. Delayed frames are released by [`Proxy::poll`].
+#[derive(Debug)]
+pub struct Proxy {
+    rules: Vec<RewriteRule>,
+    // indexed by Direction::index
+    maps: [SequenceMap; 2],
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for Proxy {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            maps: <_>::default(),
+            clock: Arc::new(SystemClock),
+        }
+    }
+}
+
+impl Proxy {
+    /// Create a new proxy
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Set the clock (frame delays, default: system clock)
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    /// Add a rewrite rule
+    pub fn with_rule(mut self, rule: RewriteRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+    /// Add a rewrite rule
+    pub fn add_rule(&mut self, rule: RewriteRule) {
+        self.rules.push(rule);
+    }
+    /// Remove all rewrite rules
+    pub fn clear_rules(&mut self) {
+        self.rules.clear();
+    }
+    /// Rewrite rules
+    pub fn rules(&self) -> &[RewriteRule] {
+        &self.rules
+    }
+    /// Reset the sequence numbers and discard delayed frames (call on new connections)
+    pub fn reset(&mut self) {
+        self.maps = <_>::default();
+    }
+    /// Next time a delayed frame is due
+    pub fn next_due(&self) -> Option<Instant> {
+        self.maps
+            .iter()
+            .filter_map(|map| map.queue.front().map(|q| q.due))
+            .min()
+    }
+    /// Handle a frame received from a peer, `direction` is the direction the frame travels
+    pub fn handle(&mut self, direction: Direction, telegram: Telegram104) -> Vec<ProxyOutput> {
+        let now = self.clock.now();
+        let mut output = Vec::new();
+        match telegram {
+            Telegram104::U(_) => {
+                output.push(ProxyOutput::Log(FrameRecord {
+                    direction,
+                    telegram: telegram.clone(),
+                    action: FrameAction::Forwarded,
+                    delay: Duration::ZERO,
+                }));
+                output.push(ProxyOutput::Send {
+                    direction,
+                    telegram,
+                });
+            }
+            Telegram104::S(ref s) => {
+                // acknowledges frames sent in the reverse direction
+                let recv_sn = self.maps[direction.reverse().index()].acknowledge(s.recv_sn());
+                output.push(ProxyOutput::Log(FrameRecord {
+                    direction,
+                    telegram: telegram.clone(),
+                    action: FrameAction::Forwarded,
+                    delay: Duration::ZERO,
+                }));
+                output.push(ProxyOutput::Send {
+                    direction,
+                    telegram: Telegram104::S(Telegram104_S::new().with_recv_sn(recv_sn)),
+                });
+            }
+            Telegram104::I(ref i) => {
+                self.maps[direction.reverse().index()].acknowledge(i.recv_sn());
+                let (rewritten, action, delay) = self.rewrite(direction, i.clone());
+                output.push(ProxyOutput::Log(FrameRecord {
+                    direction,
+                    telegram,
+                    action,
+                    delay,
+                }));
+                let map = &mut self.maps[direction.index()];
+                map.rx = seq_next(map.rx);
+                map.queue.push_back(Queued {
+                    due: now + delay,
+                    rx: map.rx,
+                    telegram: rewritten,
+                });
+            }
+        }
+        self.flush(now, &mut output);
+        output
+    }
+    /// Inject an I-frame, the sequence numbers are set by the proxy
+    pub fn inject(&mut self, direction: Direction, telegram: Telegram104_I) -> Vec<ProxyOutput> {
+        let now = self.clock.now();
+        let mut output = vec![ProxyOutput::Log(FrameRecord {
+            direction,
+            telegram: Telegram104::I(telegram.clone()),
+            action: FrameAction::Injected,
+            delay: Duration::ZERO,
+        })];
+        let map = &mut self.maps[direction.index()];
+        map.queue.push_back(Queued {
+            due: now,
+            rx: map.rx,
+            telegram: Some(telegram),
+        });
+        self.flush(now, &mut output);
+        output
+    }
+    /// Release delayed frames which are due
+    pub fn poll(&mut self) -> Vec<ProxyOutput> {
+        let mut output = Vec::new();
+        self.flush(self.clock.now(), &mut output);
+        output
+    }
+    fn rewrite(
+        &self,
+        direction: Direction,
+        mut telegram: Telegram104_I,
+    ) -> (Option<Telegram104_I>, FrameAction, Duration) {
+        let mut action = FrameAction::Forwarded;
+        let mut delay = Duration::ZERO;
+        for rule in &self.rules {
+            if !rule.matches(direction, &telegram) {
+                continue;
+            }
+            match rule.action {
+                RewriteAction::Drop => return (None, FrameAction::Dropped, delay),
+                RewriteAction::SetCot(cot) => {
+                    if telegram.cot() != cot {
+                        telegram = telegram.with_cot(cot);
+                        action = FrameAction::Rewritten;
+                    }
+                }
+                RewriteAction::RemapIoa { from, to } => {
+                    if let Some(remapped) = remap_ioa(&telegram, from, to) {
+                        telegram = remapped;
+                        action = FrameAction::Rewritten;
+                    }
+                }
+                RewriteAction::Delay(d) => delay += d,
+            }
+        }
+        (Some(telegram), action, delay)
+    }
+    fn flush(&mut self, now: Instant, output: &mut Vec<ProxyOutput>) {
+        for direction in [Direction::ToOutstation, Direction::ToMaster] {
+            let reverse = direction.reverse().index();
+            let recv_sn = self.maps[reverse].acked_rx;
+            let map = &mut self.maps[direction.index()];
+            let mut dropped = false;
+            while map.queue.front().is_some_and(|q| q.due <= now) {
+                let Some(queued) = map.queue.pop_front() else {
+                    break;
+                };
+                if let Some(telegram) = queued.telegram {
+                    let send_sn = map.tx;
+                    map.tx = seq_next(map.tx);
+                    output.push(ProxyOutput::Send {
+                        direction,
+                        telegram: Telegram104::I(
+                            telegram.with_send_sn(send_sn).with_recv_sn(recv_sn),
+                        ),
+                    });
+                } else {
+                    dropped = true;
+                }
+                map.sent.push_back((queued.rx, map.tx));
+            }
+            if dropped {
+                // the destination never acknowledges dropped frames, acknowledge them to the
+                // source if there is nothing else in flight
+                let acked_rx = map.acked_rx;
+                let recv_sn = map.acknowledge(map.acked_tx);
+                if recv_sn != acked_rx {
+                    output.push(ProxyOutput::Send {
+                        direction: direction.reverse(),
+                        telegram: Telegram104::S(Telegram104_S::new().with_recv_sn(recv_sn)),
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Returns `None` if there is no object with the address. Sequential telegrams are converted to
+/// non-sequential ones if required (unless the result does not fit into an APDU).
+fn remap_ioa(telegram: &Telegram104_I, from: u32, to: u32) -> Option<Telegram104_I> {
+    if !telegram.iou().iter().any(|iou| iou.address() == from) {
+        return None;
+    }
+    let iou: Vec<Iou> = telegram
+        .iou()
+        .iter()
+        .map(|iou| {
+            if iou.address() == from {
+                Iou::new(to, iou.value())
+            } else {
+                iou.clone()
+            }
+        })
+        .collect();
+    let consecutive = iou
+        .windows(2)
+        .all(|w| w[0].address().checked_add(1) == Some(w[1].address()));
+    if telegram.is_sequental() && !consecutive {
+        // each object gets its own address
+        if (3 + telegram.data_type().size()) * iou.len() > MAX_IOU_LEN {
+            return None;
+        }
+        let mut plain = Telegram104_I::new(telegram.data_type(), telegram.cot(), telegram.adsu())
+            .with_originator(telegram.originator())
+            .with_iou(iou);
+        if telegram.is_test() {
+            plain = plain.with_test();
+        }
+        if telegram.is_negative() {
+            plain = plain.with_negative();
+        }
+        return Some(plain);
+    }
+    Some(telegram.clone().with_iou(iou))
+}
+
+/// Frame logger
+pub type FrameLogger = Box<dyn FnMut(&FrameRecord) + Send>;
+
+enum Message {
+    Frame {
+        session: u64,
+        direction: Direction,
+        result: Result<Telegram104, Error>,
+    },
+    Inject(Direction, Telegram104_I),
+}
+
+/// Injects I-frames into the current [`TcpProxy104`] session
+#[derive(Clone)]
+pub struct Injector {
+    tx: mpsc::Sender<Message>,
+}
+
+impl Injector {
+    /// Inject a frame (ignored if there is no active session)
+    pub fn inject(&self, direction: Direction, telegram: Telegram104_I) -> Result<(), Error> {
+        self.tx
+            .send(Message::Inject(direction, telegram))
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe).into())
+    }
+}
+
+impl fmt::Debug for Injector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Injector").finish_non_exhaustive()
+    }
+}
+
+/// IEC 60870-5-104 transparent TCP proxy
+///
+/// Listens for a master and connects to the outstation for each accepted master connection. A
+/// single session is served at a time, the session ends when any of the peers disconnects.
+/// Causes of transmission are not validated, so telegrams of devices deviating from the
+/// standard are forwarded as they are.
+pub struct TcpProxy104 {
+    listener: TcpListener,
+    outstation: Vec<SocketAddr>,
+    connect_timeout: Duration,
+    proxy: Proxy,
+    logger: Option<FrameLogger>,
+    tx: mpsc::Sender<Message>,
+    rx: mpsc::Receiver<Message>,
+    session: u64,
+}
+
+impl TcpProxy104 {
+    /// Bind the listener, `outstation` is the address the proxy connects to
+    pub fn bind(listen: impl ToSocketAddrs, outstation: impl ToSocketAddrs) -> Result<Self, Error> {
+        let outstation: Vec<SocketAddr> = outstation.to_socket_addrs()?.collect();
+        if outstation.is_empty() {
+            return Err(Error::invalid_config("no outstation socket address"));
+        }
+        let (tx, rx) = mpsc::channel();
+        Ok(Self {
+            listener: TcpListener::bind(listen)?,
+            outstation,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            proxy: Proxy::new(),
+            logger: None,
+            tx,
+            rx,
+            session: 0,
+        })
+    }
+    /// Set the outstation connect timeout (default: 5 seconds)
+    pub fn with_connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+    /// Add a rewrite rule
+    pub fn with_rule(mut self, rule: RewriteRule) -> Self {
+        self.proxy.add_rule(rule);
+        self
+    }
+    /// Set the frame logger
+    pub fn with_logger<F>(mut self, logger: F) -> Self
+    where
+        F: FnMut(&FrameRecord) + Send + 'static,
+    {
+        self.logger = Some(Box::new(logger));
+        self
+    }
+    /// Local listener address
+    pub fn local_addr(&self) -> Result<SocketAddr, Error> {
+        self.listener.local_addr().map_err(Into::into)
+    }
+    /// Frame injector
+    pub fn injector(&self) -> Injector {
+        Injector {
+            tx: self.tx.clone(),
+        }
+    }
+    /// Serve sessions forever, returns on listener errors only
+    pub fn run(&mut self) -> Result<(), Error> {
+        loop {
+            let (master, _) = self.listener.accept()?;
+            // session errors are not fatal for the proxy
+            let _ = self.session(master);
+        }
+    }
+    /// Accept a master connection and serve a single session
+    pub fn run_once(&mut self) -> Result<(), Error> {
+        let (master, _) = self.listener.accept()?;
+        self.session(master)
+    }
+    fn connect_outstation(&self) -> Result<TcpStream, Error> {
+        let mut last_error = None;
+        for addr in &self.outstation {
+            match TcpStream::connect_timeout(addr, self.connect_timeout) {
+                Ok(stream) => return Ok(stream),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(last_error
+            .unwrap_or_else(|| io::ErrorKind::NotConnected.into())
+            .into())
+    }
+    fn spawn_reader(&self, stream: &TcpStream, direction: Direction) -> Result<(), Error> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let tx = self.tx.clone();
+        let session = self.session;
+        thread::spawn(move || {
+            loop {
+                let result = Telegram104::read_lenient(&mut reader);
+                let failed = result.is_err();
+                if tx
+                    .send(Message::Frame {
+                        session,
+                        direction,
+                        result,
+                    })
+                    .is_err()
+                    || failed
+                {
+                    break;
+                }
+            }
+        });
+        Ok(())
+    }
+    fn session(&mut self, master: TcpStream) -> Result<(), Error> {
+        let outstation = self.connect_outstation()?;
+        self.session += 1;
+        self.proxy.reset();
+        // frames injected between sessions are discarded
+        while self.rx.try_recv().is_ok() {}
+        let result = self.serve(&master, &outstation);
+        let _ = master.shutdown(Shutdown::Both);
+        let _ = outstation.shutdown(Shutdown::Both);
+        match result {
+            Err(Error::Io(e))
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::UnexpectedEof | io::ErrorKind::ConnectionReset
+                ) =>
+            {
+                Ok(())
+            }
+            v => v,
+        }
+    }
+    fn serve(&mut self, master: &TcpStream, outstation: &TcpStream) -> Result<(), Error> {
+        master.set_nodelay(true)?;
+        outstation.set_nodelay(true)?;
+        self.spawn_reader(master, Direction::ToOutstation)?;
+        self.spawn_reader(outstation, Direction::ToMaster)?;
+        loop {
+            let now = self.proxy.clock.now();
+            let timeout = self
+                .proxy
+                .next_due()
+                .map_or(IDLE_POLL_INTERVAL, |due| due.saturating_duration_since(now));
+            let output = match self.rx.recv_timeout(timeout) {
+                Ok(Message::Frame {
+                    session,
+                    direction,
+                    result,
+                }) => {
+                    if session != self.session {
+                        continue;
+                    }
+                    self.proxy.handle(direction, result?)
+                }
+                Ok(Message::Inject(direction, telegram)) => self.proxy.inject(direction, telegram),
+                Err(mpsc::RecvTimeoutError::Timeout) => self.proxy.poll(),
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    return Err(io::Error::from(io::ErrorKind::BrokenPipe).into());
+                }
+            };
+            for o in output {
+                match o {
+                    ProxyOutput::Send {
+                        direction,
+                        telegram,
+                    } => {
+                        let stream = match direction {
+                            Direction::ToOutstation => outstation,
+                            Direction::ToMaster => master,
+                        };
+                        telegram.write_lenient(stream)?;
+                    }
+                    ProxyOutput::Log(record) => {
+                        if let Some(logger) = self.logger.as_mut() {
+                            logger(&record);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Debug for TcpProxy104 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TcpProxy104")
+            .field("listener", &self.listener)
+            .field("outstation", &self.outstation)
+            .field("connect_timeout", &self.connect_timeout)
+            .field("proxy", &self.proxy)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::{TcpListener, TcpStream},
+        sync::{Arc, Mutex},
+        thread,
+        time::Duration,
+    };
+
+    use super::{
+        Direction, FrameAction, Proxy, ProxyOutput, RewriteAction, RewriteRule, TcpProxy104,
+    };
+    use crate::{
+        clock::{Clock, ManualClock},
+        telegram104::{Telegram104, Telegram104_I, Telegram104_S},
+        types::{
+            COT, Iou,
+            datatype::{DataType, M_ME_NB_1, M_SP_NA_1},
+        },
+    };
+
+    fn i_frame(data_type: DataType, send_sn: u16, recv_sn: u16) -> Telegram104 {
+        let mut telegram = Telegram104_I::new(data_type, COT::Spontan, 1);
+        telegram.append_iou(10, [0u8; 12]);
+        Telegram104::I(telegram.with_send_sn(send_sn).with_recv_sn(recv_sn))
+    }
+
+    fn sent(output: Vec<ProxyOutput>) -> Vec<(Direction, Telegram104)> {
+        output
+            .into_iter()
+            .filter_map(|o| match o {
+                ProxyOutput::Send {
+                    direction,
+                    telegram,
+                } => Some((direction, telegram)),
+                ProxyOutput::Log(_) => None,
+            })
+            .collect()
+    }
+
+    fn sequence(telegram: &Telegram104) -> (u16, u16) {
+        match telegram {
+            Telegram104::I(i) => (i.send_sn(), i.recv_sn()),
+            Telegram104::S(s) => (u16::MAX, s.recv_sn()),
+            Telegram104::U(_) => panic!("unexpected U-frame"),
+        }
+    }
+
+    #[test]
+    fn proxy_drop() {
+        let mut proxy = Proxy::new().with_rule(
+            RewriteRule::new(RewriteAction::Drop)
+                .with_direction(Direction::ToMaster)
+                .with_data_type(DataType::M_ME_NB_1),
+        );
+        let mut forwarded = Vec::new();
+        for (n, data_type) in [
+            DataType::M_SP_NA_1,
+            DataType::M_ME_NB_1,
+            DataType::M_SP_NA_1,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let n = u16::try_from(n).unwrap();
+            let output = proxy.handle(Direction::ToMaster, i_frame(data_type, n, 0));
+            assert!(matches!(
+                output[0],
+                ProxyOutput::Log(ref r) if r.action() == if n == 1 {
+                    FrameAction::Dropped
+                } else {
+                    FrameAction::Forwarded
+                }
+            ));
+            forwarded.extend(sent(output));
+        }
+        let sequences: Vec<(Direction, (u16, u16))> =
+            forwarded.iter().map(|(d, t)| (*d, sequence(t))).collect();
+        assert_eq!(
+            sequences,
+            [(Direction::ToMaster, (0, 0)), (Direction::ToMaster, (1, 0))]
+        );
+        // the master acknowledges 2 frames, the outstation has sent 3
+        let output = sent(proxy.handle(
+            Direction::ToOutstation,
+            Telegram104::S(Telegram104_S::new().with_recv_sn(2)),
+        ));
+        assert_eq!(output.len(), 1);
+        assert_eq!(output[0].0, Direction::ToOutstation);
+        assert_eq!(sequence(&output[0].1), (u16::MAX, 3));
+        // a dropped frame with nothing in flight is acknowledged by the proxy
+        let output = sent(proxy.handle(Direction::ToMaster, i_frame(DataType::M_ME_NB_1, 3, 0)));
+        assert_eq!(output.len(), 1);
+        assert_eq!(output[0].0, Direction::ToOutstation);
+        assert_eq!(sequence(&output[0].1), (u16::MAX, 4));
+    }
+
+    #[test]
+    fn proxy_inject() {
+        let mut proxy = Proxy::new();
+        let output = sent(proxy.handle(Direction::ToMaster, i_frame(DataType::M_SP_NA_1, 0, 0)));
+        assert_eq!(sequence(&output[0].1), (0, 0));
+        let output = sent(proxy.inject(
+            Direction::ToMaster,
+            Telegram104_I::new(DataType::M_SP_NA_1, COT::Spontan, 1),
+        ));
+        assert_eq!(sequence(&output[0].1), (1, 0));
+        let output = sent(proxy.handle(Direction::ToMaster, i_frame(DataType::M_SP_NA_1, 1, 0)));
+        assert_eq!(sequence(&output[0].1), (2, 0));
+        // the master acknowledges the injected frame only: 1 outstation frame
+        let output =
+            sent(proxy.handle(Direction::ToOutstation, i_frame(DataType::C_SC_NA_1, 0, 2)));
+        assert_eq!(output[0].0, Direction::ToOutstation);
+        assert_eq!(sequence(&output[0].1), (0, 1));
+        let output = sent(proxy.handle(
+            Direction::ToOutstation,
+            Telegram104::S(Telegram104_S::new().with_recv_sn(3)),
+        ));
+        assert_eq!(sequence(&output[0].1), (u16::MAX, 2));
+        // acknowledges of the outstation are passed through unchanged
+        let output = sent(proxy.handle(
+            Direction::ToMaster,
+            Telegram104::S(Telegram104_S::new().with_recv_sn(1)),
+        ));
+        assert_eq!(sequence(&output[0].1), (u16::MAX, 1));
+    }
+
+    #[test]
+    fn proxy_rewrite_and_delay() {
+        let delay = Duration::from_millis(100);
+        let clock = Arc::new(ManualClock::default());
+        let mut proxy = Proxy::new()
+            .with_clock(clock.clone())
+            .with_rule(RewriteRule::new(RewriteAction::SetCot(COT::Cyclic)).with_ca(1))
+            .with_rule(RewriteRule::new(RewriteAction::RemapIoa {
+                from: 11,
+                to: 20,
+            }))
+            .with_rule(
+                RewriteRule::new(RewriteAction::Delay(delay)).with_data_type(DataType::M_ME_NB_1),
+            );
+        let telegram = Telegram104_I::new(DataType::M_SP_NA_1, COT::Spontan, 1)
+            .with_seq()
+            .with_iou(
+                (10..13)
+                    .map(|a| Iou::new(a, M_SP_NA_1::default()))
+                    .collect(),
+            );
+        let output = proxy.handle(Direction::ToMaster, Telegram104::I(telegram));
+        let ProxyOutput::Log(ref record) = output[0] else {
+            panic!("log record expected");
+        };
+        assert_eq!(record.action(), FrameAction::Rewritten);
+        let output = sent(output);
+        let Telegram104::I(ref i) = output[0].1 else {
+            panic!("I-frame expected");
+        };
+        assert_eq!(i.cot(), COT::Cyclic);
+        assert!(!i.is_sequental());
+        let ioas: Vec<u32> = i.iou().iter().map(Iou::address).collect();
+        assert_eq!(ioas, [10, 20, 12]);
+        // the written frame is decoded back with the remapped addresses
+        let mut buf = Vec::new();
+        output[0].1.write_lenient(&mut buf).unwrap();
+        let Telegram104::I(i) = Telegram104::read_lenient(buf.as_slice()).unwrap() else {
+            panic!("I-frame expected");
+        };
+        let ioas: Vec<u32> = i.iou().iter().map(Iou::address).collect();
+        assert_eq!(ioas, [10, 20, 12]);
+        // delayed frame holds the following ones
+        let mut telegram = Telegram104_I::new(DataType::M_ME_NB_1, COT::Spontan, 2);
+        telegram.append_iou(30, M_ME_NB_1::default());
+        let output = proxy.handle(Direction::ToMaster, Telegram104::I(telegram));
+        let ProxyOutput::Log(ref record) = output[0] else {
+            panic!("log record expected");
+        };
+        assert_eq!(record.delay(), delay);
+        assert!(sent(output).is_empty());
+        assert!(
+            sent(proxy.handle(Direction::ToMaster, i_frame(DataType::M_SP_NA_1, 2, 0))).is_empty()
+        );
+        assert_eq!(proxy.next_due(), Some(clock.now() + delay));
+        clock.advance(delay / 2);
+        assert!(sent(proxy.poll()).is_empty());
+        clock.advance(delay / 2);
+        let output = sent(proxy.poll());
+        let sequences: Vec<(u16, u16)> = output.iter().map(|(_, t)| sequence(t)).collect();
+        assert_eq!(sequences, [(1, 0), (2, 0)]);
+        assert!(proxy.next_due().is_none());
+    }
+
+    #[test]
+    fn proxy_tcp() {
+        let outstation = TcpListener::bind("127.0.0.1:0").unwrap();
+        let records = Arc::new(Mutex::new(Vec::new()));
+        let log = records.clone();
+        let mut proxy = TcpProxy104::bind("127.0.0.1:0", outstation.local_addr().unwrap())
+            .unwrap()
+            .with_rule(
+                RewriteRule::new(RewriteAction::SetCot(COT::Background))
+                    .with_direction(Direction::ToMaster),
+            )
+            .with_logger(move |record| log.lock().unwrap().push(record.to_string()));
+        let proxy_addr = proxy.local_addr().unwrap();
+        let injector = proxy.injector();
+        let proxy_handle = thread::spawn(move || proxy.run_once());
+        let outstation_handle = thread::spawn(move || {
+            let (mut stream, _) = outstation.accept().unwrap();
+            let Telegram104::U(u) = Telegram104::read(&mut stream).unwrap() else {
+                panic!("U-frame expected");
+            };
+            assert!(u.is_start_dt());
+            Telegram104::U(u.with_con(true)).write(&stream).unwrap();
+            let Telegram104::I(i) = Telegram104::read(&mut stream).unwrap() else {
+                panic!("I-frame expected");
+            };
+            assert_eq!(i.data_type(), DataType::C_RD_NA_1);
+            let mut reply = Telegram104_I::new(DataType::M_SP_NA_1, COT::Spontan, 1)
+                .with_send_sn(0)
+                .with_recv_sn(1);
+            reply.append_iou(i.iou()[0].address(), M_SP_NA_1::default());
+            Telegram104::I(reply).write(&stream).unwrap();
+            let Telegram104::S(s) = Telegram104::read(&mut stream).unwrap() else {
+                panic!("S-frame expected");
+            };
+            // the injected frame is not seen by the outstation
+            assert_eq!(s.recv_sn(), 1);
+        });
+        let mut master = TcpStream::connect(proxy_addr).unwrap();
+        master
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        Telegram104::new_start_dt().write(&master).unwrap();
+        let Telegram104::U(u) = Telegram104::read(&mut master).unwrap() else {
+            panic!("U-frame expected");
+        };
+        assert!(u.is_start_dt() && u.is_con());
+        let mut telegram = Telegram104_I::new(DataType::C_RD_NA_1, COT::Req, 1);
+        telegram.append_iou(10, [0u8; 12]);
+        Telegram104::I(telegram).write(&master).unwrap();
+        let Telegram104::I(i) = Telegram104::read(&mut master).unwrap() else {
+            panic!("I-frame expected");
+        };
+        assert_eq!(i.cot(), COT::Background);
+        assert_eq!((i.send_sn(), i.recv_sn()), (0, 1));
+        let mut init = Telegram104_I::new(DataType::M_EI_NA_1, COT::Init, 1);
+        init.append_iou(0, [0u8; 12]);
+        injector.inject(Direction::ToMaster, init).unwrap();
+        let Telegram104::I(i) = Telegram104::read(&mut master).unwrap() else {
+            panic!("I-frame expected");
+        };
+        assert_eq!(i.data_type(), DataType::M_EI_NA_1);
+        assert_eq!(i.send_sn(), 1);
+        Telegram104::S(Telegram104_S::new().with_recv_sn(2))
+            .write(&master)
+            .unwrap();
+        outstation_handle.join().unwrap();
+        drop(master);
+        proxy_handle.join().unwrap().unwrap();
+        let records = records.lock().unwrap();
+        assert_eq!(records.len(), 6);
+        assert!(records[3].starts_with("outstation -> master Rewritten"));
+        assert!(records[4].starts_with("outstation -> master Injected"));
+    }
+
+    #[test]
+    fn proxy_tcp_non_conforming_cot() {
+        let outstation = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut proxy = TcpProxy104::bind("127.0.0.1:0", outstation.local_addr().unwrap()).unwrap();
+        let proxy_addr = proxy.local_addr().unwrap();
+        let proxy_handle = thread::spawn(move || proxy.run_once());
+        let outstation_handle = thread::spawn(move || {
+            let (mut stream, _) = outstation.accept().unwrap();
+            let Telegram104::U(u) = Telegram104::read(&mut stream).unwrap() else {
+                panic!("U-frame expected");
+            };
+            Telegram104::U(u.with_con(true)).write(&stream).unwrap();
+            // single-point information is never cyclic
+            for (send_sn, cot) in [(0, COT::Cyclic), (1, COT::Spontan)] {
+                let mut telegram =
+                    Telegram104_I::new(DataType::M_SP_NA_1, cot, 1).with_send_sn(send_sn);
+                telegram.append_iou(10, M_SP_NA_1::default());
+                Telegram104::I(telegram).write_lenient(&stream).unwrap();
+            }
+            let Telegram104::S(s) = Telegram104::read(&mut stream).unwrap() else {
+                panic!("S-frame expected");
+            };
+            assert_eq!(s.recv_sn(), 2);
+        });
+        let mut master = TcpStream::connect(proxy_addr).unwrap();
+        master
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        Telegram104::new_start_dt().write(&master).unwrap();
+        let Telegram104::U(_) = Telegram104::read(&mut master).unwrap() else {
+            panic!("U-frame expected");
+        };
+        // rejected by the strict decoder, forwarded by the proxy
+        let mut cots = Vec::new();
+        for _ in 0..2 {
+            let Telegram104::I(i) = Telegram104::read_lenient(&mut master).unwrap() else {
+                panic!("I-frame expected");
+            };
+            cots.push(i.cot());
+        }
+        assert_eq!(cots, [COT::Cyclic, COT::Spontan]);
+        Telegram104::S(Telegram104_S::new().with_recv_sn(2))
+            .write(&master)
+            .unwrap();
+        outstation_handle.join().unwrap();
+        drop(master);
+        proxy_handle.join().unwrap().unwrap();
+    }
+}
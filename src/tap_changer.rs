@@ -0,0 +1,196 @@
+use std::ops::RangeInclusive;
+
+use crate::{
+    Error,
+    telegram104::Telegram104_I,
+    types::{
+        COT,
+        datatype::{C_RC_NA_1, DataType, QDS, QU, RCO, RCS, SelectExecute, VTI},
+    },
+};
+
+/// Transformer tap changer (controlling station)
+///
+/// Maps step position information (VTI) to tap positions: the tap is the VTI value plus the
+/// offset (e.g. taps 1..=33 reported as -16..=16 with the offset 17). Raise and lower commands
+/// are regulating step commands (C_RC_NA_1), refused if the tap changer is moving (the
+/// transient flag is set) or the tap limit is reached.
+#[derive(Debug, Clone)]
+pub struct TapChanger {
+    ca: u16,
+    ioa: u32,
+    offset: i16,
+    min: i16,
+    max: i16,
+    qu: QU,
+}
+
+impl TapChanger {
+    /// Create a new tap changer, commanded at the common address and information object
+    /// address (default tap range: -64..=63, without offset)
+    pub fn new(ca: u16, ioa: u32) -> Self {
+        Self {
+            ca,
+            ioa,
+            offset: 0,
+            min: -64,
+            max: 63,
+            qu: QU::Unspecified,
+        }
+    }
+    /// Set the offset of tap positions to VTI values
+    pub fn with_offset(mut self, offset: i16) -> Self {
+        self.offset = offset;
+        self
+    }
+    /// Set the tap range (limits)
+    pub fn with_range(mut self, range: RangeInclusive<i16>) -> Self {
+        (self.min, self.max) = range.into_inner();
+        self
+    }
+    /// Set the qualifier of commands (default: unspecified)
+    pub fn with_qualifier(mut self, qu: QU) -> Self {
+        self.qu = qu;
+        self
+    }
+    /// Tap position of the step position information
+    pub fn position(&self, vti: &VTI) -> i16 {
+        i16::from(vti.value).saturating_add(self.offset)
+    }
+    /// Step position information of the tap position (controlled station)
+    pub fn vti(&self, tap: i16, transient: bool, qds: QDS) -> Result<VTI, Error> {
+        let value = tap
+            .checked_sub(self.offset)
+            .and_then(|v| i8::try_from(v).ok())
+            .filter(|v| (-64..=63).contains(v))
+            .ok_or_else(|| {
+                Error::conversion(format!("tap {} is out of the step position range", tap))
+            })?;
+        Ok(VTI {
+            value,
+            transient,
+            qds,
+        })
+    }
+    /// Regulating step command telegram (activation)
+    pub fn command(&self, rcs: RCS, se: SelectExecute) -> Telegram104_I {
+        let mut telegram = Telegram104_I::new(DataType::C_RC_NA_1, COT::Act, self.ca);
+        telegram.append_iou(
+            self.ioa,
+            C_RC_NA_1 {
+                rco: RCO {
+                    se,
+                    qu: self.qu.clone(),
+                    rcs,
+                },
+            },
+        );
+        telegram
+    }
+    /// Raise the tap by one step
+    pub fn raise(&self, current: &VTI) -> Result<Telegram104_I, Error> {
+        self.step(current, RCS::Increment)
+    }
+    /// Lower the tap by one step
+    pub fn lower(&self, current: &VTI) -> Result<Telegram104_I, Error> {
+        self.step(current, RCS::Decrement)
+    }
+    /// A step towards the target tap, `None` if the target is reached
+    pub fn step_towards(&self, current: &VTI, target: i16) -> Result<Option<Telegram104_I>, Error> {
+        if !(self.min..=self.max).contains(&target) {
+            return Err(Error::invalid_data(format!(
+                "target tap {} is out of range {}..={}",
+                target, self.min, self.max
+            )));
+        }
+        let position = self.position(current);
+        if position == target {
+            return Ok(None);
+        }
+        let rcs = if position < target {
+            RCS::Increment
+        } else {
+            RCS::Decrement
+        };
+        self.step(current, rcs).map(Some)
+    }
+    fn step(&self, current: &VTI, rcs: RCS) -> Result<Telegram104_I, Error> {
+        if current.transient {
+            return Err(Error::invalid_data("the tap changer is moving"));
+        }
+        let position = self.position(current);
+        let limit = match rcs {
+            RCS::Increment => position >= self.max,
+            RCS::Decrement => position <= self.min,
+            RCS::NotAllowed0 | RCS::NotAllowed3 => true,
+        };
+        if limit {
+            return Err(Error::invalid_data(format!(
+                "tap {} is at the limit",
+                position
+            )));
+        }
+        Ok(self.command(rcs, SelectExecute::Execute))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TapChanger;
+    use crate::{
+        telegram104::Telegram104_I,
+        types::{
+            COT,
+            datatype::{C_RC_NA_1, DataType, QDS, QU, RCS, SelectExecute, VTI},
+        },
+    };
+
+    fn rcs(telegram: &Telegram104_I) -> RCS {
+        C_RC_NA_1::from(telegram.iou()[0].value()).rco.rcs
+    }
+
+    #[test]
+    fn tap_changer() {
+        let tap_changer = TapChanger::new(1, 5000)
+            .with_offset(17)
+            .with_range(1..=33)
+            .with_qualifier(QU::ShortPulse);
+        let vti = tap_changer.vti(1, false, QDS::default()).unwrap();
+        assert_eq!(vti.value, -16);
+        assert_eq!(tap_changer.position(&vti), 1);
+        assert!(tap_changer.vti(100, false, QDS::default()).is_err());
+        // at the lower limit
+        assert!(tap_changer.lower(&vti).is_err());
+        let telegram = tap_changer.raise(&vti).unwrap();
+        assert_eq!(
+            (telegram.data_type(), telegram.cot(), telegram.adsu()),
+            (DataType::C_RC_NA_1, COT::Act, 1)
+        );
+        assert_eq!(telegram.iou()[0].address(), 5000);
+        let rco = C_RC_NA_1::from(telegram.iou()[0].value()).rco;
+        assert_eq!(
+            (rco.se, rco.qu, rco.rcs),
+            (SelectExecute::Execute, QU::ShortPulse, RCS::Increment)
+        );
+        let vti = VTI {
+            value: 0,
+            ..VTI::default()
+        };
+        assert_eq!(
+            rcs(&tap_changer.step_towards(&vti, 10).unwrap().unwrap()),
+            RCS::Decrement
+        );
+        assert_eq!(
+            rcs(&tap_changer.step_towards(&vti, 20).unwrap().unwrap()),
+            RCS::Increment
+        );
+        assert!(tap_changer.step_towards(&vti, 17).unwrap().is_none());
+        assert!(tap_changer.step_towards(&vti, 34).is_err());
+        // moving
+        let moving = VTI {
+            transient: true,
+            ..vti
+        };
+        assert!(tap_changer.raise(&moving).is_err());
+    }
+}
@@ -2,35 +2,64 @@ use std::io::{Cursor, Read, Write};
 
 use crate::{
     Error,
-    types::{COT, DataBuffer, Iou, MAX_IEC_DATA_LEN, datatype::DataType},
+    types::{
+        COT, DataBuffer, Iou, MAX_IEC_DATA_LEN,
+        datatype::{DataType, add_time_tags, convert_cp24_time_tags, strip_time_tags},
+        time::CP56Time2a,
+    },
 };
 
-const IEC_HEADER: u8 = 0x68;
-const IEC_HEADER_FIXED: u8 = 0x10;
+pub(crate) const IEC_HEADER: u8 = 0x68;
+pub(crate) const IEC_HEADER_FIXED: u8 = 0x10;
 const IEC_STOP: u8 = 0x16;
-const IEC_ACK_POSITIVE: u8 = 0xE5;
-const IEC_ACK_NEGATIVE: u8 = 0xA2;
+pub(crate) const IEC_ACK_POSITIVE: u8 = 0xE5;
+pub(crate) const IEC_ACK_NEGATIVE: u8 = 0xA2;
+
+/// Maximum length of the variable length frame user data (L field)
+pub const MAX_FRAME_LEN: u8 = 253;
+
+/// Link transmission procedure
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LinkMode {
+    /// Unbalanced transmission (a primary station polls secondary stations), the link address
+    /// field is mandatory
+    #[default]
+    Unbalanced,
+    /// Balanced transmission (both stations may initiate), the link address field is optional
+    Balanced,
+}
 
 /// IEC 60870-5-101 telegram configuration (used with each telegram)
-/// Defaults: link_address_len = 1, originator_address_len = 1, adsu_address_len = 2,
-/// iou_address_len = 3
+/// Defaults: link_mode = unbalanced, link_address_len = 1, originator_address_len = 1,
+/// adsu_address_len = 2, iou_address_len = 3, max_frame_len = 253, single_char_ack = false,
+/// broadcast link address = all ones, strict_cot = true
 #[allow(clippy::module_name_repetitions)]
 #[allow(clippy::struct_field_names)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Config {
+    link_mode: LinkMode,
     link_address_len: u8,
     originator_address_len: u8,
     adsu_address_len: u8,
     iou_address_len: u8,
+    max_frame_len: u8,
+    single_char_ack: bool,
+    broadcast_link_address: Option<u32>,
+    strict_cot: bool,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            link_mode: LinkMode::Unbalanced,
             link_address_len: 1,
             originator_address_len: 1,
             adsu_address_len: 2,
             iou_address_len: 3,
+            max_frame_len: MAX_FRAME_LEN,
+            single_char_ack: false,
+            broadcast_link_address: None,
+            strict_cot: true,
         }
     }
 }
@@ -40,6 +69,38 @@ impl Config {
     pub fn new() -> Self {
         Self::default()
     }
+    /// Create a new validated configuration with the given link mode and field lengths
+    pub fn try_new(
+        link_mode: LinkMode,
+        link_address_len: u8,
+        originator_address_len: u8,
+        adsu_address_len: u8,
+        iou_address_len: u8,
+    ) -> Result<Self, Error> {
+        Self::builder()
+            .link_mode(link_mode)
+            .link_address_len(link_address_len)
+            .originator_address_len(originator_address_len)
+            .adsu_address_len(adsu_address_len)
+            .iou_address_len(iou_address_len)
+            .build()
+    }
+    /// Create a new configuration builder
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::default()
+    }
+    /// Set the link transmission procedure, the field lengths are checked against it by
+    /// [`Config::validate`]
+    pub fn with_link_mode(mut self, link_mode: LinkMode) -> Self {
+        self.link_mode = link_mode;
+        self
+    }
+    /// Validate the cause of transmission of the telegrams read and written with this
+    /// configuration (see [`Config::strict_cot`])
+    pub fn with_strict_cot(mut self, strict_cot: bool) -> Self {
+        self.strict_cot = strict_cot;
+        self
+    }
     /// # Panics
     ///
     /// Panics if `link_address_len` is greater than 2.
@@ -72,6 +133,206 @@ impl Config {
         self.iou_address_len = iou_address_len;
         self
     }
+    /// Link transmission procedure
+    pub fn link_mode(&self) -> LinkMode {
+        self.link_mode
+    }
+    /// Link address length
+    pub fn link_address_len(&self) -> u8 {
+        self.link_address_len
+    }
+    /// Originator address length
+    pub fn originator_address_len(&self) -> u8 {
+        self.originator_address_len
+    }
+    /// ADSU (common address) length
+    pub fn adsu_address_len(&self) -> u8 {
+        self.adsu_address_len
+    }
+    /// Information object address length
+    pub fn iou_address_len(&self) -> u8 {
+        self.iou_address_len
+    }
+    /// Maximum length of the variable length frame user data (L field)
+    pub fn max_frame_len(&self) -> u8 {
+        self.max_frame_len
+    }
+    /// Is the single-character ACK (0xE5) preferred over the fixed length ACK frame
+    pub fn single_char_ack(&self) -> bool {
+        self.single_char_ack
+    }
+    /// Is the cause of transmission validated by [`Telegram101::read`] and
+    /// [`Telegram101::write`] (disable for devices deviating from the standard)
+    pub fn strict_cot(&self) -> bool {
+        self.strict_cot
+    }
+    /// Broadcast link address (None if the link address field is omitted)
+    ///
+    /// If not set explicitly, the address with all bits set is used (255 or 65535)
+    pub fn broadcast_link_address(&self) -> Option<u32> {
+        if self.link_address_len == 0 {
+            return None;
+        }
+        Some(
+            self.broadcast_link_address
+                .unwrap_or_else(|| max_address(self.link_address_len)),
+        )
+    }
+    /// Is the link address a broadcast one
+    pub fn is_broadcast(&self, link_address: u32) -> bool {
+        self.broadcast_link_address() == Some(link_address)
+    }
+    /// Broadcast common address (all bits set: 255 or 65535)
+    pub fn broadcast_ca(&self) -> u16 {
+        u16::try_from(max_address(self.adsu_address_len)).unwrap_or(u16::MAX)
+    }
+    /// Length of a variable length frame user data with no information objects
+    fn header_len(&self) -> usize {
+        1 // control field
+        + usize::from(self.link_address_len)
+        + 1 // data_type
+        + 1 // iou length
+        + 1 // cot
+        + usize::from(self.originator_address_len)
+        + usize::from(self.adsu_address_len)
+    }
+    /// Validate the configuration against the standard and the selected link mode
+    pub fn validate(&self) -> Result<(), Error> {
+        match self.link_mode {
+            LinkMode::Unbalanced => {
+                if !(1..=2).contains(&self.link_address_len) {
+                    return Err(Error::invalid_config(
+                        "link address length must be 1 or 2 in unbalanced mode",
+                    ));
+                }
+            }
+            LinkMode::Balanced => {
+                if self.link_address_len > 2 {
+                    return Err(Error::invalid_config(
+                        "link address length must be 0, 1 or 2 in balanced mode",
+                    ));
+                }
+            }
+        }
+        if self.originator_address_len > 1 {
+            return Err(Error::invalid_config(
+                "originator address length must be 0 or 1",
+            ));
+        }
+        if !(1..=2).contains(&self.adsu_address_len) {
+            return Err(Error::invalid_config("ADSU address length must be 1 or 2"));
+        }
+        if !(1..=3).contains(&self.iou_address_len) {
+            return Err(Error::invalid_config(
+                "information object address length must be 1, 2 or 3",
+            ));
+        }
+        if self.max_frame_len > MAX_FRAME_LEN {
+            return Err(Error::invalid_config(format!(
+                "max frame length must not exceed {}",
+                MAX_FRAME_LEN
+            )));
+        }
+        if usize::from(self.max_frame_len) <= self.header_len() + usize::from(self.iou_address_len)
+        {
+            return Err(Error::invalid_config(
+                "max frame length is too small to carry an information object",
+            ));
+        }
+        if let Some(broadcast) = self.broadcast_link_address {
+            if self.link_address_len == 0 {
+                return Err(Error::invalid_config(
+                    "broadcast link address requires the link address field",
+                ));
+            }
+            if broadcast > max_address(self.link_address_len) {
+                return Err(Error::invalid_config(
+                    "broadcast link address does not fit the link address field",
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Max address of a field of the given length
+pub(crate) fn max_address(len: u8) -> u32 {
+    if len >= 4 {
+        u32::MAX
+    } else {
+        (1 << (u32::from(len) * 8)) - 1
+    }
+}
+
+/// Fallible [`Config`] builder
+#[derive(Debug, Clone, Default)]
+#[allow(clippy::module_name_repetitions)]
+pub struct ConfigBuilder {
+    config: Config,
+}
+
+impl ConfigBuilder {
+    /// Create a new builder with the default values
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Link transmission procedure
+    pub fn link_mode(mut self, link_mode: LinkMode) -> Self {
+        self.config.link_mode = link_mode;
+        self
+    }
+    /// Link address length (0-2, 0 is allowed in balanced mode only)
+    pub fn link_address_len(mut self, link_address_len: u8) -> Self {
+        self.config.link_address_len = link_address_len;
+        self
+    }
+    /// Originator address length (0-1)
+    pub fn originator_address_len(mut self, originator_address_len: u8) -> Self {
+        self.config.originator_address_len = originator_address_len;
+        self
+    }
+    /// ADSU (common address) length (1-2)
+    pub fn adsu_address_len(mut self, adsu_address_len: u8) -> Self {
+        self.config.adsu_address_len = adsu_address_len;
+        self
+    }
+    /// Information object address length (1-3)
+    pub fn iou_address_len(mut self, iou_address_len: u8) -> Self {
+        self.config.iou_address_len = iou_address_len;
+        self
+    }
+    /// Maximum length of the variable length frame user data (L field, up to 253)
+    pub fn max_frame_len(mut self, max_frame_len: u8) -> Self {
+        self.config.max_frame_len = max_frame_len;
+        self
+    }
+    /// Prefer the single-character ACK (0xE5) over the fixed length ACK frame
+    pub fn single_char_ack(mut self, single_char_ack: bool) -> Self {
+        self.config.single_char_ack = single_char_ack;
+        self
+    }
+    /// Broadcast link address (all bits set by default)
+    pub fn broadcast_link_address(mut self, broadcast_link_address: u32) -> Self {
+        self.config.broadcast_link_address = Some(broadcast_link_address);
+        self
+    }
+    /// Validate the cause of transmission of the telegrams (default: true)
+    pub fn strict_cot(mut self, strict_cot: bool) -> Self {
+        self.config.strict_cot = strict_cot;
+        self
+    }
+    /// Validate and build the configuration
+    pub fn build(self) -> Result<Config, Error> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
+
+/// Reads from the user data of a complete frame, running out of data is a frame error
+fn read_asdu(frame: &mut Cursor<Vec<u8>>, buf: &mut [u8]) -> Result<(), Error> {
+    frame
+        .read_exact(buf)
+        .map_err(|_| Error::invalid_data("ASDU shorter than the frame length"))
 }
 
 fn buf_checksum(buf: &[u8]) -> u8 {
@@ -160,6 +421,15 @@ impl Telegram101 {
             ack_only: true,
         }
     }
+    /// Create a new positive link layer ACK reply for the given link address, according to the
+    /// configuration: either single-character data or a fixed length frame
+    pub fn new_link_ack(config: Config, link_address: u32) -> Self {
+        if config.single_char_ack {
+            Self::new_ack(true)
+        } else {
+            Self::new_fixed(config).with_link_address(link_address)
+        }
+    }
     /// Is this a fixed length telegram
     pub fn is_fixed(&self) -> bool {
         self.cot.is_none()
@@ -194,6 +464,22 @@ impl Telegram101 {
         self.sequental = true;
         self
     }
+    /// PRM bit (true if sent by the primary station)
+    pub fn is_prm(&self) -> bool {
+        self.prm
+    }
+    /// FCB (primary) / ACD (secondary) bit
+    pub fn fcb_acd(&self) -> bool {
+        self.fcb_acd
+    }
+    /// FCV (primary) / DFC (secondary) bit
+    pub fn fcv_dfc(&self) -> bool {
+        self.fcv_dfc
+    }
+    /// Function code
+    pub fn function_code(&self) -> u8 {
+        self.function_code
+    }
     /// Type identifier
     pub fn data_type(&self) -> DataType {
         self.data_type
@@ -226,6 +512,11 @@ impl Telegram101 {
     pub fn link_address(&self) -> u32 {
         self.link_address
     }
+    /// Is the telegram sent to the broadcast link address
+    pub fn is_broadcast(&self) -> bool {
+        self.config
+            .is_some_and(|config| config.is_broadcast(self.link_address))
+    }
     /// Originator address
     pub fn originator(&self) -> u16 {
         self.originator
@@ -252,6 +543,20 @@ impl Telegram101 {
         self.negative = true;
         self
     }
+    /// Set the COT
+    pub fn with_cot(mut self, cot: COT) -> Self {
+        self.cot = Some(cot);
+        self
+    }
+    /// Set the ADSU
+    pub fn with_adsu(mut self, adsu: u16) -> Self {
+        self.adsu = adsu;
+        self
+    }
+    /// Telegram configuration (`None` for single-character acks)
+    pub fn config(&self) -> Option<Config> {
+        self.config
+    }
     /// Set originator address
     pub fn with_originator(mut self, originator: u16) -> Self {
         self.originator = originator;
@@ -271,8 +576,31 @@ impl Telegram101 {
         self.sequental = true;
         self.iou.push(Iou::new(0, value));
     }
+    /// Converts the information objects with CP24Time2a time tags to the equivalent type with
+    /// CP56Time2a time tags, rewriting the type identifier (e.g. before forwarding the data to IEC 60870-5-104)
+    ///
+    /// The missing time tag fields are reconstructed from the reference time (see
+    /// [`CP56Time2a::from_cp24time2a`]). Telegrams of other types are returned unchanged.
+    pub fn into_cp56_time_tags(mut self, reference: &CP56Time2a) -> Result<Self, Error> {
+        self.data_type = convert_cp24_time_tags(self.data_type, &mut self.iou, reference)?;
+        Ok(self)
+    }
+    /// Adds the CP56Time2a time tag to the information objects, rewriting the type identifier
+    /// (e.g. spontaneous transmission of untimed process data). Telegrams of other types,
+    /// including CP24Time2a time tags (see [`Self::into_cp56_time_tags`]), are returned unchanged.
+    pub fn add_time_tag(mut self, time: &CP56Time2a) -> Self {
+        self.data_type = add_time_tags(self.data_type, &mut self.iou, time);
+        self
+    }
+    /// Removes the time tags of the information objects, rewriting the type identifier (e.g.
+    /// interrogation responses). Telegrams of types without untimed equivalent are returned
+    /// unchanged.
+    pub fn strip_time_tag(mut self) -> Self {
+        self.data_type = strip_time_tags(self.data_type, &mut self.iou);
+        self
+    }
     #[allow(clippy::too_many_lines)]
-    fn read_variable_length<R>(mut reader: R, config: Config) -> Result<Self, Error>
+    fn read_variable_length<R>(mut reader: R, config: Config, validate: bool) -> Result<Self, Error>
     where
         R: Read,
     {
@@ -282,7 +610,7 @@ impl Telegram101 {
         if length != buf[1] {
             return Err(Error::invalid_data("invalid length"));
         }
-        if length > 253 {
+        if length > config.max_frame_len {
             return Err(Error::invalid_data("telegram too long"));
         }
         if length < 1 {
@@ -303,31 +631,34 @@ impl Telegram101 {
         }
         let mut frame = Cursor::new(buf);
         let mut control_buf = [0; 1];
-        frame.read_exact(&mut control_buf)?;
+        read_asdu(&mut frame, &mut control_buf)?;
         let mut link_address_buf = vec![0; usize::from(config.link_address_len)];
         if link_address_buf.capacity() > 0 {
-            frame.read_exact(&mut link_address_buf)?;
+            read_asdu(&mut frame, &mut link_address_buf)?;
         }
         link_address_buf.resize(4, 0);
         let link_address = u32::from_le_bytes(link_address_buf.try_into().unwrap());
         let mut buf = [0u8; 3];
-        frame.read_exact(&mut buf)?;
+        read_asdu(&mut frame, &mut buf)?;
         let data_type = DataType::try_from(buf[0])
             .map_err(|_| Error::invalid_data(format!("invalid data_type {}", buf[0])))?;
         let iou_len = usize::from(buf[1] & 0b0111_1111);
         let sequental = buf[1] & 0b1000_0000 != 0;
         let cot = COT::try_from(buf[2] & 0b0011_1111)
             .map_err(|_| Error::invalid_data(format!("invalid cot {}", buf[2])))?;
+        if validate && iou_len > 0 {
+            data_type.validate_cot(cot)?;
+        }
         let test = buf[2] & 0b1000_0000 != 0;
         let negative = buf[2] & 0b0100_0000 != 0;
         let mut originator_buf = vec![0; usize::from(config.originator_address_len)];
         if originator_buf.capacity() > 0 {
-            frame.read_exact(&mut originator_buf)?;
+            read_asdu(&mut frame, &mut originator_buf)?;
         }
         originator_buf.resize(2, 0);
         let originator = u16::from_le_bytes(originator_buf.try_into().unwrap());
         let mut adsu_buf = vec![0; usize::from(config.adsu_address_len)];
-        frame.read_exact(&mut adsu_buf)?;
+        read_asdu(&mut frame, &mut adsu_buf)?;
         adsu_buf.resize(2, 0);
         let adsu = u16::from_le_bytes(adsu_buf.try_into().unwrap());
         let mut iou = Vec::with_capacity(iou_len);
@@ -335,7 +666,7 @@ impl Telegram101 {
         for i in 0..iou_len {
             let address = if i == 0 || !sequental {
                 let mut address_buf = vec![0; usize::from(config.iou_address_len)];
-                frame.read_exact(&mut address_buf)?;
+                read_asdu(&mut frame, &mut address_buf)?;
                 address_buf.resize(4, 0);
                 first_address = u32::from_le_bytes(address_buf.try_into().unwrap());
                 first_address
@@ -343,7 +674,7 @@ impl Telegram101 {
                 first_address + u32::try_from(i).unwrap()
             };
             let mut value = vec![0u8; data_type.size()];
-            frame.read_exact(&mut value)?;
+            read_asdu(&mut frame, &mut value)?;
 
             value.resize(MAX_IEC_DATA_LEN, 0);
 
@@ -407,14 +738,30 @@ impl Telegram101 {
         })
     }
     /// Read a telegram from a reader
-    pub fn read<R>(mut reader: R, config: Config) -> Result<Self, Error>
+    ///
+    /// If [`Config::strict_cot`] is set, ASDUs with a cause of transmission not allowed for the
+    /// data type are rejected
+    pub fn read<R>(reader: R, config: Config) -> Result<Self, Error>
+    where
+        R: Read,
+    {
+        Self::read_frame(reader, config, config.strict_cot)
+    }
+    /// Read a telegram from a reader without validating the cause of transmission
+    pub fn read_lenient<R>(reader: R, config: Config) -> Result<Self, Error>
+    where
+        R: Read,
+    {
+        Self::read_frame(reader, config, false)
+    }
+    fn read_frame<R>(mut reader: R, config: Config, validate: bool) -> Result<Self, Error>
     where
         R: Read,
     {
         let mut frame_header = [0; 1];
         reader.read_exact(&mut frame_header)?;
         match frame_header[0] {
-            IEC_HEADER => Self::read_variable_length(reader, config),
+            IEC_HEADER => Self::read_variable_length(reader, config, validate),
             IEC_HEADER_FIXED => Self::read_fixed_length(reader, config),
             IEC_ACK_POSITIVE => Ok(Self::new_ack(true)),
             IEC_ACK_NEGATIVE => Ok(Self::new_ack(false)),
@@ -439,10 +786,26 @@ impl Telegram101 {
 
     /// Write the telegram to a writer
     ///
+    /// If [`Config::strict_cot`] is set, the cause of transmission must be allowed for the data
+    /// type (see [`DataType::allows_cot`](crate::types::datatype::DataType::allows_cot))
+    pub fn write<W>(&self, writer: W) -> Result<(), Error>
+    where
+        W: Write,
+    {
+        self.write_frame(writer, self.config.unwrap_or_default().strict_cot)
+    }
+    /// Write the telegram to a writer without validating the cause of transmission (e.g. to
+    /// forward telegrams of devices deviating from the standard)
+    pub fn write_lenient<W>(&self, writer: W) -> Result<(), Error>
+    where
+        W: Write,
+    {
+        self.write_frame(writer, false)
+    }
     /// # Panics
     ///
     /// Should not panic
-    pub fn write<W>(&self, mut writer: W) -> Result<(), Error>
+    fn write_frame<W>(&self, mut writer: W, validate: bool) -> Result<(), Error>
     where
         W: Write,
     {
@@ -460,14 +823,10 @@ impl Telegram101 {
             if self.iou.len() > usize::from(u8::MAX) {
                 return Err(Error::invalid_data("too many information objects"));
             }
-            let mut capacity = 1 // control field
-            + usize::from(config.link_address_len) // link address
-            + 1 // data_type
-            + 1 // iou length
-            + 1 // cot
-            + usize::from(config.originator_address_len) // originator
-            + usize::from(config.adsu_address_len) // adsu
-            ;
+            if validate && !self.iou.is_empty() {
+                self.data_type.validate_cot(cot)?;
+            }
+            let mut capacity = config.header_len();
             let kind_size = self.data_type.size();
             if self.sequental {
                 capacity += usize::from(config.iou_address_len) + kind_size * self.iou.len();
@@ -498,7 +857,7 @@ impl Telegram101 {
                 buf.extend(&iou.value[..kind_size]);
             }
             let length = buf.len();
-            if length > 253 {
+            if length > usize::from(config.max_frame_len) {
                 return Err(Error::invalid_data("telegram too long"));
             }
             writer.write_all(&[
@@ -522,3 +881,144 @@ impl Telegram101 {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::{Config, LinkMode, Telegram101};
+    use crate::types::{COT, datatype::DataType};
+
+    #[test]
+    fn config_builder_link_address_len() {
+        assert!(
+            Config::builder()
+                .link_mode(LinkMode::Unbalanced)
+                .link_address_len(0)
+                .build()
+                .is_err()
+        );
+        let config = Config::builder()
+            .link_mode(LinkMode::Balanced)
+            .link_address_len(0)
+            .build()
+            .unwrap();
+        assert_eq!(config.link_address_len(), 0);
+        assert_eq!(config.broadcast_link_address(), None);
+        assert!(Config::try_new(LinkMode::Balanced, 3, 1, 2, 3).is_err());
+        assert!(Config::try_new(LinkMode::Unbalanced, 2, 0, 1, 2).is_ok());
+    }
+
+    #[test]
+    fn config_legacy_setters() {
+        // the link mode cross-check is left to validate
+        let config = Config::new().with_link_address_len(0);
+        assert!(config.validate().is_err());
+        assert!(config.with_link_mode(LinkMode::Balanced).validate().is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "link_address_len <= 2")]
+    fn config_legacy_setter_range() {
+        let _ = Config::new().with_link_address_len(3);
+    }
+
+    #[test]
+    fn config_builder_field_lengths() {
+        assert!(Config::builder().originator_address_len(2).build().is_err());
+        assert!(Config::builder().adsu_address_len(0).build().is_err());
+        assert!(Config::builder().adsu_address_len(3).build().is_err());
+        assert!(Config::builder().iou_address_len(0).build().is_err());
+        assert!(Config::builder().iou_address_len(4).build().is_err());
+        assert!(Config::builder().max_frame_len(254).build().is_err());
+        assert!(Config::builder().max_frame_len(11).build().is_err());
+        assert!(Config::builder().max_frame_len(12).build().is_ok());
+    }
+
+    #[test]
+    fn config_broadcast_link_address() {
+        let config = Config::new();
+        assert_eq!(config.broadcast_link_address(), Some(0xFF));
+        assert!(config.is_broadcast(0xFF));
+        let config = Config::builder().link_address_len(2).build().unwrap();
+        assert_eq!(config.broadcast_link_address(), Some(0xFFFF));
+        let config = Config::builder()
+            .broadcast_link_address(0x7F)
+            .build()
+            .unwrap();
+        assert!(config.is_broadcast(0x7F));
+        assert!(!config.is_broadcast(0xFF));
+        assert!(
+            Config::builder()
+                .broadcast_link_address(0x100)
+                .build()
+                .is_err()
+        );
+        let telegram = Telegram101::new_fixed(config).with_link_address(0x7F);
+        assert!(telegram.is_broadcast());
+    }
+
+    #[test]
+    fn max_frame_len_enforced() {
+        let config = Config::builder().max_frame_len(20).build().unwrap();
+        let mut telegram = Telegram101::new(DataType::M_SP_NA_1, COT::Spontan, 1, config);
+        telegram.append_iou(1, [1u8; 12]);
+        telegram.append_iou(2, [1u8; 12]);
+        let mut buf = Vec::new();
+        telegram.write(&mut buf).unwrap();
+        telegram.append_iou(3, [1u8; 12]);
+        telegram.append_iou(4, [1u8; 12]);
+        assert!(telegram.write(&mut Vec::new()).is_err());
+        let telegram = Telegram101::read(Cursor::new(buf.clone()), config).unwrap();
+        assert_eq!(telegram.iou().len(), 2);
+        let strict = Config::builder().max_frame_len(12).build().unwrap();
+        assert!(Telegram101::read(Cursor::new(buf), strict).is_err());
+    }
+
+    #[test]
+    fn cot_validation() {
+        let config = Config::new();
+        let mut telegram = Telegram101::new(DataType::C_SC_NA_1, COT::Act, 1, config);
+        telegram.append_iou(10, [1u8; 12]);
+        let mut buf = Vec::new();
+        telegram.write(&mut buf).unwrap();
+        assert!(Telegram101::read(Cursor::new(buf.clone()), config).is_ok());
+        let mut cyclic = Telegram101::new(DataType::C_SC_NA_1, COT::Cyclic, 1, config);
+        cyclic.append_iou(10, [1u8; 12]);
+        assert!(cyclic.write(&mut Vec::new()).is_err());
+        // without information objects, as I-frames of IEC 60870-5-104
+        let empty = Telegram101::new(DataType::C_SC_NA_1, COT::Cyclic, 1, config);
+        assert!(empty.write(&mut Vec::new()).is_ok());
+        // patch the COT and the checksum
+        let cot = 7 + usize::from(config.link_address_len);
+        buf[cot] = COT::Cyclic as u8;
+        let checksum = buf.len() - 2;
+        buf[checksum] = buf[checksum].wrapping_sub(COT::Act as u8 - COT::Cyclic as u8);
+        let err = Telegram101::read(Cursor::new(buf.clone()), config).unwrap_err();
+        assert!(err.to_string().contains("not allowed"));
+        let telegram = Telegram101::read_lenient(Cursor::new(buf.clone()), config).unwrap();
+        assert_eq!(telegram.cot(), COT::Cyclic);
+        // lenient configuration, e.g. for devices deviating from the standard
+        let lenient = config.with_strict_cot(false);
+        assert!(Telegram101::read(Cursor::new(buf), lenient).is_ok());
+        assert!(cyclic.write_lenient(&mut Vec::new()).is_ok());
+        let cyclic = Telegram101::new(DataType::C_SC_NA_1, COT::Cyclic, 1, lenient)
+            .with_iou(cyclic.iou().to_vec());
+        assert!(cyclic.write(&mut Vec::new()).is_ok());
+    }
+
+    #[test]
+    fn link_ack() {
+        let config = Config::builder().single_char_ack(true).build().unwrap();
+        let mut buf = Vec::new();
+        Telegram101::new_link_ack(config, 5)
+            .write(&mut buf)
+            .unwrap();
+        assert_eq!(buf, [0xE5]);
+        let mut buf = Vec::new();
+        Telegram101::new_link_ack(Config::new(), 5)
+            .write(&mut buf)
+            .unwrap();
+        assert_eq!(buf, [0x10, 0x00, 0x05, 0x05, 0x16]);
+    }
+}
@@ -2,9 +2,20 @@ use std::io::{Cursor, Read, Write};
 
 use crate::{
     Error,
-    types::{COT, DataBuffer, Iou, MAX_IEC_DATA_LEN, datatype::DataType},
+    types::{
+        COT, DataBuffer, Iou, MAX_IEC_DATA_LEN,
+        datatype::{DataType, add_time_tags, convert_cp24_time_tags, strip_time_tags},
+        time::CP56Time2a,
+    },
 };
 
+/// Broadcast common address
+pub(crate) const CA_BROADCAST: u16 = 0xFFFF;
+/// Max length of information objects in an I-frame (253 - control fields - ASDU header)
+pub(crate) const MAX_IOU_LEN: usize = 253 - 4 - 6;
+/// Max information object address (3 bytes)
+pub(crate) const IOA_MAX: u32 = 0x00FF_FFFF;
+
 const IEC_HEADER: u8 = 0x68;
 const FRAME_COUNTER_MAX: u16 = 32767;
 
@@ -100,22 +111,41 @@ impl Telegram104 {
         Self::U(Telegram104_U::new_test())
     }
     /// Write the telegram to a writer
-    pub fn write(&self, mut writer: impl Write) -> Result<(), Error> {
+    ///
+    /// The cause of transmission of I-frames must be allowed for the data type (see
+    /// [`DataType::allows_cot`](crate::types::datatype::DataType::allows_cot))
+    pub fn write(&self, writer: impl Write) -> Result<(), Error> {
+        self.write_frame(writer, true)
+    }
+    /// Write the telegram to a writer without validating the cause of transmission (e.g. to
+    /// forward telegrams of devices deviating from the standard)
+    pub fn write_lenient(&self, writer: impl Write) -> Result<(), Error> {
+        self.write_frame(writer, false)
+    }
+    fn write_frame(&self, mut writer: impl Write, validate: bool) -> Result<(), Error> {
         let mut buf = Cursor::new(Vec::with_capacity(256));
         buf.write_all(&[IEC_HEADER])?;
         match self {
             Self::U(u) => u.write(&mut buf)?,
             Self::S(s) => s.write(&mut buf)?,
-            Self::I(i) => i.write(&mut buf)?,
+            Self::I(i) => i.write(&mut buf, validate)?,
         }
         writer.write_all(&buf.into_inner()).map_err(Into::into)
     }
     /// Read the telegram from a reader
     ///
+    /// I-frames with a cause of transmission not allowed for the data type are rejected
+    pub fn read(reader: impl Read) -> Result<Self, Error> {
+        Self::read_frame(reader, true)
+    }
+    /// Read the telegram from a reader without validating the cause of transmission
+    pub fn read_lenient(reader: impl Read) -> Result<Self, Error> {
+        Self::read_frame(reader, false)
+    }
     /// # Panics
     ///
     /// Should not panic
-    pub fn read(mut reader: impl Read) -> Result<Self, Error> {
+    fn read_frame(mut reader: impl Read, validate: bool) -> Result<Self, Error> {
         let mut header_buf = [0u8, 2];
         reader.read_exact(&mut header_buf)?;
         if header_buf[0] != IEC_HEADER {
@@ -138,7 +168,7 @@ impl Telegram104 {
                 _ => return Err(Error::invalid_data("Invalid control field")),
             }
         } else {
-            Telegram104::I(Telegram104_I::read(Cursor::new(buf))?)
+            Telegram104::I(Telegram104_I::read(Cursor::new(buf), validate)?)
         })
     }
 }
@@ -180,6 +210,11 @@ impl Telegram104_S {
         //counter.increment_rx(); // TODO - check
         Ok(())
     }
+    /// Manually sets the RX (receive) sequence number
+    pub fn with_recv_sn(mut self, recv_sn: u16) -> Self {
+        self.recv_sn = recv_sn;
+        self
+    }
     /// Get the receive sequence number
     pub fn recv_sn(&self) -> u16 {
         self.recv_sn
@@ -498,7 +533,30 @@ impl Telegram104_I {
         self.sequental = true;
         self.iou.push(Iou::new(0, value));
     }
-    fn read<R>(mut reader: R) -> Result<Self, Error>
+    /// Converts the information objects with CP24Time2a time tags to the equivalent type with
+    /// CP56Time2a time tags, rewriting the type identifier (forwarding IEC 60870-5-101 data)
+    ///
+    /// The missing time tag fields are reconstructed from the reference time (see
+    /// [`CP56Time2a::from_cp24time2a`]). Telegrams of other types are returned unchanged.
+    pub fn into_cp56_time_tags(mut self, reference: &CP56Time2a) -> Result<Self, Error> {
+        self.data_type = convert_cp24_time_tags(self.data_type, &mut self.iou, reference)?;
+        Ok(self)
+    }
+    /// Adds the CP56Time2a time tag to the information objects, rewriting the type identifier
+    /// (e.g. spontaneous transmission of untimed process data). Telegrams of other types,
+    /// including CP24Time2a time tags (see [`Self::into_cp56_time_tags`]), are returned unchanged.
+    pub fn add_time_tag(mut self, time: &CP56Time2a) -> Self {
+        self.data_type = add_time_tags(self.data_type, &mut self.iou, time);
+        self
+    }
+    /// Removes the time tags of the information objects, rewriting the type identifier (e.g.
+    /// interrogation responses). Telegrams of types without untimed equivalent are returned
+    /// unchanged.
+    pub fn strip_time_tag(mut self) -> Self {
+        self.data_type = strip_time_tags(self.data_type, &mut self.iou);
+        self
+    }
+    fn read<R>(mut reader: R, validate: bool) -> Result<Self, Error>
     where
         R: Read,
     {
@@ -541,6 +599,9 @@ impl Telegram104_I {
 
         let cot = COT::try_from(cot_byte & 0b0011_1111)
             .map_err(|_| Error::invalid_data("Invalid COT"))?;
+        if validate && iou_len > 0 {
+            data_type.validate_cot(cot)?;
+        }
 
         let mut originator = [0u8; 1];
         reader.read_exact(&mut originator)?;
@@ -587,7 +648,7 @@ impl Telegram104_I {
         })
     }
 
-    fn write<W>(&self, mut writer: W) -> Result<(), Error>
+    fn write<W>(&self, mut writer: W, validate: bool) -> Result<(), Error>
     where
         W: Write,
     {
@@ -600,6 +661,9 @@ impl Telegram104_I {
         if self.iou.len() > usize::from(u8::MAX) {
             return Err(Error::invalid_data("too many information objects"));
         }
+        if validate && !self.iou.is_empty() {
+            self.data_type.validate_cot(self.cot)?;
+        }
         let kind_size = self.data_type.size();
         let mut length = 4; // control fields
         if !self.iou.is_empty() {
@@ -656,7 +720,119 @@ impl Telegram104_I {
 
 #[cfg(test)]
 mod tests {
-    use super::{ChatSequenceCounter, FRAME_COUNTER_MAX};
+    use super::{ChatSequenceCounter, FRAME_COUNTER_MAX, Telegram104, Telegram104_I};
+    use crate::types::{
+        COT,
+        datatype::{
+            C_SC_NA_1, C_SE_NB_1, C_SE_TB_1, DataType, M_ME_NB_1, M_ME_TE_1, M_SP_TA_1, M_SP_TB_1,
+            QDS, SVA,
+        },
+        time::{CP24Time2a, CP56Time2a},
+    };
+
+    #[test]
+    fn telegram_into_cp56_time_tags() {
+        let reference = CP56Time2a {
+            ms: 0,
+            iv: false,
+            min: 15,
+            su: false,
+            hour: 8,
+            dow: 3.into(),
+            day: 3,
+            month: 1,
+            year: 24,
+        };
+        let mut telegram = Telegram104_I::new(DataType::M_SP_TA_1, COT::Spontan, 1);
+        telegram.append_iou(
+            100,
+            M_SP_TA_1 {
+                time: CP24Time2a {
+                    ms: 1_234,
+                    min: 14,
+                    iv: false,
+                },
+                ..M_SP_TA_1::default()
+            },
+        );
+        let telegram = telegram.into_cp56_time_tags(&reference).unwrap();
+        assert_eq!(telegram.data_type(), DataType::M_SP_TB_1);
+        let mut buf = Vec::new();
+        Telegram104::from(telegram).write(&mut buf).unwrap();
+        let Telegram104::I(telegram) = Telegram104::read(&buf[..]).unwrap() else {
+            panic!("I-frame expected");
+        };
+        assert_eq!(telegram.data_type(), DataType::M_SP_TB_1);
+        let value = M_SP_TB_1::from(telegram.iou()[0].value());
+        assert_eq!(
+            (value.time.hour, value.time.min, value.time.ms),
+            (8, 14, 1_234)
+        );
+    }
+
+    #[test]
+    fn telegram_add_strip_time_tag() {
+        let time = CP56Time2a {
+            min: 30,
+            hour: 12,
+            day: 5,
+            month: 2,
+            year: 24,
+            ..CP56Time2a::default()
+        };
+        let value = M_ME_NB_1 {
+            sva: SVA { value: -100 },
+            qds: QDS {
+                ov: true,
+                ..QDS::default()
+            },
+        };
+        let mut telegram = Telegram104_I::new(DataType::M_ME_NB_1, COT::Spontan, 1);
+        telegram.append_iou(1, value.clone());
+        telegram.append_iou(2, value.clone());
+        let telegram = telegram.add_time_tag(&time);
+        assert_eq!(telegram.data_type(), DataType::M_ME_TE_1);
+        for iou in telegram.iou() {
+            let timed = M_ME_TE_1::from(iou.value());
+            assert_eq!(
+                (&timed.sva, &timed.qds, &timed.time),
+                (&value.sva, &value.qds, &time)
+            );
+        }
+        let telegram = telegram.strip_time_tag();
+        assert_eq!(telegram.data_type(), DataType::M_ME_NB_1);
+        assert_eq!(M_ME_NB_1::from(telegram.iou()[1].value()), value);
+        // already untimed
+        assert_eq!(telegram.strip_time_tag().data_type(), DataType::M_ME_NB_1);
+        let mut telegram = Telegram104_I::new(DataType::C_SE_NB_1, COT::Act, 1);
+        telegram.append_iou(
+            10,
+            C_SE_NB_1 {
+                sva: SVA { value: 7 },
+                ..C_SE_NB_1::default()
+            },
+        );
+        let telegram = telegram.add_time_tag(&time);
+        assert_eq!(telegram.data_type(), DataType::C_SE_TB_1);
+        let command = C_SE_TB_1::from(telegram.iou()[0].value());
+        assert_eq!((command.sva.value, command.time), (7, time));
+        assert_eq!(telegram.strip_time_tag().data_type(), DataType::C_SE_NB_1);
+    }
+
+    #[test]
+    fn telegram_cot_validation() {
+        let mut telegram = Telegram104_I::new(DataType::C_SC_NA_1, COT::Act, 1);
+        telegram.append_iou(10, C_SC_NA_1::default());
+        let mut buf = Vec::new();
+        Telegram104::I(telegram.clone()).write(&mut buf).unwrap();
+        assert!(Telegram104::read(buf.as_slice()).is_ok());
+        // single commands are never cyclic
+        let telegram = telegram.with_cot(COT::Cyclic);
+        assert!(Telegram104::I(telegram).write(&mut Vec::new()).is_err());
+        buf[8] = COT::Cyclic as u8;
+        let err = Telegram104::read(buf.as_slice()).unwrap_err();
+        assert!(err.to_string().contains("not allowed"));
+    }
 
     #[test]
     fn chat_sequence_counter_new() {
@@ -137,6 +137,64 @@ pub enum COT {
     COT63 = 63,
 }
 
+impl COT {
+    /// Interrogated by the group (1..=16), 0 for the station interrogation
+    pub(crate) fn interrogated(group: u8) -> COT {
+        match group {
+            0 => COT::Inrogen,
+            1 => COT::Inro1,
+            2 => COT::Inro2,
+            3 => COT::Inro3,
+            4 => COT::Inro4,
+            5 => COT::Inro5,
+            6 => COT::Inro6,
+            7 => COT::Inro7,
+            8 => COT::Inro8,
+            9 => COT::Inro9,
+            10 => COT::Inro10,
+            11 => COT::Inro11,
+            12 => COT::Inro12,
+            13 => COT::Inro13,
+            14 => COT::Inro14,
+            15 => COT::Inro15,
+            _ => COT::Inro16,
+        }
+    }
+    /// Requested by the counter group (1..=4), 0 for the general request
+    pub(crate) fn counter_interrogated(group: u8) -> COT {
+        match group {
+            0 => COT::ReqCoGen,
+            1 => COT::ReqCo1,
+            2 => COT::ReqCo2,
+            3 => COT::ReqCo3,
+            _ => COT::ReqCo4,
+        }
+    }
+    /// Confirmation of a command with the cause (activation or deactivation), other causes are
+    /// mirrored
+    pub(crate) fn confirmation(self) -> COT {
+        match self {
+            COT::Act => COT::ActCon,
+            COT::Deact => COT::DeactCon,
+            cot => cot,
+        }
+    }
+    /// Is the COT a response of the controlled station to a command (confirmation, termination
+    /// or an error)
+    pub(crate) fn is_command_response(self) -> bool {
+        matches!(
+            self,
+            COT::ActCon
+                | COT::DeactCon
+                | COT::ActTerm
+                | COT::UnknownType
+                | COT::UnknownCause
+                | COT::UnknownAsduAddress
+                | COT::UnknownObjectAddress
+        )
+    }
+}
+
 impl TryFrom<u8> for COT {
     type Error = Error;
 
@@ -1,10 +1,12 @@
 #![allow(non_camel_case_types)]
 
+use bma_ts::Timestamp;
+
 use crate::Error;
 
 use super::{
-    DataBuffer,
-    time::{CP16Time2a, CP24Time2a, CP56Time2a},
+    DataBuffer, Iou,
+    time::{CP16Time2a, CP24Time2a, CP56Time2a, TimePolicy, TimeReference},
 };
 
 /// Single point information
@@ -339,9 +341,9 @@ impl From<QDS> for u8 {
 /// Value with transient state indication
 #[derive(Debug, Clone, Eq, PartialEq, Default)]
 pub struct VTI {
-    /// Value
-    pub value: u8,
-    /// Transient state indication
+    /// Value, signed 7 bits (-64..=63), out of range values are truncated on encoding
+    pub value: i8,
+    /// Transient state indication (the equipment is moving)
     pub transient: bool,
     /// Quality descriptor
     pub qds: QDS,
@@ -350,8 +352,9 @@ pub struct VTI {
 impl From<[u8; 2]> for VTI {
     fn from(buf: [u8; 2]) -> Self {
         VTI {
-            value: buf[0] & 0b0111_1111,
-            transient: buf[1] & 0b1000_0000 != 0,
+            // sign extension of the 7-bit value
+            value: (buf[0] << 1).cast_signed() >> 1,
+            transient: buf[0] & 0b1000_0000 != 0,
             qds: QDS::from(buf[1]),
         }
     }
@@ -360,7 +363,7 @@ impl From<[u8; 2]> for VTI {
 impl From<VTI> for [u8; 2] {
     fn from(data: VTI) -> [u8; 2] {
         let mut buf = [0; 2];
-        buf[0] = data.value | (u8::from(data.transient) << 7);
+        buf[0] = data.value.cast_unsigned() & 0b0111_1111 | (u8::from(data.transient) << 7);
         buf[1] = u8::from(data.qds);
         buf
     }
@@ -495,17 +498,36 @@ impl From<M_BO_TA_1> for DataBuffer {
     }
 }
 
-/// Normalized value
+/// Normalized value, a fraction in the range [-1, 1 - 2^-15]
 #[derive(Debug, Clone, Eq, PartialEq, Default)]
 pub struct NVA {
-    /// Value
-    pub value: u16,
+    /// Value, in units of 2^-15
+    pub value: i16,
+}
+
+impl NVA {
+    /// The fraction
+    pub fn to_f32(&self) -> f32 {
+        f32::from(self.value) / 32768.0
+    }
+    /// Create from a fraction, out of range values are saturated
+    // the value is rounded and clamped to the i16 range before the cast
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn from_f32(value: f32) -> Self {
+        NVA {
+            value: if value.is_nan() {
+                0
+            } else {
+                (value * 32768.0).round().clamp(-32768.0, 32767.0) as i16
+            },
+        }
+    }
 }
 
 impl From<[u8; 2]> for NVA {
     fn from(buf: [u8; 2]) -> Self {
         NVA {
-            value: u16::from_le_bytes(buf),
+            value: i16::from_le_bytes(buf),
         }
     }
 }
@@ -578,13 +600,32 @@ impl From<M_ME_TA_1> for DataBuffer {
 #[derive(Debug, Clone, Eq, PartialEq, Default)]
 pub struct SVA {
     /// Value
-    pub value: u16,
+    pub value: i16,
+}
+
+impl SVA {
+    /// The value as a float
+    pub fn to_f32(&self) -> f32 {
+        f32::from(self.value)
+    }
+    /// Create from a float, the value is rounded, out of range values are saturated
+    // the value is rounded and clamped to the i16 range before the cast
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn from_f32(value: f32) -> Self {
+        SVA {
+            value: if value.is_nan() {
+                0
+            } else {
+                value.round().clamp(-32768.0, 32767.0) as i16
+            },
+        }
+    }
 }
 
 impl From<[u8; 2]> for SVA {
     fn from(buf: [u8; 2]) -> Self {
         SVA {
-            value: u16::from_le_bytes(buf),
+            value: i16::from_le_bytes(buf),
         }
     }
 }
@@ -916,6 +957,18 @@ impl From<M_EP_TA_1> for DataBuffer {
     }
 }
 
+impl M_EP_TA_1 {
+    /// Start (time tag, resolved against the reference) and end (elapsed time) of the event
+    pub fn resolve(
+        &self,
+        reference: impl Into<TimeReference>,
+        policy: &TimePolicy,
+    ) -> Result<(Timestamp, Timestamp), Error> {
+        let start = self.time.resolve(reference, policy)?;
+        Ok((start, self.elapsed.resolve(start)))
+    }
+}
+
 /// Start events of protection equipment
 #[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Clone, Eq, PartialEq, Default)]
@@ -1031,6 +1084,18 @@ impl From<M_EP_TB_1> for DataBuffer {
     }
 }
 
+impl M_EP_TB_1 {
+    /// Start (time tag, resolved against the reference) and end (relay duration time) of the event
+    pub fn resolve(
+        &self,
+        reference: impl Into<TimeReference>,
+        policy: &TimePolicy,
+    ) -> Result<(Timestamp, Timestamp), Error> {
+        let start = self.time.resolve(reference, policy)?;
+        Ok((start, self.relay_duration.resolve(start)))
+    }
+}
+
 /// Output circuit information
 #[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Clone, Eq, PartialEq, Default)]
@@ -1100,6 +1165,18 @@ impl From<M_EP_TC_1> for DataBuffer {
     }
 }
 
+impl M_EP_TC_1 {
+    /// Start (time tag, resolved against the reference) and end (relay operation time) of the event
+    pub fn resolve(
+        &self,
+        reference: impl Into<TimeReference>,
+        policy: &TimePolicy,
+    ) -> Result<(Timestamp, Timestamp), Error> {
+        let start = self.time.resolve(reference, policy)?;
+        Ok((start, self.relay_op_time.resolve(start)))
+    }
+}
+
 /// Status change detection
 #[derive(Debug, Clone, Eq, PartialEq, Default)]
 pub struct SCD {
@@ -1229,9 +1306,7 @@ impl From<M_DP_TB_1> for DataBuffer {
 #[derive(Debug, Clone, Eq, PartialEq, Default)]
 pub struct M_ST_TB_1 {
     /// Value with transient state indication
-    pub vti_value: u8,
-    /// Quality descriptor
-    pub qds: QDS,
+    pub vti: VTI,
     /// Time tag
     pub time: CP56Time2a,
 }
@@ -1239,8 +1314,7 @@ pub struct M_ST_TB_1 {
 impl From<DataBuffer> for M_ST_TB_1 {
     fn from(buf: DataBuffer) -> Self {
         Self {
-            vti_value: buf[0],
-            qds: QDS::from(buf[1]),
+            vti: VTI::from([buf[0], buf[1]]),
             time: CP56Time2a::from([buf[2], buf[3], buf[4], buf[5], buf[6], buf[7], buf[8]]),
         }
     }
@@ -1249,8 +1323,7 @@ impl From<DataBuffer> for M_ST_TB_1 {
 impl From<M_ST_TB_1> for DataBuffer {
     fn from(data: M_ST_TB_1) -> DataBuffer {
         let mut buf = DataBuffer::default();
-        buf[0] = data.vti_value;
-        buf[1] = u8::from(data.qds);
+        buf[0..2].copy_from_slice(&<[u8; 2]>::from(data.vti));
         buf[2..9].copy_from_slice(&<[u8; 7]>::from(data.time));
         buf
     }
@@ -1442,6 +1515,14 @@ impl From<M_EP_TD_1> for DataBuffer {
     }
 }
 
+impl M_EP_TD_1 {
+    /// Start (time tag) and end (elapsed time) of the event
+    pub fn resolve(&self, policy: &TimePolicy) -> Result<(Timestamp, Timestamp), Error> {
+        let start = self.time.to_timestamp(policy)?;
+        Ok((start, self.elapsed.resolve(start)))
+    }
+}
+
 /// Packed start events of protection equipment with time tag
 #[derive(Debug, Clone, Eq, PartialEq, Default)]
 pub struct M_EP_TE_1 {
@@ -1477,6 +1558,14 @@ impl From<M_EP_TE_1> for DataBuffer {
     }
 }
 
+impl M_EP_TE_1 {
+    /// Start (time tag) and end (relay duration time) of the event
+    pub fn resolve(&self, policy: &TimePolicy) -> Result<(Timestamp, Timestamp), Error> {
+        let start = self.time.to_timestamp(policy)?;
+        Ok((start, self.relay_duration.resolve(start)))
+    }
+}
+
 /// Packed output circuit information with time tag
 #[derive(Debug, Clone, Eq, PartialEq, Default)]
 pub struct M_EP_TF_1 {
@@ -1512,8 +1601,16 @@ impl From<M_EP_TF_1> for DataBuffer {
     }
 }
 
+impl M_EP_TF_1 {
+    /// Start (time tag) and end (relay operation time) of the event
+    pub fn resolve(&self, policy: &TimePolicy) -> Result<(Timestamp, Timestamp), Error> {
+        let start = self.time.to_timestamp(policy)?;
+        Ok((start, self.relay_op_time.resolve(start)))
+    }
+}
+
 /// Select/execute command
-#[derive(Debug, Clone, Eq, PartialEq, Default)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
 #[repr(u8)]
 pub enum SelectExecute {
     /// Execute
@@ -3020,6 +3117,88 @@ impl TryFrom<u8> for DataType {
 }
 
 impl DataType {
+    /// Commands, terminated by the controlled station with ActTerm after ActCon
+    pub(crate) fn has_termination(self) -> bool {
+        matches!(
+            self,
+            DataType::C_SC_NA_1
+                | DataType::C_DC_NA_1
+                | DataType::C_RC_NA_1
+                | DataType::C_SE_NA_1
+                | DataType::C_SE_NB_1
+                | DataType::C_SE_NC_1
+                | DataType::C_BO_NA_1
+                | DataType::C_SC_TA_1
+                | DataType::C_DC_TA_1
+                | DataType::C_RC_TA_1
+                | DataType::C_SE_TA_1
+                | DataType::C_SE_TB_1
+                | DataType::C_SE_TC_1
+                | DataType::C_BO_TA_1
+                | DataType::C_IC_NA_1
+                | DataType::C_CI_NA_1
+        )
+    }
+    /// The equivalent type with a CP56Time2a time tag for types with a CP24Time2a time tag
+    pub fn cp56_equivalent(self) -> Option<DataType> {
+        match self {
+            DataType::M_SP_TA_1 => Some(DataType::M_SP_TB_1),
+            DataType::M_DP_TA_1 => Some(DataType::M_DP_TB_1),
+            DataType::M_ST_TA_1 => Some(DataType::M_ST_TB_1),
+            DataType::M_BO_TA_1 => Some(DataType::M_BO_TB_1),
+            DataType::M_ME_TA_1 => Some(DataType::M_ME_TD_1),
+            DataType::M_ME_TB_1 => Some(DataType::M_ME_TE_1),
+            DataType::M_ME_TC_1 => Some(DataType::M_ME_TF_1),
+            DataType::M_IT_TA_1 => Some(DataType::M_IT_TB_1),
+            DataType::M_EP_TA_1 => Some(DataType::M_EP_TD_1),
+            DataType::M_EP_TB_1 => Some(DataType::M_EP_TE_1),
+            DataType::M_EP_TC_1 => Some(DataType::M_EP_TF_1),
+            _ => None,
+        }
+    }
+    /// The equivalent type with a CP56Time2a time tag for untimed types and types with a
+    /// CP24Time2a time tag
+    pub fn timed(self) -> Option<DataType> {
+        match self {
+            DataType::M_SP_NA_1 => Some(DataType::M_SP_TB_1),
+            DataType::M_DP_NA_1 => Some(DataType::M_DP_TB_1),
+            DataType::M_ST_NA_1 => Some(DataType::M_ST_TB_1),
+            DataType::M_BO_NA_1 => Some(DataType::M_BO_TB_1),
+            DataType::M_ME_NA_1 => Some(DataType::M_ME_TD_1),
+            DataType::M_ME_NB_1 => Some(DataType::M_ME_TE_1),
+            DataType::M_ME_NC_1 => Some(DataType::M_ME_TF_1),
+            DataType::M_IT_NA_1 => Some(DataType::M_IT_TB_1),
+            DataType::C_SC_NA_1 => Some(DataType::C_SC_TA_1),
+            DataType::C_DC_NA_1 => Some(DataType::C_DC_TA_1),
+            DataType::C_RC_NA_1 => Some(DataType::C_RC_TA_1),
+            DataType::C_SE_NA_1 => Some(DataType::C_SE_TA_1),
+            DataType::C_SE_NB_1 => Some(DataType::C_SE_TB_1),
+            DataType::C_SE_NC_1 => Some(DataType::C_SE_TC_1),
+            DataType::C_BO_NA_1 => Some(DataType::C_BO_TA_1),
+            _ => self.cp56_equivalent(),
+        }
+    }
+    /// The equivalent type without time tag for time-tagged types
+    pub fn untimed(self) -> Option<DataType> {
+        match self {
+            DataType::M_SP_TA_1 | DataType::M_SP_TB_1 => Some(DataType::M_SP_NA_1),
+            DataType::M_DP_TA_1 | DataType::M_DP_TB_1 => Some(DataType::M_DP_NA_1),
+            DataType::M_ST_TA_1 | DataType::M_ST_TB_1 => Some(DataType::M_ST_NA_1),
+            DataType::M_BO_TA_1 | DataType::M_BO_TB_1 => Some(DataType::M_BO_NA_1),
+            DataType::M_ME_TA_1 | DataType::M_ME_TD_1 => Some(DataType::M_ME_NA_1),
+            DataType::M_ME_TB_1 | DataType::M_ME_TE_1 => Some(DataType::M_ME_NB_1),
+            DataType::M_ME_TC_1 | DataType::M_ME_TF_1 => Some(DataType::M_ME_NC_1),
+            DataType::M_IT_TA_1 | DataType::M_IT_TB_1 => Some(DataType::M_IT_NA_1),
+            DataType::C_SC_TA_1 => Some(DataType::C_SC_NA_1),
+            DataType::C_DC_TA_1 => Some(DataType::C_DC_NA_1),
+            DataType::C_RC_TA_1 => Some(DataType::C_RC_NA_1),
+            DataType::C_SE_TA_1 => Some(DataType::C_SE_NA_1),
+            DataType::C_SE_TB_1 => Some(DataType::C_SE_NB_1),
+            DataType::C_SE_TC_1 => Some(DataType::C_SE_NC_1),
+            DataType::C_BO_TA_1 => Some(DataType::C_BO_NA_1),
+            _ => None,
+        }
+    }
     /// Get the size of the data type in bytes
     #[allow(clippy::match_same_arms)]
     pub fn size(self) -> usize {
@@ -3098,3 +3277,533 @@ impl DataType {
         }
     }
 }
+
+/// Information with a CP24Time2a time tag, having an equivalent type with a CP56Time2a time tag
+///
+/// IEC 60870-5-104 does not allow the CP24Time2a types, so 101 data must be converted before
+/// being forwarded.
+pub trait IntoCP56TimeTag {
+    /// The equivalent type with a CP56Time2a time tag
+    type Output;
+    /// Converts the information, the missing time tag fields are reconstructed from the reference
+    /// time (see [`CP56Time2a::from_cp24time2a`])
+    fn into_cp56_time_tag(self, reference: &CP56Time2a) -> Result<Self::Output, Error>;
+}
+
+impl IntoCP56TimeTag for M_SP_TA_1 {
+    type Output = M_SP_TB_1;
+
+    fn into_cp56_time_tag(self, reference: &CP56Time2a) -> Result<M_SP_TB_1, Error> {
+        Ok(M_SP_TB_1 {
+            siq: self.siq,
+            time: CP56Time2a::from_cp24time2a(self.time, reference)?,
+        })
+    }
+}
+
+impl IntoCP56TimeTag for M_DP_TA_1 {
+    type Output = M_DP_TB_1;
+
+    fn into_cp56_time_tag(self, reference: &CP56Time2a) -> Result<M_DP_TB_1, Error> {
+        Ok(M_DP_TB_1 {
+            diq: self.diq,
+            time: CP56Time2a::from_cp24time2a(self.time, reference)?,
+        })
+    }
+}
+
+impl IntoCP56TimeTag for M_ST_TA_1 {
+    type Output = M_ST_TB_1;
+
+    fn into_cp56_time_tag(self, reference: &CP56Time2a) -> Result<M_ST_TB_1, Error> {
+        Ok(M_ST_TB_1 {
+            vti: self.vti,
+            time: CP56Time2a::from_cp24time2a(self.time, reference)?,
+        })
+    }
+}
+
+impl IntoCP56TimeTag for M_BO_TA_1 {
+    type Output = M_BO_TB_1;
+
+    fn into_cp56_time_tag(self, reference: &CP56Time2a) -> Result<M_BO_TB_1, Error> {
+        Ok(M_BO_TB_1 {
+            bsi: self.bsi,
+            qds: self.qds,
+            time: CP56Time2a::from_cp24time2a(self.time, reference)?,
+        })
+    }
+}
+
+impl IntoCP56TimeTag for M_ME_TA_1 {
+    type Output = M_ME_TD_1;
+
+    fn into_cp56_time_tag(self, reference: &CP56Time2a) -> Result<M_ME_TD_1, Error> {
+        Ok(M_ME_TD_1 {
+            nva: self.nva,
+            qds: self.qds,
+            time: CP56Time2a::from_cp24time2a(self.time, reference)?,
+        })
+    }
+}
+
+impl IntoCP56TimeTag for M_ME_TB_1 {
+    type Output = M_ME_TE_1;
+
+    fn into_cp56_time_tag(self, reference: &CP56Time2a) -> Result<M_ME_TE_1, Error> {
+        Ok(M_ME_TE_1 {
+            sva: self.sva,
+            qds: self.qds,
+            time: CP56Time2a::from_cp24time2a(self.time, reference)?,
+        })
+    }
+}
+
+impl IntoCP56TimeTag for M_ME_TC_1 {
+    type Output = M_ME_TF_1;
+
+    fn into_cp56_time_tag(self, reference: &CP56Time2a) -> Result<M_ME_TF_1, Error> {
+        Ok(M_ME_TF_1 {
+            r32: self.r32,
+            qds: self.qds,
+            time: CP56Time2a::from_cp24time2a(self.time, reference)?,
+        })
+    }
+}
+
+impl IntoCP56TimeTag for M_IT_TA_1 {
+    type Output = M_IT_TB_1;
+
+    fn into_cp56_time_tag(self, reference: &CP56Time2a) -> Result<M_IT_TB_1, Error> {
+        Ok(M_IT_TB_1 {
+            bcr: self.bcr,
+            seq_qd: self.seq_qd,
+            time: CP56Time2a::from_cp24time2a(self.time, reference)?,
+        })
+    }
+}
+
+impl IntoCP56TimeTag for M_EP_TA_1 {
+    type Output = M_EP_TD_1;
+
+    fn into_cp56_time_tag(self, reference: &CP56Time2a) -> Result<M_EP_TD_1, Error> {
+        Ok(M_EP_TD_1 {
+            sep: self.sep,
+            elapsed: self.elapsed,
+            time: CP56Time2a::from_cp24time2a(self.time, reference)?,
+        })
+    }
+}
+
+impl IntoCP56TimeTag for M_EP_TB_1 {
+    type Output = M_EP_TE_1;
+
+    fn into_cp56_time_tag(self, reference: &CP56Time2a) -> Result<M_EP_TE_1, Error> {
+        Ok(M_EP_TE_1 {
+            start_ep: self.start_ep,
+            qdp: self.qdp,
+            relay_duration: self.relay_duration,
+            time: CP56Time2a::from_cp24time2a(self.time, reference)?,
+        })
+    }
+}
+
+impl IntoCP56TimeTag for M_EP_TC_1 {
+    type Output = M_EP_TF_1;
+
+    fn into_cp56_time_tag(self, reference: &CP56Time2a) -> Result<M_EP_TF_1, Error> {
+        Ok(M_EP_TF_1 {
+            oci: self.oci,
+            qdp: self.qdp,
+            relay_op_time: self.relay_op_time,
+            time: CP56Time2a::from_cp24time2a(self.time, reference)?,
+        })
+    }
+}
+
+fn iou_into_cp56_time_tag<T>(iou: &mut [Iou], reference: &CP56Time2a) -> Result<(), Error>
+where
+    T: From<DataBuffer> + IntoCP56TimeTag,
+    T::Output: Into<DataBuffer>,
+{
+    for i in iou {
+        i.value = T::from(i.value).into_cp56_time_tag(reference)?.into();
+    }
+    Ok(())
+}
+
+/// Converts information objects of a CP24Time2a type to the CP56Time2a equivalent, returns the
+/// new data type (unchanged if the type has no CP24Time2a time tag)
+pub(crate) fn convert_cp24_time_tags(
+    data_type: DataType,
+    iou: &mut [Iou],
+    reference: &CP56Time2a,
+) -> Result<DataType, Error> {
+    match data_type {
+        DataType::M_SP_TA_1 => iou_into_cp56_time_tag::<M_SP_TA_1>(iou, reference)?,
+        DataType::M_DP_TA_1 => iou_into_cp56_time_tag::<M_DP_TA_1>(iou, reference)?,
+        DataType::M_ST_TA_1 => iou_into_cp56_time_tag::<M_ST_TA_1>(iou, reference)?,
+        DataType::M_BO_TA_1 => iou_into_cp56_time_tag::<M_BO_TA_1>(iou, reference)?,
+        DataType::M_ME_TA_1 => iou_into_cp56_time_tag::<M_ME_TA_1>(iou, reference)?,
+        DataType::M_ME_TB_1 => iou_into_cp56_time_tag::<M_ME_TB_1>(iou, reference)?,
+        DataType::M_ME_TC_1 => iou_into_cp56_time_tag::<M_ME_TC_1>(iou, reference)?,
+        DataType::M_IT_TA_1 => iou_into_cp56_time_tag::<M_IT_TA_1>(iou, reference)?,
+        DataType::M_EP_TA_1 => iou_into_cp56_time_tag::<M_EP_TA_1>(iou, reference)?,
+        DataType::M_EP_TB_1 => iou_into_cp56_time_tag::<M_EP_TB_1>(iou, reference)?,
+        DataType::M_EP_TC_1 => iou_into_cp56_time_tag::<M_EP_TC_1>(iou, reference)?,
+        _ => return Ok(data_type),
+    }
+    Ok(data_type.cp56_equivalent().unwrap())
+}
+
+/// Information without time tag which can be sent with a CP56Time2a time tag (e.g. spontaneous
+/// data), types with a CP24Time2a time tag are converted with [`IntoCP56TimeTag`]
+pub trait AddTimeTag {
+    /// The equivalent type with a CP56Time2a time tag
+    type Output;
+    /// Converts the information, adding the time tag
+    fn add_time_tag(self, time: CP56Time2a) -> Self::Output;
+}
+
+/// Information with a time tag which can be sent without it (e.g. interrogation responses)
+pub trait StripTimeTag {
+    /// The equivalent type without time tag
+    type Output;
+    /// Converts the information, removing the time tag
+    fn strip_time_tag(self) -> Self::Output;
+}
+
+macro_rules! impl_add_time_tag {
+    ($from: ident, $to: ident, $($field: ident),+) => {
+        impl AddTimeTag for $from {
+            type Output = $to;
+
+            fn add_time_tag(self, time: CP56Time2a) -> $to {
+                $to {
+                    $($field: self.$field,)+
+                    time,
+                }
+            }
+        }
+    };
+}
+
+macro_rules! impl_strip_time_tag {
+    ($from: ident, $to: ident, $($field: ident),+) => {
+        impl StripTimeTag for $from {
+            type Output = $to;
+
+            fn strip_time_tag(self) -> $to {
+                $to {
+                    $($field: self.$field,)+
+                }
+            }
+        }
+    };
+}
+
+macro_rules! impl_time_tag_conversion {
+    ($untimed: ident, $timed: ident, $($field: ident),+) => {
+        impl_add_time_tag!($untimed, $timed, $($field),+);
+        impl_strip_time_tag!($timed, $untimed, $($field),+);
+    };
+}
+
+impl_time_tag_conversion!(M_SP_NA_1, M_SP_TB_1, siq);
+impl_time_tag_conversion!(M_DP_NA_1, M_DP_TB_1, diq);
+impl_time_tag_conversion!(M_ST_NA_1, M_ST_TB_1, vti);
+impl_time_tag_conversion!(M_BO_NA_1, M_BO_TB_1, bsi, qds);
+impl_time_tag_conversion!(M_ME_NA_1, M_ME_TD_1, nva, qds);
+impl_time_tag_conversion!(M_ME_NB_1, M_ME_TE_1, sva, qds);
+impl_time_tag_conversion!(M_ME_NC_1, M_ME_TF_1, r32, qds);
+impl_time_tag_conversion!(M_IT_NA_1, M_IT_TB_1, bcr, seq_qd);
+impl_time_tag_conversion!(C_SC_NA_1, C_SC_TA_1, sco);
+impl_time_tag_conversion!(C_DC_NA_1, C_DC_TA_1, dco);
+impl_time_tag_conversion!(C_RC_NA_1, C_RC_TA_1, rco);
+impl_time_tag_conversion!(C_SE_NA_1, C_SE_TA_1, nva, qos);
+impl_time_tag_conversion!(C_SE_NB_1, C_SE_TB_1, sva, qos);
+impl_time_tag_conversion!(C_SE_NC_1, C_SE_TC_1, r32, qos);
+impl_time_tag_conversion!(C_BO_NA_1, C_BO_TB_1, bsi);
+
+impl_strip_time_tag!(M_SP_TA_1, M_SP_NA_1, siq);
+impl_strip_time_tag!(M_DP_TA_1, M_DP_NA_1, diq);
+impl_strip_time_tag!(M_ST_TA_1, M_ST_NA_1, vti);
+impl_strip_time_tag!(M_BO_TA_1, M_BO_NA_1, bsi, qds);
+impl_strip_time_tag!(M_ME_TA_1, M_ME_NA_1, nva, qds);
+impl_strip_time_tag!(M_ME_TB_1, M_ME_NB_1, sva, qds);
+impl_strip_time_tag!(M_ME_TC_1, M_ME_NC_1, r32, qds);
+impl_strip_time_tag!(M_IT_TA_1, M_IT_NA_1, bcr, seq_qd);
+
+fn iou_add_time_tag<T>(iou: &mut [Iou], time: &CP56Time2a)
+where
+    T: From<DataBuffer> + AddTimeTag,
+    T::Output: Into<DataBuffer>,
+{
+    for i in iou {
+        i.value = T::from(i.value).add_time_tag(time.clone()).into();
+    }
+}
+
+fn iou_strip_time_tag<T>(iou: &mut [Iou])
+where
+    T: From<DataBuffer> + StripTimeTag,
+    T::Output: Into<DataBuffer>,
+{
+    for i in iou {
+        i.value = T::from(i.value).strip_time_tag().into();
+    }
+}
+
+/// Adds the CP56Time2a time tag to information objects, returns the new data type (unchanged if
+/// the type has no time-tagged equivalent or already has a time tag)
+pub(crate) fn add_time_tags(data_type: DataType, iou: &mut [Iou], time: &CP56Time2a) -> DataType {
+    match data_type {
+        DataType::M_SP_NA_1 => iou_add_time_tag::<M_SP_NA_1>(iou, time),
+        DataType::M_DP_NA_1 => iou_add_time_tag::<M_DP_NA_1>(iou, time),
+        DataType::M_ST_NA_1 => iou_add_time_tag::<M_ST_NA_1>(iou, time),
+        DataType::M_BO_NA_1 => iou_add_time_tag::<M_BO_NA_1>(iou, time),
+        DataType::M_ME_NA_1 => iou_add_time_tag::<M_ME_NA_1>(iou, time),
+        DataType::M_ME_NB_1 => iou_add_time_tag::<M_ME_NB_1>(iou, time),
+        DataType::M_ME_NC_1 => iou_add_time_tag::<M_ME_NC_1>(iou, time),
+        DataType::M_IT_NA_1 => iou_add_time_tag::<M_IT_NA_1>(iou, time),
+        DataType::C_SC_NA_1 => iou_add_time_tag::<C_SC_NA_1>(iou, time),
+        DataType::C_DC_NA_1 => iou_add_time_tag::<C_DC_NA_1>(iou, time),
+        DataType::C_RC_NA_1 => iou_add_time_tag::<C_RC_NA_1>(iou, time),
+        DataType::C_SE_NA_1 => iou_add_time_tag::<C_SE_NA_1>(iou, time),
+        DataType::C_SE_NB_1 => iou_add_time_tag::<C_SE_NB_1>(iou, time),
+        DataType::C_SE_NC_1 => iou_add_time_tag::<C_SE_NC_1>(iou, time),
+        DataType::C_BO_NA_1 => iou_add_time_tag::<C_BO_NA_1>(iou, time),
+        _ => return data_type,
+    }
+    data_type.timed().unwrap()
+}
+
+/// Removes the time tags of information objects, returns the new data type (unchanged if the
+/// type has no untimed equivalent, e.g. protection events)
+pub(crate) fn strip_time_tags(data_type: DataType, iou: &mut [Iou]) -> DataType {
+    match data_type {
+        DataType::M_SP_TA_1 => iou_strip_time_tag::<M_SP_TA_1>(iou),
+        DataType::M_SP_TB_1 => iou_strip_time_tag::<M_SP_TB_1>(iou),
+        DataType::M_DP_TA_1 => iou_strip_time_tag::<M_DP_TA_1>(iou),
+        DataType::M_DP_TB_1 => iou_strip_time_tag::<M_DP_TB_1>(iou),
+        DataType::M_ST_TA_1 => iou_strip_time_tag::<M_ST_TA_1>(iou),
+        DataType::M_ST_TB_1 => iou_strip_time_tag::<M_ST_TB_1>(iou),
+        DataType::M_BO_TA_1 => iou_strip_time_tag::<M_BO_TA_1>(iou),
+        DataType::M_BO_TB_1 => iou_strip_time_tag::<M_BO_TB_1>(iou),
+        DataType::M_ME_TA_1 => iou_strip_time_tag::<M_ME_TA_1>(iou),
+        DataType::M_ME_TD_1 => iou_strip_time_tag::<M_ME_TD_1>(iou),
+        DataType::M_ME_TB_1 => iou_strip_time_tag::<M_ME_TB_1>(iou),
+        DataType::M_ME_TE_1 => iou_strip_time_tag::<M_ME_TE_1>(iou),
+        DataType::M_ME_TC_1 => iou_strip_time_tag::<M_ME_TC_1>(iou),
+        DataType::M_ME_TF_1 => iou_strip_time_tag::<M_ME_TF_1>(iou),
+        DataType::M_IT_TA_1 => iou_strip_time_tag::<M_IT_TA_1>(iou),
+        DataType::M_IT_TB_1 => iou_strip_time_tag::<M_IT_TB_1>(iou),
+        DataType::C_SC_TA_1 => iou_strip_time_tag::<C_SC_TA_1>(iou),
+        DataType::C_DC_TA_1 => iou_strip_time_tag::<C_DC_TA_1>(iou),
+        DataType::C_RC_TA_1 => iou_strip_time_tag::<C_RC_TA_1>(iou),
+        DataType::C_SE_TA_1 => iou_strip_time_tag::<C_SE_TA_1>(iou),
+        DataType::C_SE_TB_1 => iou_strip_time_tag::<C_SE_TB_1>(iou),
+        DataType::C_SE_TC_1 => iou_strip_time_tag::<C_SE_TC_1>(iou),
+        DataType::C_BO_TA_1 => iou_strip_time_tag::<C_BO_TB_1>(iou),
+        _ => return data_type,
+    }
+    data_type.untimed().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{
+        AddTimeTag, BCR, C_BO_NA_1, DataType, IntoCP56TimeTag, M_EP_TA_1, M_EP_TB_1, M_IT_TA_1,
+        M_ST_NA_1, M_ST_TA_1, QDS, SEP, SeqQD, StripTimeTag, VTI, add_time_tags,
+        convert_cp24_time_tags, strip_time_tags,
+    };
+    use crate::types::{
+        DataBuffer, Iou,
+        time::{CP16Time2a, CP24Time2a, CP56Time2a, TimePolicy},
+    };
+
+    fn reference() -> CP56Time2a {
+        CP56Time2a {
+            ms: 500,
+            iv: false,
+            min: 0,
+            su: true,
+            hour: 10,
+            dow: 1.into(),
+            day: 1,
+            month: 7,
+            year: 24,
+        }
+    }
+
+    #[test]
+    fn add_strip_time_tag() {
+        let time = reference();
+        let st = M_ST_NA_1 {
+            vti: VTI {
+                value: -5,
+                transient: true,
+                qds: QDS::default(),
+            },
+        };
+        let timed = st.clone().add_time_tag(time.clone());
+        assert_eq!((&timed.vti, &timed.time), (&st.vti, &time));
+        assert_eq!(timed.strip_time_tag(), st);
+        let it = M_IT_TA_1 {
+            bcr: BCR { value: 9 },
+            ..M_IT_TA_1::default()
+        };
+        assert_eq!(it.strip_time_tag().bcr.value, 9);
+        let command = C_BO_NA_1::default().add_time_tag(time.clone());
+        assert_eq!(command.strip_time_tag(), C_BO_NA_1::default());
+        // protection events have no untimed types
+        let mut iou = vec![Iou::new(
+            1,
+            M_EP_TA_1 {
+                sep: SEP {
+                    ei: true,
+                    ..SEP::default()
+                },
+                ..M_EP_TA_1::default()
+            },
+        )];
+        assert_eq!(
+            strip_time_tags(DataType::M_EP_TA_1, &mut iou),
+            DataType::M_EP_TA_1
+        );
+        // CP24Time2a time tags are not replaced, see convert_cp24_time_tags
+        assert_eq!(
+            add_time_tags(DataType::M_EP_TA_1, &mut iou, &time),
+            DataType::M_EP_TA_1
+        );
+        assert!(M_EP_TA_1::from(iou[0].value).sep.ei);
+        for data_type in [
+            DataType::M_SP_NA_1,
+            DataType::C_RC_NA_1,
+            DataType::M_ME_TB_1,
+        ] {
+            let timed = data_type.timed().unwrap();
+            assert_eq!(timed.untimed(), data_type.untimed().or(Some(data_type)));
+        }
+        assert_eq!(DataType::C_IC_NA_1.timed(), None);
+        assert_eq!(DataType::M_EP_TD_1.untimed(), None);
+    }
+
+    #[test]
+    fn vti_signed() {
+        let vti = VTI::from([0b0111_1111, 0]);
+        assert_eq!((vti.value, vti.transient), (-1, false));
+        let vti = VTI::from([0b1100_0000, 0b1000_0000]);
+        assert_eq!((vti.value, vti.transient, vti.qds.iv), (-64, true, true));
+        let vti = VTI::from([0b0011_1111, 0b1000_0000]);
+        assert_eq!((vti.value, vti.transient), (63, false));
+        for value in -64..=63 {
+            for transient in [false, true] {
+                let vti = VTI {
+                    value,
+                    transient,
+                    qds: QDS::default(),
+                };
+                assert_eq!(VTI::from(<[u8; 2]>::from(vti.clone())), vti);
+            }
+        }
+        let buf = DataBuffer::from(M_ST_NA_1 {
+            vti: VTI {
+                value: -3,
+                ..VTI::default()
+            },
+        });
+        assert_eq!(buf[0], 0b0111_1101);
+    }
+
+    #[test]
+    fn cp24_into_cp56_time_tag() {
+        let time = CP24Time2a {
+            ms: 59_000,
+            min: 59,
+            iv: false,
+        };
+        let st = M_ST_TA_1 {
+            vti: VTI {
+                value: 5,
+                transient: true,
+                qds: QDS {
+                    iv: true,
+                    ..QDS::default()
+                },
+            },
+            time,
+        }
+        .into_cp56_time_tag(&reference())
+        .unwrap();
+        assert_eq!(DataBuffer::from(st.clone())[..2], [0x85, 0x80]);
+        assert!(st.vti.qds.iv);
+        assert_eq!((st.time.hour, st.time.min, st.time.ms), (9, 59, 59_000));
+        assert!(st.time.su);
+        let it = M_IT_TA_1 {
+            bcr: BCR { value: 7 },
+            seq_qd: SeqQD {
+                seq: 3,
+                ..SeqQD::default()
+            },
+            time,
+        }
+        .into_cp56_time_tag(&reference())
+        .unwrap();
+        assert_eq!(it.bcr.value, 7);
+        assert_eq!(it.seq_qd.seq, 3);
+        assert_eq!(it.time.day, 1);
+    }
+
+    #[test]
+    fn cp24_time_tags_data_type() {
+        assert_eq!(
+            DataType::M_ME_TB_1.cp56_equivalent(),
+            Some(DataType::M_ME_TE_1)
+        );
+        assert_eq!(DataType::M_SP_TB_1.cp56_equivalent(), None);
+        let mut iou = [Iou::new(
+            1,
+            DataBuffer::from(M_ST_TA_1 {
+                vti: VTI::default(),
+                time: CP24Time2a {
+                    ms: 0,
+                    min: 1,
+                    iv: false,
+                },
+            }),
+        )];
+        let data_type =
+            convert_cp24_time_tags(DataType::M_ST_TA_1, &mut iou, &reference()).unwrap();
+        assert_eq!(data_type, DataType::M_ST_TB_1);
+        let data_type =
+            convert_cp24_time_tags(DataType::M_ST_TB_1, &mut iou, &reference()).unwrap();
+        assert_eq!(data_type, DataType::M_ST_TB_1);
+    }
+
+    #[test]
+    fn protection_event_resolve() {
+        let event = M_EP_TB_1 {
+            relay_duration: CP16Time2a { ms: 3_000 },
+            time: CP24Time2a {
+                ms: 58_000,
+                min: 59,
+                iv: false,
+            },
+            ..M_EP_TB_1::default()
+        };
+        let (start, end) = event.resolve(reference(), &TimePolicy::Utc).unwrap();
+        let expected = CP56Time2a {
+            ms: 58_000,
+            min: 59,
+            hour: 9,
+            ..reference()
+        }
+        .to_timestamp(&TimePolicy::Utc)
+        .unwrap();
+        assert_eq!(start, expected);
+        assert_eq!(end, expected + Duration::from_secs(3));
+    }
+}
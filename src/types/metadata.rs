@@ -0,0 +1,319 @@
+use crate::Error;
+
+use super::{COT, datatype::DataType};
+
+/// Direction of information transfer
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Direction {
+    /// Monitor direction (from the controlled station)
+    Monitor,
+    /// Control direction (to the controlled station, confirmed in the monitor direction)
+    Control,
+}
+
+/// Category of process, system and parameter information
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Category {
+    /// Process information
+    Process,
+    /// System information (interrogation, clock synchronization, end of initialization etc.)
+    System,
+    /// Parameters
+    Parameter,
+}
+
+/// Kind of the time tag of a data type
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TimeTagKind {
+    /// No time tag
+    None,
+    /// CP24Time2a time tag
+    Cp24,
+    /// CP56Time2a time tag
+    Cp56,
+}
+
+/// Set of causes of transmission
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub struct CotSet(u64);
+
+impl CotSet {
+    /// Empty set
+    pub const EMPTY: Self = Self(0);
+
+    const fn of(cots: &[COT]) -> Self {
+        let mut mask = 0;
+        let mut i = 0;
+        while i < cots.len() {
+            mask |= 1 << cots[i] as u8;
+            i += 1;
+        }
+        Self(mask)
+    }
+    const fn range(from: COT, to: COT) -> Self {
+        let mut mask = 0;
+        let mut cot = from as u8;
+        while cot <= to as u8 {
+            mask |= 1 << cot;
+            cot += 1;
+        }
+        Self(mask)
+    }
+    const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+    /// Is the cause of transmission in the set
+    pub fn contains(self, cot: COT) -> bool {
+        self.0 & (1 << cot as u8) != 0
+    }
+    /// Is the set empty
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+    /// Iterate over the causes of transmission in the set
+    pub fn iter(self) -> impl Iterator<Item = COT> {
+        (0..64)
+            .filter(move |n| self.0 & (1 << n) != 0)
+            .filter_map(|n| COT::try_from(n).ok())
+    }
+}
+
+const INTERROGATED: CotSet = CotSet::range(COT::Inrogen, COT::Inro16);
+const COUNTER_INTERROGATED: CotSet = CotSet::range(COT::ReqCoGen, COT::ReqCo4);
+const UNKNOWN: CotSet = CotSet::range(COT::UnknownType, COT::UnknownObjectAddress);
+const STATUS: CotSet = CotSet::of(&[
+    COT::Background,
+    COT::Spontan,
+    COT::Req,
+    COT::RetRem,
+    COT::RetLoc,
+])
+.union(INTERROGATED);
+const STATUS_TIMED: CotSet = CotSet::of(&[COT::Spontan, COT::Req, COT::RetRem, COT::RetLoc]);
+const BITSTRING: CotSet =
+    CotSet::of(&[COT::Background, COT::Spontan, COT::Req]).union(INTERROGATED);
+const MEASURED: CotSet =
+    CotSet::of(&[COT::Cyclic, COT::Background, COT::Spontan, COT::Req]).union(INTERROGATED);
+const TIMED: CotSet = CotSet::of(&[COT::Spontan, COT::Req]);
+const TOTALS: CotSet = CotSet::of(&[COT::Spontan]).union(COUNTER_INTERROGATED);
+const PROTECTION: CotSet = CotSet::of(&[COT::Spontan]);
+const INIT: CotSet = CotSet::of(&[COT::Init]);
+const COMMAND: CotSet = CotSet::of(&[
+    COT::Act,
+    COT::ActCon,
+    COT::Deact,
+    COT::DeactCon,
+    COT::ActTerm,
+])
+.union(UNKNOWN);
+const COUNTER_INTERROGATION: CotSet =
+    CotSet::of(&[COT::Act, COT::ActCon, COT::ActTerm]).union(UNKNOWN);
+const READ: CotSet = CotSet::of(&[COT::Req]).union(UNKNOWN);
+const ACTIVATION: CotSet = CotSet::of(&[COT::Act, COT::ActCon]).union(UNKNOWN);
+const SPONTANEOUS_ACTIVATION: CotSet =
+    CotSet::of(&[COT::Spontan, COT::Act, COT::ActCon]).union(UNKNOWN);
+const PARAMETER: CotSet = CotSet::of(&[COT::Act, COT::ActCon])
+    .union(INTERROGATED)
+    .union(UNKNOWN);
+const PARAMETER_ACTIVATION: CotSet =
+    CotSet::of(&[COT::Act, COT::ActCon, COT::Deact, COT::DeactCon]).union(UNKNOWN);
+
+/// Metadata of a data type
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct DataTypeInfo {
+    data_type: DataType,
+    direction: Direction,
+    category: Category,
+    time_tag: TimeTagKind,
+    cots: CotSet,
+}
+
+impl DataTypeInfo {
+    /// Data type
+    pub fn data_type(&self) -> DataType {
+        self.data_type
+    }
+    /// Direction of information transfer
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+    /// Category
+    pub fn category(&self) -> Category {
+        self.category
+    }
+    /// Kind of the time tag
+    pub fn time_tag(&self) -> TimeTagKind {
+        self.time_tag
+    }
+    /// The equivalent type without time tag (see [`DataType::untimed`])
+    pub fn untimed(&self) -> Option<DataType> {
+        self.data_type.untimed()
+    }
+    /// The equivalent type with a CP56Time2a time tag (see [`DataType::timed`])
+    pub fn timed(&self) -> Option<DataType> {
+        self.data_type.timed()
+    }
+    /// Causes of transmission allowed by IEC 60870-5-101/104, including confirmations and
+    /// errors of the controlled station
+    pub fn allowed_cots(&self) -> CotSet {
+        self.cots
+    }
+}
+
+impl DataType {
+    /// Metadata of the data type, `None` for the undefined type
+    pub fn info(self) -> Option<DataTypeInfo> {
+        use Category::{Parameter, Process, System};
+        use Direction::{Control, Monitor};
+        use TimeTagKind::{Cp24, Cp56};
+
+        let (direction, category, time_tag, cots) = match self {
+            DataType::ASDU_TYPEUNDEF => return None,
+            DataType::M_SP_NA_1 | DataType::M_DP_NA_1 | DataType::M_ST_NA_1 => {
+                (Monitor, Process, TimeTagKind::None, STATUS)
+            }
+            DataType::M_SP_TA_1 | DataType::M_DP_TA_1 | DataType::M_ST_TA_1 => {
+                (Monitor, Process, Cp24, STATUS_TIMED)
+            }
+            DataType::M_SP_TB_1 | DataType::M_DP_TB_1 | DataType::M_ST_TB_1 => {
+                (Monitor, Process, Cp56, STATUS_TIMED)
+            }
+            DataType::M_BO_NA_1 | DataType::M_PS_NA_1 => {
+                (Monitor, Process, TimeTagKind::None, BITSTRING)
+            }
+            DataType::M_ME_NA_1
+            | DataType::M_ME_NB_1
+            | DataType::M_ME_NC_1
+            | DataType::M_ME_ND_1 => (Monitor, Process, TimeTagKind::None, MEASURED),
+            DataType::M_BO_TA_1
+            | DataType::M_ME_TA_1
+            | DataType::M_ME_TB_1
+            | DataType::M_ME_TC_1 => (Monitor, Process, Cp24, TIMED),
+            DataType::M_BO_TB_1
+            | DataType::M_ME_TD_1
+            | DataType::M_ME_TE_1
+            | DataType::M_ME_TF_1 => (Monitor, Process, Cp56, TIMED),
+            DataType::M_IT_NA_1 => (Monitor, Process, TimeTagKind::None, TOTALS),
+            DataType::M_IT_TA_1 => (Monitor, Process, Cp24, TOTALS),
+            DataType::M_IT_TB_1 => (Monitor, Process, Cp56, TOTALS),
+            DataType::M_EP_TA_1 | DataType::M_EP_TB_1 | DataType::M_EP_TC_1 => {
+                (Monitor, Process, Cp24, PROTECTION)
+            }
+            DataType::M_EP_TD_1 | DataType::M_EP_TE_1 | DataType::M_EP_TF_1 => {
+                (Monitor, Process, Cp56, PROTECTION)
+            }
+            DataType::C_SC_NA_1
+            | DataType::C_DC_NA_1
+            | DataType::C_RC_NA_1
+            | DataType::C_SE_NA_1
+            | DataType::C_SE_NB_1
+            | DataType::C_SE_NC_1
+            | DataType::C_BO_NA_1 => (Control, Process, TimeTagKind::None, COMMAND),
+            DataType::C_SC_TA_1
+            | DataType::C_DC_TA_1
+            | DataType::C_RC_TA_1
+            | DataType::C_SE_TA_1
+            | DataType::C_SE_TB_1
+            | DataType::C_SE_TC_1
+            | DataType::C_BO_TA_1 => (Control, Process, Cp56, COMMAND),
+            DataType::M_EI_NA_1 => (Monitor, System, TimeTagKind::None, INIT),
+            DataType::C_IC_NA_1 => (Control, System, TimeTagKind::None, COMMAND),
+            DataType::C_CI_NA_1 => (Control, System, TimeTagKind::None, COUNTER_INTERROGATION),
+            DataType::C_RD_NA_1 => (Control, System, TimeTagKind::None, READ),
+            DataType::C_CS_NA_1 => (Control, System, Cp56, SPONTANEOUS_ACTIVATION),
+            DataType::C_TS_NA_1 | DataType::C_RP_NA_1 => {
+                (Control, System, TimeTagKind::None, ACTIVATION)
+            }
+            DataType::C_CD_NA_1 => (Control, System, TimeTagKind::None, SPONTANEOUS_ACTIVATION),
+            DataType::C_TS_TA_1 => (Control, System, Cp56, ACTIVATION),
+            DataType::P_ME_NA_1 | DataType::P_ME_NB_1 | DataType::P_ME_NC_1 => {
+                (Control, Parameter, TimeTagKind::None, PARAMETER)
+            }
+            DataType::P_AC_NA_1 => (Control, Parameter, TimeTagKind::None, PARAMETER_ACTIVATION),
+        };
+        Some(DataTypeInfo {
+            data_type: self,
+            direction,
+            category,
+            time_tag,
+            cots,
+        })
+    }
+    /// Is the cause of transmission allowed for the data type
+    pub fn allows_cot(self, cot: COT) -> bool {
+        self.info()
+            .is_some_and(|info| info.allowed_cots().contains(cot))
+    }
+    /// Validate the cause of transmission of a telegram
+    pub(crate) fn validate_cot(self, cot: COT) -> Result<(), Error> {
+        if self.allows_cot(cot) {
+            Ok(())
+        } else {
+            Err(Error::invalid_data(format!(
+                "cause of transmission {:?} is not allowed for {:?}",
+                cot, self
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Category, Direction, TimeTagKind};
+    use crate::types::{COT, datatype::DataType};
+
+    #[test]
+    fn data_type_info() {
+        let info = DataType::M_ME_TF_1.info().unwrap();
+        assert_eq!(
+            (info.direction(), info.category(), info.time_tag()),
+            (Direction::Monitor, Category::Process, TimeTagKind::Cp56)
+        );
+        assert_eq!(info.untimed(), Some(DataType::M_ME_NC_1));
+        assert_eq!(info.timed(), None);
+        assert_eq!(
+            DataType::M_ME_NC_1.info().unwrap().timed(),
+            Some(DataType::M_ME_TF_1)
+        );
+        assert_eq!(
+            DataType::M_SP_TA_1.info().unwrap().time_tag(),
+            TimeTagKind::Cp24
+        );
+        let info = DataType::C_CS_NA_1.info().unwrap();
+        assert_eq!(
+            (info.direction(), info.category()),
+            (Direction::Control, Category::System)
+        );
+        assert_eq!(
+            DataType::P_AC_NA_1.info().unwrap().category(),
+            Category::Parameter
+        );
+        assert!(DataType::ASDU_TYPEUNDEF.info().is_none());
+        assert_eq!(
+            DataType::M_EI_NA_1
+                .info()
+                .unwrap()
+                .allowed_cots()
+                .iter()
+                .collect::<Vec<_>>(),
+            [COT::Init]
+        );
+    }
+
+    #[test]
+    fn allowed_cots() {
+        assert!(DataType::C_SC_NA_1.allows_cot(COT::Act));
+        assert!(DataType::C_SC_NA_1.allows_cot(COT::ActTerm));
+        assert!(DataType::C_SC_NA_1.allows_cot(COT::UnknownObjectAddress));
+        assert!(!DataType::C_SC_NA_1.allows_cot(COT::Cyclic));
+        assert!(DataType::M_SP_NA_1.allows_cot(COT::Inro16));
+        assert!(!DataType::M_SP_NA_1.allows_cot(COT::Cyclic));
+        assert!(!DataType::M_SP_TB_1.allows_cot(COT::Inrogen));
+        assert!(DataType::M_ME_NC_1.allows_cot(COT::Cyclic));
+        assert!(DataType::M_IT_NA_1.allows_cot(COT::ReqCo4));
+        assert!(!DataType::M_IT_NA_1.allows_cot(COT::Inrogen));
+        assert!(DataType::C_CD_NA_1.allows_cot(COT::Spontan));
+        assert!(!DataType::ASDU_TYPEUNDEF.allows_cot(COT::Spontan));
+        assert!(DataType::C_SC_NA_1.validate_cot(COT::Cyclic).is_err());
+    }
+}
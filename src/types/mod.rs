@@ -1,6 +1,12 @@
 mod cot;
 /// IEC 60870-5 101/104 common data types
 pub mod datatype;
+/// Data type metadata: direction, category, time tag and allowed causes of transmission
+pub mod metadata;
+/// Unified point values of monitor direction information objects
+pub mod point;
+/// Engineering unit scaling of normalized and scaled values
+pub mod scaling;
 /// IEC 60870-5 101/104 time types
 pub mod time;
 
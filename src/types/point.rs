@@ -0,0 +1,725 @@
+use super::{
+    DataBuffer,
+    datatype::{
+        BCR, C_BO_TB_1, C_DC_TA_1, C_RC_TA_1, C_SC_TA_1, C_SE_TA_1, C_SE_TB_1, C_SE_TC_1,
+        C_TS_TA_1, DIQ, DPI, DataType, EventState, M_BO_NA_1, M_BO_TA_1, M_BO_TB_1, M_DP_NA_1,
+        M_DP_TA_1, M_DP_TB_1, M_EP_TA_1, M_EP_TB_1, M_EP_TC_1, M_EP_TD_1, M_EP_TE_1, M_EP_TF_1,
+        M_IT_NA_1, M_IT_TA_1, M_IT_TB_1, M_ME_NA_1, M_ME_NB_1, M_ME_NC_1, M_ME_ND_1, M_ME_TA_1,
+        M_ME_TB_1, M_ME_TC_1, M_ME_TD_1, M_ME_TE_1, M_ME_TF_1, M_PS_NA_1, M_SP_NA_1, M_SP_TA_1,
+        M_SP_TB_1, M_ST_NA_1, M_ST_TA_1, M_ST_TB_1, NVA, OCI, QDP, QDS, SCD, SEP, SIQ, SVA, SeqQD,
+        StartEP, VTI,
+    },
+    time::{CP16Time2a, CP24Time2a, CP56Time2a, TimeTag},
+};
+
+/// Value of a monitor direction information object, independent of the type identification
+#[derive(Debug, Clone, PartialEq)]
+pub enum PointValue {
+    /// Single point (M_SP_*)
+    Single(bool),
+    /// Double point (M_DP_*)
+    Double(DPI),
+    /// Step position and transient state (M_ST_*)
+    Step(i8, bool),
+    /// Bit string of 32 bits (M_BO_*)
+    Bitstring(u32),
+    /// Measured value, normalized (M_ME_NA_1, M_ME_TA_1, M_ME_TD_1, M_ME_ND_1)
+    Normalized(NVA),
+    /// Measured value, scaled (M_ME_NB_1, M_ME_TB_1, M_ME_TE_1)
+    Scaled(SVA),
+    /// Measured value, short floating point (M_ME_NC_1, M_ME_TC_1, M_ME_TF_1)
+    Float(f32),
+    /// Integrated total and its sequence number (M_IT_*)
+    Counter(BCR, u8),
+    /// Single event of protection equipment and the elapsed time (M_EP_TA_1, M_EP_TD_1)
+    ProtectionEvent(EventState, CP16Time2a),
+    /// Packed start events of protection equipment and the relay duration time (M_EP_TB_1,
+    /// M_EP_TE_1)
+    ProtectionStart(StartEP, CP16Time2a),
+    /// Packed output circuit information of protection equipment and the relay operation time
+    /// (M_EP_TC_1, M_EP_TF_1)
+    ProtectionCircuit(OCI, CP16Time2a),
+    /// Packed single point information with status change detection (M_PS_NA_1)
+    StatusChange(SCD),
+}
+
+impl PointValue {
+    /// Value as a number, for measured values only (normalized values as fractions)
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            PointValue::Normalized(nva) => Some(nva.to_f32()),
+            PointValue::Scaled(sva) => Some(sva.to_f32()),
+            PointValue::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+/// Quality of an information object, the union of the quality descriptors of all monitor types
+/// (flags a type does not have are always unset)
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub struct Quality {
+    /// Invalid
+    pub iv: bool,
+    /// Not topical
+    pub nt: bool,
+    /// Substituted
+    pub sb: bool,
+    /// Blocked
+    pub bl: bool,
+    /// Overflow (QDS)
+    pub ov: bool,
+    /// Elapsed time invalid (SEP, QDP)
+    pub ei: bool,
+    /// Counter adjusted (SeqQD)
+    pub ca: bool,
+    /// Counter carry (SeqQD)
+    pub cy: bool,
+}
+
+impl Quality {
+    /// Is the value good (no invalid, not topical, substituted or blocked flag set)
+    pub fn is_good(&self) -> bool {
+        !(self.iv || self.nt || self.sb || self.bl)
+    }
+}
+
+impl From<&SIQ> for Quality {
+    fn from(q: &SIQ) -> Self {
+        Quality {
+            iv: q.iv,
+            nt: q.nt,
+            sb: q.sb,
+            bl: q.bl,
+            ..Quality::default()
+        }
+    }
+}
+
+impl From<&DIQ> for Quality {
+    fn from(q: &DIQ) -> Self {
+        Quality {
+            iv: q.iv,
+            nt: q.nt,
+            sb: q.sb,
+            bl: q.bl,
+            ..Quality::default()
+        }
+    }
+}
+
+impl From<&QDS> for Quality {
+    fn from(q: &QDS) -> Self {
+        Quality {
+            iv: q.iv,
+            nt: q.nt,
+            sb: q.sb,
+            bl: q.bl,
+            ov: q.ov,
+            ..Quality::default()
+        }
+    }
+}
+
+impl From<&SEP> for Quality {
+    fn from(q: &SEP) -> Self {
+        Quality {
+            iv: q.iv,
+            nt: q.nt,
+            sb: q.sb,
+            bl: q.bl,
+            ei: q.ei,
+            ..Quality::default()
+        }
+    }
+}
+
+impl From<&QDP> for Quality {
+    fn from(q: &QDP) -> Self {
+        Quality {
+            iv: q.iv,
+            nt: q.nt,
+            sb: q.sb,
+            bl: q.bl,
+            ei: q.ei,
+            ..Quality::default()
+        }
+    }
+}
+
+impl From<&SeqQD> for Quality {
+    fn from(q: &SeqQD) -> Self {
+        Quality {
+            iv: q.iv,
+            ca: q.ca,
+            cy: q.cy,
+            ..Quality::default()
+        }
+    }
+}
+
+/// Information elements with a quality descriptor
+pub trait HasQuality {
+    /// Quality
+    fn quality(&self) -> Quality;
+    /// Set the quality, the flags the descriptor does not have are ignored
+    fn set_quality(&mut self, quality: Quality);
+}
+
+impl HasQuality for SIQ {
+    fn quality(&self) -> Quality {
+        self.into()
+    }
+    fn set_quality(&mut self, quality: Quality) {
+        (self.iv, self.nt, self.sb, self.bl) = (quality.iv, quality.nt, quality.sb, quality.bl);
+    }
+}
+
+impl HasQuality for DIQ {
+    fn quality(&self) -> Quality {
+        self.into()
+    }
+    fn set_quality(&mut self, quality: Quality) {
+        (self.iv, self.nt, self.sb, self.bl) = (quality.iv, quality.nt, quality.sb, quality.bl);
+    }
+}
+
+impl HasQuality for QDS {
+    fn quality(&self) -> Quality {
+        self.into()
+    }
+    fn set_quality(&mut self, quality: Quality) {
+        (self.iv, self.nt, self.sb, self.bl) = (quality.iv, quality.nt, quality.sb, quality.bl);
+        self.ov = quality.ov;
+    }
+}
+
+impl HasQuality for VTI {
+    fn quality(&self) -> Quality {
+        self.qds.quality()
+    }
+    fn set_quality(&mut self, quality: Quality) {
+        self.qds.set_quality(quality);
+    }
+}
+
+impl HasQuality for SEP {
+    fn quality(&self) -> Quality {
+        self.into()
+    }
+    fn set_quality(&mut self, quality: Quality) {
+        (self.iv, self.nt, self.sb, self.bl) = (quality.iv, quality.nt, quality.sb, quality.bl);
+        self.ei = quality.ei;
+    }
+}
+
+impl HasQuality for QDP {
+    fn quality(&self) -> Quality {
+        self.into()
+    }
+    fn set_quality(&mut self, quality: Quality) {
+        (self.iv, self.nt, self.sb, self.bl) = (quality.iv, quality.nt, quality.sb, quality.bl);
+        self.ei = quality.ei;
+    }
+}
+
+impl HasQuality for SeqQD {
+    fn quality(&self) -> Quality {
+        self.into()
+    }
+    fn set_quality(&mut self, quality: Quality) {
+        (self.iv, self.ca, self.cy) = (quality.iv, quality.ca, quality.cy);
+    }
+}
+
+macro_rules! impl_has_quality {
+    ($field: ident, $($t: ty),+) => {
+        $(
+            impl HasQuality for $t {
+                fn quality(&self) -> Quality {
+                    self.$field.quality()
+                }
+                fn set_quality(&mut self, quality: Quality) {
+                    self.$field.set_quality(quality);
+                }
+            }
+        )+
+    };
+}
+
+impl_has_quality!(siq, M_SP_NA_1, M_SP_TA_1, M_SP_TB_1);
+impl_has_quality!(diq, M_DP_NA_1, M_DP_TA_1, M_DP_TB_1);
+impl_has_quality!(vti, M_ST_NA_1, M_ST_TA_1, M_ST_TB_1);
+impl_has_quality!(
+    qds, M_BO_NA_1, M_BO_TA_1, M_BO_TB_1, M_ME_NA_1, M_ME_TA_1, M_ME_TD_1, M_ME_NB_1, M_ME_TB_1,
+    M_ME_TE_1, M_ME_NC_1, M_ME_TC_1, M_ME_TF_1, M_PS_NA_1
+);
+impl_has_quality!(seq_qd, M_IT_NA_1, M_IT_TA_1, M_IT_TB_1);
+impl_has_quality!(sep, M_EP_TA_1, M_EP_TD_1);
+impl_has_quality!(qdp, M_EP_TB_1, M_EP_TC_1, M_EP_TE_1, M_EP_TF_1);
+
+/// Information elements with a time tag
+pub trait HasTimeTag {
+    /// Time tag type (CP24Time2a or CP56Time2a)
+    type Time: Clone + Into<TimeTag>;
+    /// Time tag
+    fn time(&self) -> &Self::Time;
+    /// Set the time tag
+    fn set_time(&mut self, time: Self::Time);
+    /// Time tag, independent of the type
+    fn time_tag(&self) -> TimeTag {
+        self.time().clone().into()
+    }
+}
+
+macro_rules! impl_has_time_tag {
+    ($time: ty, $($t: ty),+) => {
+        $(
+            impl HasTimeTag for $t {
+                type Time = $time;
+
+                fn time(&self) -> &$time {
+                    &self.time
+                }
+                fn set_time(&mut self, time: $time) {
+                    self.time = time;
+                }
+            }
+        )+
+    };
+}
+
+impl_has_time_tag!(
+    CP24Time2a, M_SP_TA_1, M_DP_TA_1, M_ST_TA_1, M_BO_TA_1, M_ME_TA_1, M_ME_TB_1, M_ME_TC_1,
+    M_IT_TA_1, M_EP_TA_1, M_EP_TB_1, M_EP_TC_1
+);
+impl_has_time_tag!(
+    CP56Time2a, M_SP_TB_1, M_DP_TB_1, M_ST_TB_1, M_BO_TB_1, M_ME_TD_1, M_ME_TE_1, M_ME_TF_1,
+    M_IT_TB_1, M_EP_TD_1, M_EP_TE_1, M_EP_TF_1, C_SC_TA_1, C_DC_TA_1, C_RC_TA_1, C_SE_TA_1,
+    C_SE_TB_1, C_SE_TC_1, C_BO_TB_1, C_TS_TA_1
+);
+
+/// Value, quality and time tag of a monitor direction information object
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointData {
+    /// Value
+    pub value: PointValue,
+    /// Quality
+    pub quality: Quality,
+    /// Time tag, if the type has one
+    pub time: Option<TimeTag>,
+}
+
+impl PointData {
+    /// Decode a monitor direction information object, `None` if the data type is not a process
+    /// information one
+    pub fn decode(data_type: DataType, value: DataBuffer) -> Option<Self> {
+        Some(match data_type {
+            DataType::M_SP_NA_1 => M_SP_NA_1::from(value).into(),
+            DataType::M_SP_TA_1 => M_SP_TA_1::from(value).into(),
+            DataType::M_SP_TB_1 => M_SP_TB_1::from(value).into(),
+            DataType::M_DP_NA_1 => M_DP_NA_1::from(value).into(),
+            DataType::M_DP_TA_1 => M_DP_TA_1::from(value).into(),
+            DataType::M_DP_TB_1 => M_DP_TB_1::from(value).into(),
+            DataType::M_ST_NA_1 => M_ST_NA_1::from(value).into(),
+            DataType::M_ST_TA_1 => M_ST_TA_1::from(value).into(),
+            DataType::M_ST_TB_1 => M_ST_TB_1::from(value).into(),
+            DataType::M_BO_NA_1 => M_BO_NA_1::from(value).into(),
+            DataType::M_BO_TA_1 => M_BO_TA_1::from(value).into(),
+            DataType::M_BO_TB_1 => M_BO_TB_1::from(value).into(),
+            DataType::M_ME_NA_1 => M_ME_NA_1::from(value).into(),
+            DataType::M_ME_TA_1 => M_ME_TA_1::from(value).into(),
+            DataType::M_ME_TD_1 => M_ME_TD_1::from(value).into(),
+            DataType::M_ME_ND_1 => M_ME_ND_1::from(value).into(),
+            DataType::M_ME_NB_1 => M_ME_NB_1::from(value).into(),
+            DataType::M_ME_TB_1 => M_ME_TB_1::from(value).into(),
+            DataType::M_ME_TE_1 => M_ME_TE_1::from(value).into(),
+            DataType::M_ME_NC_1 => M_ME_NC_1::from(value).into(),
+            DataType::M_ME_TC_1 => M_ME_TC_1::from(value).into(),
+            DataType::M_ME_TF_1 => M_ME_TF_1::from(value).into(),
+            DataType::M_IT_NA_1 => M_IT_NA_1::from(value).into(),
+            DataType::M_IT_TA_1 => M_IT_TA_1::from(value).into(),
+            DataType::M_IT_TB_1 => M_IT_TB_1::from(value).into(),
+            DataType::M_EP_TA_1 => M_EP_TA_1::from(value).into(),
+            DataType::M_EP_TB_1 => M_EP_TB_1::from(value).into(),
+            DataType::M_EP_TC_1 => M_EP_TC_1::from(value).into(),
+            DataType::M_EP_TD_1 => M_EP_TD_1::from(value).into(),
+            DataType::M_EP_TE_1 => M_EP_TE_1::from(value).into(),
+            DataType::M_EP_TF_1 => M_EP_TF_1::from(value).into(),
+            DataType::M_PS_NA_1 => M_PS_NA_1::from(value).into(),
+            _ => return None,
+        })
+    }
+}
+
+macro_rules! impl_point_data {
+    ($t: ty, |$d: ident| $value: expr, $quality: expr, $time: expr) => {
+        impl From<$t> for PointData {
+            fn from($d: $t) -> Self {
+                let quality = $quality;
+                let time = $time;
+                PointData {
+                    value: $value,
+                    quality,
+                    time,
+                }
+            }
+        }
+    };
+}
+
+impl_point_data!(
+    M_SP_NA_1,
+    |d| PointValue::Single(d.siq.spi.into()),
+    d.quality(),
+    None
+);
+impl_point_data!(
+    M_SP_TA_1,
+    |d| PointValue::Single(d.siq.spi.into()),
+    d.quality(),
+    Some(d.time_tag())
+);
+impl_point_data!(
+    M_SP_TB_1,
+    |d| PointValue::Single(d.siq.spi.into()),
+    d.quality(),
+    Some(d.time_tag())
+);
+impl_point_data!(
+    M_DP_NA_1,
+    |d| PointValue::Double(d.diq.dpi),
+    d.quality(),
+    None
+);
+impl_point_data!(
+    M_DP_TA_1,
+    |d| PointValue::Double(d.diq.dpi),
+    d.quality(),
+    Some(d.time_tag())
+);
+impl_point_data!(
+    M_DP_TB_1,
+    |d| PointValue::Double(d.diq.dpi),
+    d.quality(),
+    Some(d.time_tag())
+);
+impl_point_data!(
+    M_ST_NA_1,
+    |d| PointValue::Step(d.vti.value, d.vti.transient),
+    d.quality(),
+    None
+);
+impl_point_data!(
+    M_ST_TA_1,
+    |d| PointValue::Step(d.vti.value, d.vti.transient),
+    d.quality(),
+    Some(d.time_tag())
+);
+impl_point_data!(
+    M_ST_TB_1,
+    |d| PointValue::Step(d.vti.value, d.vti.transient),
+    d.quality(),
+    Some(d.time_tag())
+);
+impl_point_data!(
+    M_BO_NA_1,
+    |d| PointValue::Bitstring(d.bsi.value),
+    d.quality(),
+    None
+);
+impl_point_data!(
+    M_BO_TA_1,
+    |d| PointValue::Bitstring(d.bsi.value),
+    d.quality(),
+    Some(d.time_tag())
+);
+impl_point_data!(
+    M_BO_TB_1,
+    |d| PointValue::Bitstring(d.bsi.value),
+    d.quality(),
+    Some(d.time_tag())
+);
+impl_point_data!(
+    M_ME_NA_1,
+    |d| PointValue::Normalized(d.nva),
+    d.quality(),
+    None
+);
+impl_point_data!(
+    M_ME_TA_1,
+    |d| PointValue::Normalized(d.nva),
+    d.quality(),
+    Some(d.time_tag())
+);
+impl_point_data!(
+    M_ME_TD_1,
+    |d| PointValue::Normalized(d.nva),
+    d.quality(),
+    Some(d.time_tag())
+);
+impl_point_data!(
+    M_ME_ND_1,
+    |d| PointValue::Normalized(d.nva),
+    Quality::default(),
+    None
+);
+impl_point_data!(M_ME_NB_1, |d| PointValue::Scaled(d.sva), d.quality(), None);
+impl_point_data!(
+    M_ME_TB_1,
+    |d| PointValue::Scaled(d.sva),
+    d.quality(),
+    Some(d.time_tag())
+);
+impl_point_data!(
+    M_ME_TE_1,
+    |d| PointValue::Scaled(d.sva),
+    d.quality(),
+    Some(d.time_tag())
+);
+impl_point_data!(
+    M_ME_NC_1,
+    |d| PointValue::Float(d.r32.value),
+    d.quality(),
+    None
+);
+impl_point_data!(
+    M_ME_TC_1,
+    |d| PointValue::Float(d.r32.value),
+    d.quality(),
+    Some(d.time_tag())
+);
+impl_point_data!(
+    M_ME_TF_1,
+    |d| PointValue::Float(d.r32.value),
+    d.quality(),
+    Some(d.time_tag())
+);
+impl_point_data!(
+    M_IT_NA_1,
+    |d| PointValue::Counter(d.bcr, d.seq_qd.seq),
+    d.quality(),
+    None
+);
+impl_point_data!(
+    M_IT_TA_1,
+    |d| PointValue::Counter(d.bcr, d.seq_qd.seq),
+    d.quality(),
+    Some(d.time_tag())
+);
+impl_point_data!(
+    M_IT_TB_1,
+    |d| PointValue::Counter(d.bcr, d.seq_qd.seq),
+    d.quality(),
+    Some(d.time_tag())
+);
+impl_point_data!(
+    M_EP_TA_1,
+    |d| PointValue::ProtectionEvent(d.sep.es, d.elapsed),
+    d.quality(),
+    Some(d.time_tag())
+);
+impl_point_data!(
+    M_EP_TD_1,
+    |d| PointValue::ProtectionEvent(d.sep.es, d.elapsed),
+    d.quality(),
+    Some(d.time_tag())
+);
+impl_point_data!(
+    M_EP_TB_1,
+    |d| PointValue::ProtectionStart(d.start_ep, d.relay_duration),
+    d.quality(),
+    Some(d.time_tag())
+);
+impl_point_data!(
+    M_EP_TE_1,
+    |d| PointValue::ProtectionStart(d.start_ep, d.relay_duration),
+    d.quality(),
+    Some(d.time_tag())
+);
+impl_point_data!(
+    M_EP_TC_1,
+    |d| PointValue::ProtectionCircuit(d.oci, d.relay_op_time),
+    d.quality(),
+    Some(d.time_tag())
+);
+impl_point_data!(
+    M_EP_TF_1,
+    |d| PointValue::ProtectionCircuit(d.oci, d.relay_op_time),
+    d.quality(),
+    Some(d.time_tag())
+);
+impl_point_data!(
+    M_PS_NA_1,
+    |d| PointValue::StatusChange(d.scd),
+    d.quality(),
+    None
+);
+
+#[cfg(test)]
+mod tests {
+    use super::{HasQuality, HasTimeTag, PointData, PointValue, Quality};
+    use crate::types::{
+        DataBuffer,
+        datatype::{
+            BCR, DataType, EventState, M_EP_TD_1, M_IT_NA_1, M_ME_NB_1, M_ME_TF_1, M_SP_TA_1, QDS,
+            R32, SEP, SIQ, SPI, SVA, SeqQD,
+        },
+        time::{CP16Time2a, CP24Time2a, CP56Time2a, TimeTag},
+    };
+
+    #[test]
+    fn point_data_decode() {
+        let decode = |data_type, buf| PointData::decode(data_type, buf).unwrap();
+        let point = decode(
+            DataType::M_ME_NB_1,
+            M_ME_NB_1 {
+                sva: SVA { value: -12 },
+                qds: QDS {
+                    ov: true,
+                    ..QDS::default()
+                },
+            }
+            .into(),
+        );
+        assert_eq!(point.value, PointValue::Scaled(SVA { value: -12 }));
+        assert_eq!(point.value.as_f32(), Some(-12.0));
+        assert!(point.quality.ov && point.quality.is_good());
+        assert_eq!(point.time, None);
+        let time = CP56Time2a {
+            year: 24,
+            month: 7,
+            day: 1,
+            ..CP56Time2a::default()
+        };
+        let point = decode(
+            DataType::M_ME_TF_1,
+            M_ME_TF_1 {
+                r32: R32 { value: 1.5 },
+                qds: QDS::default(),
+                time: time.clone(),
+            }
+            .into(),
+        );
+        assert_eq!(point.value.as_f32(), Some(1.5));
+        assert_eq!(point.time, Some(TimeTag::Cp56(time.clone())));
+        let point = decode(
+            DataType::M_SP_TA_1,
+            M_SP_TA_1 {
+                siq: SIQ {
+                    nt: true,
+                    spi: SPI::On,
+                    ..SIQ::default()
+                },
+                time: CP24Time2a {
+                    ms: 1_000,
+                    min: 5,
+                    iv: true,
+                },
+            }
+            .into(),
+        );
+        assert_eq!(point.value, PointValue::Single(true));
+        assert!(!point.quality.is_good());
+        assert!(point.time.unwrap().is_invalid());
+        let point = decode(
+            DataType::M_IT_NA_1,
+            M_IT_NA_1 {
+                bcr: BCR { value: 42 },
+                seq_qd: SeqQD {
+                    cy: true,
+                    seq: 3,
+                    ..SeqQD::default()
+                },
+            }
+            .into(),
+        );
+        assert_eq!(point.value, PointValue::Counter(BCR { value: 42 }, 3));
+        assert_eq!(
+            point.quality,
+            Quality {
+                cy: true,
+                ..Quality::default()
+            }
+        );
+        let point = decode(
+            DataType::M_EP_TD_1,
+            M_EP_TD_1 {
+                sep: SEP {
+                    ei: true,
+                    es: EventState::On,
+                    ..SEP::default()
+                },
+                elapsed: CP16Time2a { ms: 250 },
+                time,
+            }
+            .into(),
+        );
+        assert_eq!(
+            point.value,
+            PointValue::ProtectionEvent(EventState::On, CP16Time2a { ms: 250 })
+        );
+        assert!(point.quality.ei);
+        assert!(PointData::decode(DataType::C_SC_NA_1, DataBuffer::default()).is_none());
+        assert!(PointData::decode(DataType::M_EI_NA_1, DataBuffer::default()).is_none());
+    }
+
+    fn invalidate(points: &mut [&mut dyn HasQuality]) {
+        for point in points {
+            let mut quality = point.quality();
+            quality.iv = true;
+            point.set_quality(quality);
+        }
+    }
+
+    fn stamp<T: HasTimeTag<Time = CP56Time2a>>(point: &mut T, time: &CP56Time2a) {
+        point.set_time(time.clone());
+    }
+
+    #[test]
+    fn quality_and_time_tag_traits() {
+        let mut single = M_SP_TA_1::default();
+        let mut counter = M_IT_NA_1 {
+            seq_qd: SeqQD {
+                cy: true,
+                ..SeqQD::default()
+            },
+            ..M_IT_NA_1::default()
+        };
+        let mut float = M_ME_TF_1::default();
+        invalidate(&mut [&mut single, &mut counter, &mut float]);
+        assert!(single.siq.iv && counter.seq_qd.iv && float.qds.iv);
+        // the other flags are kept
+        assert!(counter.seq_qd.cy);
+        // flags the descriptor does not have are ignored
+        let mut measured = M_ME_NB_1::default();
+        measured.set_quality(Quality {
+            ov: true,
+            ei: true,
+            ..Quality::default()
+        });
+        assert_eq!(
+            measured.quality(),
+            Quality {
+                ov: true,
+                ..Quality::default()
+            }
+        );
+        let time = CP56Time2a {
+            year: 24,
+            month: 1,
+            day: 2,
+            ..CP56Time2a::default()
+        };
+        stamp(&mut float, &time);
+        assert_eq!(float.time_tag(), TimeTag::Cp56(time));
+        single.set_time(CP24Time2a {
+            ms: 5,
+            min: 1,
+            iv: false,
+        });
+        assert_eq!(single.time().ms, 5);
+        assert!(matches!(single.time_tag(), TimeTag::Cp24(_)));
+    }
+}
@@ -0,0 +1,196 @@
+use std::ops::RangeInclusive;
+
+use crate::Error;
+
+use super::datatype::{
+    C_SE_NA_1, C_SE_NB_1, C_SE_TA_1, C_SE_TB_1, M_ME_NA_1, M_ME_NB_1, M_ME_ND_1, M_ME_TA_1,
+    M_ME_TB_1, M_ME_TD_1, M_ME_TE_1, NVA, P_ME_NA_1, P_ME_NB_1, SVA,
+};
+
+/// Information elements with a normalized or scaled value
+pub trait AnalogValue {
+    /// Raw value (normalized values: the fraction, scaled values: the integer)
+    fn raw(&self) -> f32;
+    /// Set the raw value, out of range values are saturated
+    fn set_raw(&mut self, raw: f32);
+}
+
+impl AnalogValue for NVA {
+    fn raw(&self) -> f32 {
+        self.to_f32()
+    }
+    fn set_raw(&mut self, raw: f32) {
+        *self = NVA::from_f32(raw);
+    }
+}
+
+impl AnalogValue for SVA {
+    fn raw(&self) -> f32 {
+        self.to_f32()
+    }
+    fn set_raw(&mut self, raw: f32) {
+        *self = SVA::from_f32(raw);
+    }
+}
+
+macro_rules! impl_analog_value {
+    ($field: ident, $($t: ty),+) => {
+        $(
+            impl AnalogValue for $t {
+                fn raw(&self) -> f32 {
+                    self.$field.raw()
+                }
+                fn set_raw(&mut self, raw: f32) {
+                    self.$field.set_raw(raw);
+                }
+            }
+        )+
+    };
+}
+
+impl_analog_value!(
+    nva, M_ME_NA_1, M_ME_TA_1, M_ME_TD_1, M_ME_ND_1, C_SE_NA_1, C_SE_TA_1, P_ME_NA_1
+);
+impl_analog_value!(
+    sva, M_ME_NB_1, M_ME_TB_1, M_ME_TE_1, C_SE_NB_1, C_SE_TB_1, P_ME_NB_1
+);
+
+/// Linear scaling of raw values to engineering units
+///
+/// The raw range (e.g. -1..=1 for normalized values, -32768..=32767 for scaled ones) is mapped
+/// to the engineering range, then the offset is added. The default scaling is the identity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scaling {
+    raw: (f32, f32),
+    engineering: (f32, f32),
+    offset: f32,
+    unit: Option<String>,
+}
+
+impl Default for Scaling {
+    fn default() -> Self {
+        Self {
+            raw: (0.0, 1.0),
+            engineering: (0.0, 1.0),
+            offset: 0.0,
+            unit: None,
+        }
+    }
+}
+
+impl Scaling {
+    /// Create a new scaling of the raw range to the engineering range
+    pub fn new(raw: RangeInclusive<f32>, engineering: RangeInclusive<f32>) -> Result<Self, Error> {
+        let raw = raw.into_inner();
+        let engineering = engineering.into_inner();
+        for (name, (start, end)) in [("raw", raw), ("engineering", engineering)] {
+            if !start.is_finite() || !end.is_finite() || (end - start).abs() < f32::EPSILON {
+                return Err(Error::invalid_config(format!(
+                    "invalid {} range: {}..={}",
+                    name, start, end
+                )));
+            }
+        }
+        Ok(Self {
+            raw,
+            engineering,
+            ..Self::default()
+        })
+    }
+    /// Set the offset, added after the range mapping
+    pub fn with_offset(mut self, offset: f32) -> Self {
+        self.offset = offset;
+        self
+    }
+    /// Set the engineering unit
+    pub fn with_unit(mut self, unit: impl Into<String>) -> Self {
+        self.unit = Some(unit.into());
+        self
+    }
+    /// Offset
+    pub fn offset(&self) -> f32 {
+        self.offset
+    }
+    /// Engineering unit
+    pub fn unit(&self) -> Option<&str> {
+        self.unit.as_deref()
+    }
+    /// Convert a raw value to engineering units
+    pub fn to_engineering(&self, raw: f32) -> f32 {
+        let (raw_start, raw_end) = self.raw;
+        let (start, end) = self.engineering;
+        start + (raw - raw_start) * (end - start) / (raw_end - raw_start) + self.offset
+    }
+    /// Convert a value in engineering units to the raw one
+    pub fn to_raw(&self, value: f32) -> f32 {
+        let (raw_start, raw_end) = self.raw;
+        let (start, end) = self.engineering;
+        raw_start + (value - self.offset - start) * (raw_end - raw_start) / (end - start)
+    }
+    /// Read the value of an information element in engineering units
+    pub fn read(&self, data: &impl AnalogValue) -> f32 {
+        self.to_engineering(data.raw())
+    }
+    /// Write a value in engineering units to an information element
+    pub fn write(&self, data: &mut impl AnalogValue, value: f32) {
+        data.set_raw(self.to_raw(value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AnalogValue, Scaling};
+    use crate::types::{
+        DataBuffer,
+        datatype::{C_SE_NA_1, M_ME_NA_1, M_ME_NB_1, NVA, P_ME_NB_1, SVA},
+    };
+
+    #[test]
+    fn signed_values() {
+        let buf: DataBuffer = M_ME_NA_1 {
+            nva: NVA { value: -16384 },
+            ..M_ME_NA_1::default()
+        }
+        .into();
+        assert_eq!(&buf[..2], &[0x00, 0xC0]);
+        assert!((M_ME_NA_1::from(buf).nva.to_f32() + 0.5).abs() < f32::EPSILON);
+        assert_eq!(NVA::from_f32(-1.0).value, i16::MIN);
+        assert_eq!(NVA::from_f32(1.0).value, i16::MAX);
+        assert_eq!(NVA::from_f32(0.25).value, 8192);
+        assert_eq!(NVA::from_f32(f32::NAN).value, 0);
+        assert_eq!(SVA::from_f32(-123.4).value, -123);
+        assert_eq!(SVA::from_f32(1e6).value, i16::MAX);
+        let mut buf = DataBuffer::default();
+        buf[..2].copy_from_slice(&(-5_i16).to_le_bytes());
+        assert!((M_ME_NB_1::from(buf).sva.to_f32() + 5.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn scaling() {
+        // normalized 0..1 is 0..250 bar
+        let scaling = Scaling::new(0.0..=1.0, 0.0..=250.0)
+            .unwrap()
+            .with_unit("bar");
+        assert_eq!(scaling.unit(), Some("bar"));
+        let mut value = M_ME_NA_1::default();
+        scaling.write(&mut value, 125.0);
+        assert_eq!(value.nva.value, 16384);
+        assert!((scaling.read(&value) - 125.0).abs() < 0.01);
+        // out of range values are saturated
+        scaling.write(&mut value, -300.0);
+        assert_eq!(value.nva.value, i16::MIN);
+        // scaled value in 0.1 °C with an offset
+        let scaling = Scaling::new(0.0..=10.0, 0.0..=1.0)
+            .unwrap()
+            .with_offset(-40.0);
+        let mut value = P_ME_NB_1::default();
+        scaling.write(&mut value, 21.5);
+        assert_eq!(value.sva.value, 615);
+        assert!((scaling.read(&value) - 21.5).abs() < f32::EPSILON);
+        let mut command = C_SE_NA_1::default();
+        Scaling::default().write(&mut command, -0.5);
+        assert!((command.raw() + 0.5).abs() < f32::EPSILON);
+        assert!(Scaling::new(1.0..=1.0, 0.0..=1.0).is_err());
+        assert!(Scaling::new(0.0..=1.0, 0.0..=f32::NAN).is_err());
+    }
+}
@@ -3,9 +3,11 @@
 use std::time::Duration;
 
 use bma_ts::Timestamp;
-use chrono::{Datelike, Timelike};
+use chrono::{
+    Datelike, FixedOffset, LocalResult, NaiveDate, NaiveDateTime, TimeDelta, TimeZone, Timelike,
+};
 
-use crate::Error;
+use crate::{Error, clock::Clock};
 
 const ERR_TIME_CONVERSION_FAILED: &str = "Time conversion failed";
 
@@ -30,6 +32,14 @@ impl TryFrom<Duration> for CP16Time2a {
     }
 }
 
+impl CP16Time2a {
+    /// End of an elapsed time interval (e.g. relay duration or operating time of protection
+    /// events) which starts at the time tag
+    pub fn resolve(self, start: Timestamp) -> Timestamp {
+        start + Duration::from(self)
+    }
+}
+
 impl From<CP16Time2a> for Duration {
     fn from(value: CP16Time2a) -> Duration {
         Duration::from_millis(u64::from(value.ms))
@@ -76,6 +86,55 @@ impl TryFrom<Duration> for CP24Time2a {
     }
 }
 
+impl CP24Time2a {
+    /// Resolves to the absolute time closest to the reference (usually the time of reception)
+    ///
+    /// The reference is converted to the local time of the policy and its minutes and
+    /// milliseconds are replaced. Of the candidates in the reference hour and the adjacent ones,
+    /// the closest to the reference is chosen, so the hour rollover is handled in both
+    /// directions (the device clock behind or ahead of the reference). The invalid flag is not
+    /// considered.
+    pub fn resolve(
+        &self,
+        reference: impl Into<TimeReference>,
+        policy: &TimePolicy,
+    ) -> Result<Timestamp, Error> {
+        let reference = reference
+            .into()
+            .to_timestamp(policy)?
+            .try_into_datetime_utc()
+            .map_err(Error::conversion)?
+            .naive_utc();
+        let (offset, _) = policy.offset_at(reference);
+        let offset = TimeDelta::seconds(offset.into());
+        let t = self.nearest(reference + offset)?;
+        Timestamp::try_from((t - offset).and_utc()).map_err(Error::conversion)
+    }
+    /// The time with the minutes and milliseconds, closest to the (naive) reference
+    fn nearest(self, reference: NaiveDateTime) -> Result<NaiveDateTime, Error> {
+        if self.ms > 59_999 || self.min > 59 {
+            return Err(Error::conversion(format!(
+                "CP24Time2a out of range: {}:{}",
+                self.min, self.ms
+            )));
+        }
+        let base = reference
+            .with_minute(u32::from(self.min))
+            .and_then(|t| t.with_second(u32::from(self.ms / 1000)))
+            .and_then(|t| t.with_nanosecond(u32::from(self.ms % 1000) * 1_000_000))
+            .ok_or_else(|| Error::conversion(ERR_TIME_CONVERSION_FAILED))?;
+        Ok([
+            base.checked_sub_signed(TimeDelta::hours(1)),
+            Some(base),
+            base.checked_add_signed(TimeDelta::hours(1)),
+        ]
+        .into_iter()
+        .flatten()
+        .min_by_key(|t| (*t - reference).abs())
+        .unwrap_or(base))
+    }
+}
+
 impl From<CP24Time2a> for Duration {
     fn from(value: CP24Time2a) -> Duration {
         Duration::from_millis(u64::from(value.min) * 60_000 + u64::from(value.ms))
@@ -103,28 +162,22 @@ impl TryFrom<Timestamp> for CP24Time2a {
     }
 }
 
+/// Resolves to the local time closest to the current system time, see [`CP24Time2a::resolve`]
 impl TryFrom<CP24Time2a> for Timestamp {
     type Error = Error;
 
     fn try_from(value: CP24Time2a) -> Result<Self, Self::Error> {
-        let seconds = value.ms / 1000;
-        let ms = value.ms % 1000;
-        let t = chrono::Local::now()
-            .with_minute(u32::from(value.min))
-            .ok_or_else(|| Error::conversion(ERR_TIME_CONVERSION_FAILED))?
-            .with_second(seconds.into())
-            .ok_or_else(|| Error::conversion(ERR_TIME_CONVERSION_FAILED))?
-            .with_nanosecond(u32::from(ms) * 1_000_000)
-            .ok_or_else(|| Error::conversion(ERR_TIME_CONVERSION_FAILED))?;
-        t.try_into().map_err(Error::conversion)
+        value.resolve(Timestamp::now(), &TimePolicy::Local)
     }
 }
 
+/// Decodes the wire format: milliseconds (2 octets), minutes (bits 1-6), reserved (bit 7) and
+/// the invalid flag (bit 8) of the third octet
 impl From<[u8; 3]> for CP24Time2a {
     fn from(buf: [u8; 3]) -> Self {
         let ms = u16::from_le_bytes([buf[0], buf[1]]);
         let min = buf[2] & 0b0011_1111;
-        let iv = min & 0b1000_0000 != 0;
+        let iv = buf[2] & 0b1000_0000 != 0;
         CP24Time2a { ms, min, iv }
     }
 }
@@ -196,6 +249,132 @@ pub struct CP56Time2a {
     pub year: u8,
 }
 
+impl CP56Time2a {
+    /// Reconstructs a full time tag from a CP24Time2a (minutes and milliseconds only)
+    ///
+    /// The date and hour are taken from the reference time (usually the time of reception). Of
+    /// the candidates in the reference hour and the adjacent ones, the closest to the reference is
+    /// chosen, so e.g. 59:59.900 received at 13:00:00.200 is resolved to 12:59:59.900 of the same
+    /// day. The invalid flag is taken from the CP24Time2a, the summer time flag from the
+    /// reference.
+    pub fn from_cp24time2a(time: CP24Time2a, reference: &CP56Time2a) -> Result<Self, Error> {
+        let t = time.nearest(reference.to_naive()?)?;
+        Self::from_naive(t, time.iv, reference.su)
+    }
+    /// Current time of the clock
+    pub fn now(clock: &dyn Clock, policy: &TimePolicy) -> Result<Self, Error> {
+        Self::from_timestamp(clock.timestamp(), policy)
+    }
+    /// Checks the fields are in range (milliseconds, minutes, hours, day, month, year)
+    pub fn validate(&self) -> Result<(), Error> {
+        for (valid, field, value) in [
+            (self.ms <= 59_999, "milliseconds", self.ms),
+            (self.min <= 59, "minutes", u16::from(self.min)),
+            (self.hour <= 23, "hours", u16::from(self.hour)),
+            ((1..=31).contains(&self.day), "day", u16::from(self.day)),
+            (
+                (1..=12).contains(&self.month),
+                "month",
+                u16::from(self.month),
+            ),
+            (self.year <= 99, "year", u16::from(self.year)),
+        ] {
+            if !valid {
+                return Err(Error::conversion(format!(
+                    "CP56Time2a {} out of range: {}",
+                    field, value
+                )));
+            }
+        }
+        Ok(())
+    }
+    /// Converts to a timestamp, the fields are interpreted according to the policy
+    ///
+    /// The summer time flag selects the offset for time zones with summer time (and resolves
+    /// ambiguous local times), the day of week is ignored.
+    pub fn to_timestamp(&self, policy: &TimePolicy) -> Result<Timestamp, Error> {
+        let local = self.to_naive()?;
+        let utc = match policy {
+            TimePolicy::Utc => local,
+            TimePolicy::Fixed(offset) => {
+                local - TimeDelta::seconds(offset.local_minus_utc().into())
+            }
+            TimePolicy::Local => match chrono::Local.from_local_datetime(&local) {
+                LocalResult::Single(t) => t.naive_utc(),
+                LocalResult::Ambiguous(a, b) => {
+                    let a_summer = a.offset().local_minus_utc() > b.offset().local_minus_utc();
+                    if a_summer == self.su {
+                        a.naive_utc()
+                    } else {
+                        b.naive_utc()
+                    }
+                }
+                LocalResult::None => {
+                    return Err(Error::conversion(format!(
+                        "local time does not exist: {}",
+                        local
+                    )));
+                }
+            },
+            TimePolicy::Zone(zone) => local - TimeDelta::seconds(zone.offset(self.su).into()),
+        };
+        Timestamp::try_from(utc.and_utc()).map_err(Error::conversion)
+    }
+    /// Converts a timestamp, the fields are set according to the policy
+    ///
+    /// The summer time flag is set for time zones with summer time only. Years outside of
+    /// 2000-2099 can not be represented.
+    pub fn from_timestamp(timestamp: Timestamp, policy: &TimePolicy) -> Result<Self, Error> {
+        let utc = timestamp
+            .try_into_datetime_utc()
+            .map_err(Error::conversion)?
+            .naive_utc();
+        let (offset, su) = policy.offset_at(utc);
+        let local = utc + TimeDelta::seconds(offset.into());
+        Self::from_naive(local, false, su)
+    }
+    fn to_naive(&self) -> Result<NaiveDateTime, Error> {
+        self.validate()?;
+        NaiveDate::from_ymd_opt(
+            2000 + i32::from(self.year),
+            u32::from(self.month),
+            u32::from(self.day),
+        )
+        .and_then(|d| {
+            d.and_hms_milli_opt(
+                u32::from(self.hour),
+                u32::from(self.min),
+                u32::from(self.ms / 1000),
+                u32::from(self.ms % 1000),
+            )
+        })
+        .ok_or_else(|| Error::conversion(ERR_TIME_CONVERSION_FAILED))
+    }
+    fn from_naive(t: NaiveDateTime, iv: bool, su: bool) -> Result<Self, Error> {
+        if !(2000..2100).contains(&t.year()) {
+            return Err(Error::conversion(format!(
+                "CP56Time2a year out of range: {}",
+                t.year()
+            )));
+        }
+        // leap seconds
+        let ms = (t.nanosecond() / 1_000_000 + t.second() * 1000).min(59_999);
+        Ok(CP56Time2a {
+            ms: u16::try_from(ms).map_err(Error::conversion)?,
+            iv,
+            min: u8::try_from(t.minute()).map_err(Error::conversion)?,
+            su,
+            hour: u8::try_from(t.hour()).map_err(Error::conversion)?,
+            dow: u8::try_from(t.weekday().number_from_monday())
+                .map_err(Error::conversion)?
+                .into(),
+            day: u8::try_from(t.day()).map_err(Error::conversion)?,
+            month: u8::try_from(t.month()).map_err(Error::conversion)?,
+            year: u8::try_from(t.year() % 100).map_err(Error::conversion)?,
+        })
+    }
+}
+
 impl From<[u8; 7]> for CP56Time2a {
     fn from(buf: [u8; 7]) -> Self {
         let ms = u16::from_le_bytes([buf[0], buf[1]]);
@@ -237,77 +416,389 @@ impl From<CP56Time2a> for [u8; 7] {
 impl TryFrom<Timestamp> for CP56Time2a {
     type Error = Error;
 
+    /// Converts to the local time of the host, see [`CP56Time2a::from_timestamp`]
     fn try_from(value: Timestamp) -> Result<Self, Self::Error> {
-        let t = value.try_into_datetime_local().map_err(Error::conversion)?;
-        let mut ms = t.nanosecond() / 1_000_000 + t.second() * 1000;
-        if ms > 59_999 {
-            ms = 59_999;
-        }
-        let mut min = t.minute();
-        if min > 59 {
-            min = 59;
-        }
-        let mut hour = t.hour();
-        if hour > 23 {
-            hour = 23;
-        }
-        let offset = t.offset().local_minus_utc();
-        let standard_offset = t
-            .with_month(1)
-            .ok_or_else(|| Error::conversion(ERR_TIME_CONVERSION_FAILED))?
-            .with_day(1)
-            .ok_or_else(|| Error::conversion(ERR_TIME_CONVERSION_FAILED))?
-            .offset()
-            .local_minus_utc();
-        let summer_time = offset != standard_offset;
-        Ok(CP56Time2a {
-            ms: u16::try_from(ms).unwrap(),
-            iv: false,
-            min: u8::try_from(min).unwrap(),
-            su: summer_time,
-            hour: u8::try_from(hour).unwrap(),
-            dow: u8::try_from(t.weekday().number_from_monday())
-                .map_err(Error::conversion)?
-                .into(),
-            day: u8::try_from(t.day()).map_err(Error::conversion)?,
-            month: u8::try_from(t.month()).map_err(Error::conversion)?,
-            year: u8::try_from(t.year() % 100).map_err(Error::conversion)?,
-        })
+        CP56Time2a::from_timestamp(value, &TimePolicy::Local)
     }
 }
 
 impl TryFrom<CP56Time2a> for Timestamp {
     type Error = Error;
 
+    /// Converts from the local time of the host, see [`CP56Time2a::to_timestamp`]
     fn try_from(value: CP56Time2a) -> Result<Self, Self::Error> {
-        let seconds = value.ms / 1000;
-        let ms = value.ms % 1000;
-        let t = chrono::Local::now()
-            .with_minute(u32::from(value.min))
-            .ok_or_else(|| Error::conversion(ERR_TIME_CONVERSION_FAILED))?
-            .with_second(seconds.into())
-            .ok_or_else(|| Error::conversion(ERR_TIME_CONVERSION_FAILED))?
-            .with_nanosecond(u32::from(ms) * 1_000_000)
-            .ok_or_else(|| Error::conversion(ERR_TIME_CONVERSION_FAILED))?
-            .with_hour(u32::from(value.hour))
-            .ok_or_else(|| Error::conversion(ERR_TIME_CONVERSION_FAILED))?
-            .with_day(u32::from(value.day))
-            .ok_or_else(|| Error::conversion(ERR_TIME_CONVERSION_FAILED))?
-            .with_month(u32::from(value.month))
-            .ok_or_else(|| Error::conversion(ERR_TIME_CONVERSION_FAILED))?
-            .with_year(2000 + i32::from(value.year))
-            .ok_or_else(|| Error::conversion(ERR_TIME_CONVERSION_FAILED))?;
-        t.try_into().map_err(Error::conversion)
+        value.to_timestamp(&TimePolicy::Local)
+    }
+}
+
+/// Time zone policy of CP56Time2a conversions
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub enum TimePolicy {
+    /// UTC, the summer time flag is never set
+    #[default]
+    Utc,
+    /// Local time zone of the host
+    Local,
+    /// Fixed offset from UTC, the summer time flag is never set
+    Fixed(FixedOffset),
+    /// Time zone with summer time rules
+    Zone(TimeZoneRules),
+}
+
+impl TimePolicy {
+    /// UTC offset (seconds) and the summer time flag at the UTC time
+    fn offset_at(&self, utc: NaiveDateTime) -> (i32, bool) {
+        match self {
+            TimePolicy::Utc => (0, false),
+            TimePolicy::Fixed(offset) => (offset.local_minus_utc(), false),
+            TimePolicy::Local => {
+                let offset = |t: &NaiveDateTime| {
+                    chrono::Local
+                        .from_utc_datetime(t)
+                        .offset()
+                        .local_minus_utc()
+                };
+                // the standard offset is the smaller one of January and July
+                let standard = [1, 7]
+                    .into_iter()
+                    .filter_map(|month| NaiveDate::from_ymd_opt(utc.year(), month, 1))
+                    .map(|d| offset(&d.and_time(chrono::NaiveTime::MIN)))
+                    .min()
+                    .unwrap_or_default();
+                let current = offset(&utc);
+                (current, current > standard)
+            }
+            TimePolicy::Zone(zone) => zone.offset_at(utc),
+        }
+    }
+}
+
+/// Reference time to resolve partial time tags
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum TimeReference {
+    /// Full time tag, interpreted according to the policy
+    Cp56(CP56Time2a),
+    /// Timestamp
+    Timestamp(Timestamp),
+}
+
+impl From<CP56Time2a> for TimeReference {
+    fn from(value: CP56Time2a) -> Self {
+        TimeReference::Cp56(value)
+    }
+}
+
+impl From<&CP56Time2a> for TimeReference {
+    fn from(value: &CP56Time2a) -> Self {
+        TimeReference::Cp56(value.clone())
+    }
+}
+
+impl From<Timestamp> for TimeReference {
+    fn from(value: Timestamp) -> Self {
+        TimeReference::Timestamp(value)
+    }
+}
+
+/// Time tag of an information object
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum TimeTag {
+    /// Three octet binary time (minutes, seconds and milliseconds)
+    Cp24(CP24Time2a),
+    /// Seven octet binary time
+    Cp56(CP56Time2a),
+}
+
+impl From<CP24Time2a> for TimeTag {
+    fn from(value: CP24Time2a) -> Self {
+        TimeTag::Cp24(value)
+    }
+}
+
+impl From<CP56Time2a> for TimeTag {
+    fn from(value: CP56Time2a) -> Self {
+        TimeTag::Cp56(value)
+    }
+}
+
+impl TimeTag {
+    /// Is the invalid flag set
+    pub fn is_invalid(&self) -> bool {
+        match self {
+            TimeTag::Cp24(t) => t.iv,
+            TimeTag::Cp56(t) => t.iv,
+        }
+    }
+    /// Convert to a timestamp, CP24Time2a time tags are resolved against the reference
+    pub fn resolve(
+        &self,
+        reference: impl Into<TimeReference>,
+        policy: &TimePolicy,
+    ) -> Result<Timestamp, Error> {
+        match self {
+            TimeTag::Cp24(t) => t.resolve(reference, policy),
+            TimeTag::Cp56(t) => t.to_timestamp(policy),
+        }
+    }
+}
+
+impl TimeReference {
+    fn to_timestamp(&self, policy: &TimePolicy) -> Result<Timestamp, Error> {
+        match self {
+            TimeReference::Cp56(t) => t.to_timestamp(policy),
+            TimeReference::Timestamp(t) => Ok(*t),
+        }
+    }
+}
+
+/// Summer time transition rule (POSIX `Mm.w.d/time`)
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Transition {
+    /// Month (1-12)
+    pub month: u8,
+    /// Week of the month (1-4, 5 = the last one)
+    pub week: u8,
+    /// Day of week (0 = Sunday)
+    pub weekday: u8,
+    /// Local time of the transition (seconds after midnight)
+    pub time: i32,
+}
+
+impl Transition {
+    /// Checks the fields are in range (month, week, day of week)
+    pub fn validate(&self) -> Result<(), Error> {
+        if !(1..=12).contains(&self.month) || !(1..=5).contains(&self.week) || self.weekday > 6 {
+            return Err(Error::invalid_config(format!(
+                "invalid summer time transition: M{}.{}.{}",
+                self.month, self.week, self.weekday
+            )));
+        }
+        Ok(())
+    }
+    /// UTC time of the transition in the year, the offset is the one before the transition
+    fn utc(self, year: i32, offset: i32) -> Option<NaiveDateTime> {
+        if self.validate().is_err() {
+            return None;
+        }
+        let first = NaiveDate::from_ymd_opt(year, u32::from(self.month), 1)?;
+        let first_weekday = first.weekday().num_days_from_sunday();
+        let mut day = 1 + (u32::from(self.weekday) + 7 - first_weekday) % 7;
+        day += 7 * (u32::from(self.week) - 1);
+        while NaiveDate::from_ymd_opt(year, u32::from(self.month), day).is_none() {
+            day -= 7;
+        }
+        let date = NaiveDate::from_ymd_opt(year, u32::from(self.month), day)?;
+        date.and_time(chrono::NaiveTime::MIN)
+            .checked_add_signed(TimeDelta::seconds(i64::from(self.time) - i64::from(offset)))
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct SummerTime {
+    offset: i32,
+    start: Transition,
+    end: Transition,
+}
+
+/// Time zone rules: a standard offset and optional yearly summer time transitions
+///
+/// The rules are created from POSIX TZ strings (e.g. `CET-1CEST,M3.5.0,M10.5.0/3` for
+/// Europe/Berlin) or built manually. The same rules are applied to all years.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TimeZoneRules {
+    name: String,
+    standard: i32,
+    summer: Option<SummerTime>,
+}
+
+impl TimeZoneRules {
+    /// Create rules without summer time
+    pub fn new(name: &str, standard: FixedOffset) -> Self {
+        Self {
+            name: name.to_owned(),
+            standard: standard.local_minus_utc(),
+            summer: None,
+        }
+    }
+    /// Set the summer time offset and transitions (the start time is local standard time, the
+    /// end time is local summer time)
+    pub fn with_summer_time(
+        mut self,
+        offset: FixedOffset,
+        start: Transition,
+        end: Transition,
+    ) -> Result<Self, Error> {
+        start.validate()?;
+        end.validate()?;
+        self.summer = Some(SummerTime {
+            offset: offset.local_minus_utc(),
+            start,
+            end,
+        });
+        Ok(self)
+    }
+    /// Parse a POSIX TZ string (`std offset [dst [offset] ,start[/time],end[/time]]`, the
+    /// transitions in the `Mm.w.d` format)
+    pub fn parse(tz: &str) -> Result<Self, Error> {
+        let err = || Error::conversion(format!("invalid POSIX TZ string: {}", tz));
+        let mut parser = PosixParser { s: tz, pos: 0 };
+        parser.name().ok_or_else(err)?;
+        // POSIX offsets are positive west of Greenwich
+        let standard = -parser.time().ok_or_else(err)?;
+        let mut rules = Self {
+            name: tz.to_owned(),
+            standard,
+            summer: None,
+        };
+        if parser.is_empty() {
+            return Ok(rules);
+        }
+        parser.name().ok_or_else(err)?;
+        let offset = if parser.peek() == Some(b',') {
+            standard + 3600
+        } else {
+            -parser.time().ok_or_else(err)?
+        };
+        if !parser.eat(b',') {
+            return Err(err());
+        }
+        let start = parser.transition().ok_or_else(err)?;
+        if !parser.eat(b',') {
+            return Err(err());
+        }
+        let end = parser.transition().ok_or_else(err)?;
+        if !parser.is_empty() {
+            return Err(err());
+        }
+        rules.summer = Some(SummerTime { offset, start, end });
+        Ok(rules)
+    }
+    /// Name of the time zone
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// UTC offset (seconds) of standard or summer time
+    fn offset(&self, summer_time: bool) -> i32 {
+        match &self.summer {
+            Some(s) if summer_time => s.offset,
+            _ => self.standard,
+        }
+    }
+    /// UTC offset (seconds) and the summer time flag at the UTC time
+    fn offset_at(&self, utc: NaiveDateTime) -> (i32, bool) {
+        let Some(s) = &self.summer else {
+            return (self.standard, false);
+        };
+        let year = (utc + TimeDelta::seconds(self.standard.into())).year();
+        let (Some(start), Some(end)) =
+            (s.start.utc(year, self.standard), s.end.utc(year, s.offset))
+        else {
+            return (self.standard, false);
+        };
+        let summer_time = if start < end {
+            utc >= start && utc < end
+        } else {
+            // southern hemisphere
+            utc >= start || utc < end
+        };
+        if summer_time {
+            (s.offset, true)
+        } else {
+            (self.standard, false)
+        }
+    }
+}
+
+struct PosixParser<'a> {
+    s: &'a str,
+    pos: usize,
+}
+
+impl PosixParser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.s.as_bytes().get(self.pos).copied()
+    }
+    fn is_empty(&self) -> bool {
+        self.pos >= self.s.len()
+    }
+    fn eat(&mut self, c: u8) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+    fn name(&mut self) -> Option<&str> {
+        let start = self.pos;
+        if self.eat(b'<') {
+            while self.peek().is_some_and(|c| c != b'>') {
+                self.pos += 1;
+            }
+            let name = &self.s[start + 1..self.pos];
+            return (self.eat(b'>') && name.len() >= 3).then_some(name);
+        }
+        while self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+            self.pos += 1;
+        }
+        let name = &self.s[start..self.pos];
+        (name.len() >= 3).then_some(name)
+    }
+    fn number(&mut self) -> Option<i32> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        self.s[start..self.pos].parse().ok()
+    }
+    /// `[+-]hh[:mm[:ss]]` in seconds
+    fn time(&mut self) -> Option<i32> {
+        let sign = if self.eat(b'-') {
+            -1
+        } else {
+            self.eat(b'+');
+            1
+        };
+        let mut seconds = self.number()? * 3600;
+        if self.eat(b':') {
+            seconds += self.number().filter(|m| *m < 60)? * 60;
+            if self.eat(b':') {
+                seconds += self.number().filter(|s| *s < 60)?;
+            }
+        }
+        Some(sign * seconds)
+    }
+    /// `Mm.w.d[/time]`
+    fn transition(&mut self) -> Option<Transition> {
+        if !self.eat(b'M') {
+            return None;
+        }
+        let month = u8::try_from(self.number()?)
+            .ok()
+            .filter(|m| (1..=12).contains(m))?;
+        self.eat(b'.').then_some(())?;
+        let week = u8::try_from(self.number()?)
+            .ok()
+            .filter(|w| (1..=5).contains(w))?;
+        self.eat(b'.').then_some(())?;
+        let weekday = u8::try_from(self.number()?).ok().filter(|d| *d <= 6)?;
+        let time = if self.eat(b'/') { self.time()? } else { 7200 };
+        Some(Transition {
+            month,
+            week,
+            weekday,
+            time,
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use bma_ts::Timestamp;
-    use chrono::{Local, TimeZone, Timelike};
+    use chrono::{FixedOffset, Local, TimeZone, Timelike};
     use std::time::Duration;
 
-    use super::{CP16Time2a, CP24Time2a, CP56Time2a};
+    use super::{CP16Time2a, CP24Time2a, CP56Time2a, TimePolicy, TimeZoneRules, Transition};
 
     #[test]
     fn test_cp16time2a_from_duration() {
@@ -436,6 +927,106 @@ mod tests {
         assert!(cp56time2a.su);
     }
 
+    #[test]
+    fn test_cp24time2a_iv_from_bytes() {
+        let cp24time2a: CP24Time2a = [0x07, 0x87, 0x94].into();
+        assert_eq!(cp24time2a.min, 20);
+        assert!(cp24time2a.iv);
+    }
+
+    #[test]
+    fn test_cp24time2a_iv_roundtrip() {
+        // the invalid flag is the top bit of the minutes octet, not of the minutes value
+        for iv in [false, true] {
+            let cp24time2a = CP24Time2a {
+                ms: 34567,
+                min: 59,
+                iv,
+            };
+            let bytes: [u8; 3] = cp24time2a.into();
+            assert_eq!(bytes[2], 59 | (u8::from(iv) << 7));
+            assert_eq!(CP24Time2a::from(bytes), cp24time2a);
+        }
+        // the reserved bit is ignored
+        let cp24time2a: CP24Time2a = [0x07, 0x87, 0x54].into();
+        assert_eq!((cp24time2a.min, cp24time2a.iv), (20, false));
+    }
+
+    fn cp56(year: u8, month: u8, day: u8, hour: u8, min: u8, ms: u16) -> CP56Time2a {
+        CP56Time2a {
+            ms,
+            iv: false,
+            min,
+            su: false,
+            hour,
+            dow: 1.into(),
+            day,
+            month,
+            year,
+        }
+    }
+
+    #[test]
+    fn test_cp56time2a_from_cp24time2a() {
+        let reference = cp56(24, 7, 1, 12, 34, 56_789);
+        let time = CP24Time2a {
+            ms: 1_000,
+            min: 30,
+            iv: true,
+        };
+        let t = CP56Time2a::from_cp24time2a(time, &reference).unwrap();
+        assert_eq!((t.hour, t.min, t.ms, t.day), (12, 30, 1_000, 1));
+        assert!(t.iv);
+        // a device clock slightly ahead of the reference
+        let time = CP24Time2a {
+            ms: 57_000,
+            min: 34,
+            iv: false,
+        };
+        let t = CP56Time2a::from_cp24time2a(time, &reference).unwrap();
+        assert_eq!((t.hour, t.min, t.ms), (12, 34, 57_000));
+        assert!(
+            CP56Time2a::from_cp24time2a(
+                CP24Time2a {
+                    ms: 60_000,
+                    min: 0,
+                    iv: false
+                },
+                &reference
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_cp56time2a_from_cp24time2a_rollover() {
+        // minute rollover over midnight and the end of the year
+        let reference = cp56(25, 1, 1, 0, 0, 200);
+        let time = CP24Time2a {
+            ms: 59_900,
+            min: 59,
+            iv: false,
+        };
+        let t = CP56Time2a::from_cp24time2a(time, &reference).unwrap();
+        assert_eq!(
+            (t.year, t.month, t.day, t.hour, t.min, t.ms),
+            (24, 12, 31, 23, 59, 59_900)
+        );
+        assert_eq!(t.dow, 2.into());
+        // the device clock is ahead, the next hour
+        let reference = cp56(24, 2, 29, 23, 59, 59_000);
+        let time = CP24Time2a {
+            ms: 500,
+            min: 0,
+            iv: false,
+        };
+        let t = CP56Time2a::from_cp24time2a(time, &reference).unwrap();
+        assert_eq!(
+            (t.year, t.month, t.day, t.hour, t.min, t.ms),
+            (24, 3, 1, 0, 0, 500)
+        );
+    }
+
     #[test]
     fn test_bytes_from_cp56time2a() {
         let cp56time2a = CP56Time2a {
@@ -452,4 +1043,199 @@ mod tests {
         let bytes: [u8; 7] = cp56time2a.into();
         assert_eq!(bytes, [0xD5, 0xDD, 0x22, 0x92, 0b0101_1110, 0x07, 0x18]);
     }
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, min: u32, sec: u32) -> Timestamp {
+        chrono::Utc
+            .with_ymd_and_hms(year, month, day, hour, min, sec)
+            .unwrap()
+            .try_into()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_cp56time2a_policy_utc_and_fixed() {
+        let t = cp56(24, 7, 1, 12, 34, 56_789);
+        let timestamp = t.to_timestamp(&TimePolicy::Utc).unwrap();
+        assert_eq!(
+            timestamp,
+            utc(2024, 7, 1, 12, 34, 56) + Duration::from_millis(789)
+        );
+        assert_eq!(
+            CP56Time2a::from_timestamp(timestamp, &TimePolicy::Utc).unwrap(),
+            t
+        );
+        let fixed = TimePolicy::Fixed(FixedOffset::east_opt(2 * 3600).unwrap());
+        assert_eq!(
+            t.to_timestamp(&fixed).unwrap(),
+            utc(2024, 7, 1, 10, 34, 56) + Duration::from_millis(789)
+        );
+        let t = CP56Time2a::from_timestamp(utc(2024, 12, 31, 23, 0, 0), &fixed).unwrap();
+        assert_eq!((t.year, t.month, t.day, t.hour), (25, 1, 1, 1));
+        assert_eq!(t.dow, 3.into());
+        assert!(!t.su);
+    }
+
+    #[test]
+    fn test_cp56time2a_policy_zone() {
+        let zone = TimePolicy::Zone(TimeZoneRules::parse("CET-1CEST,M3.5.0,M10.5.0/3").unwrap());
+        let t = CP56Time2a::from_timestamp(utc(2024, 7, 1, 12, 0, 0), &zone).unwrap();
+        assert_eq!((t.hour, t.su), (14, true));
+        let t = CP56Time2a::from_timestamp(utc(2024, 1, 1, 12, 0, 0), &zone).unwrap();
+        assert_eq!((t.hour, t.su), (13, false));
+        // transitions
+        let t = CP56Time2a::from_timestamp(utc(2024, 3, 31, 0, 59, 59), &zone).unwrap();
+        assert_eq!((t.hour, t.min, t.su), (1, 59, false));
+        let t = CP56Time2a::from_timestamp(utc(2024, 3, 31, 1, 0, 0), &zone).unwrap();
+        assert_eq!((t.hour, t.min, t.su), (3, 0, true));
+        let t = CP56Time2a::from_timestamp(utc(2024, 10, 27, 0, 30, 0), &zone).unwrap();
+        assert_eq!((t.hour, t.min, t.su), (2, 30, true));
+        let t = CP56Time2a::from_timestamp(utc(2024, 10, 27, 1, 30, 0), &zone).unwrap();
+        assert_eq!((t.hour, t.min, t.su), (2, 30, false));
+        // the summer time flag resolves the ambiguous hour
+        let mut t = cp56(24, 10, 27, 2, 30, 0);
+        t.su = true;
+        assert_eq!(t.to_timestamp(&zone).unwrap(), utc(2024, 10, 27, 0, 30, 0));
+        t.su = false;
+        assert_eq!(t.to_timestamp(&zone).unwrap(), utc(2024, 10, 27, 1, 30, 0));
+        // southern hemisphere
+        let zone = TimePolicy::Zone(TimeZoneRules::parse("AEST-10AEDT,M10.1.0,M4.1.0/3").unwrap());
+        let t = CP56Time2a::from_timestamp(utc(2024, 1, 1, 0, 0, 0), &zone).unwrap();
+        assert_eq!((t.hour, t.su), (11, true));
+        let t = CP56Time2a::from_timestamp(utc(2024, 7, 1, 0, 0, 0), &zone).unwrap();
+        assert_eq!((t.hour, t.su), (10, false));
+        let zone = TimeZoneRules::parse("<+0530>-5:30").unwrap();
+        let t =
+            CP56Time2a::from_timestamp(utc(2024, 7, 1, 0, 0, 0), &TimePolicy::Zone(zone)).unwrap();
+        assert_eq!((t.hour, t.min, t.su), (5, 30, false));
+    }
+
+    #[test]
+    fn test_cp56time2a_policy_errors() {
+        for t in [
+            cp56(24, 0, 1, 12, 0, 0),
+            cp56(24, 13, 1, 12, 0, 0),
+            cp56(24, 2, 30, 12, 0, 0),
+            cp56(24, 7, 0, 12, 0, 0),
+            cp56(24, 7, 1, 24, 0, 0),
+            cp56(24, 7, 1, 12, 60, 0),
+            cp56(24, 7, 1, 12, 0, 60_000),
+            cp56(100, 7, 1, 12, 0, 0),
+        ] {
+            assert!(t.to_timestamp(&TimePolicy::Utc).is_err(), "{:?}", t);
+        }
+        assert!(CP56Time2a::from_timestamp(utc(2100, 1, 1, 0, 0, 0), &TimePolicy::Utc).is_err());
+        let transition = Transition {
+            month: 3,
+            week: 0,
+            weekday: 0,
+            time: 7200,
+        };
+        let rules = TimeZoneRules::new("CET", FixedOffset::east_opt(3600).unwrap());
+        for start in [
+            transition,
+            Transition {
+                week: 5,
+                weekday: 7,
+                ..transition
+            },
+            Transition {
+                week: 5,
+                month: 13,
+                ..transition
+            },
+        ] {
+            let end = Transition {
+                month: 10,
+                week: 5,
+                ..transition
+            };
+            assert!(
+                rules
+                    .clone()
+                    .with_summer_time(FixedOffset::east_opt(7200).unwrap(), start, end)
+                    .is_err()
+            );
+        }
+        for tz in [
+            "",
+            "CET",
+            "CET-1CEST",
+            "CET-1CEST,M3.5.0",
+            "CET-1CEST,M13.5.0,M10.5.0",
+        ] {
+            assert!(TimeZoneRules::parse(tz).is_err(), "{}", tz);
+        }
+    }
+
+    #[test]
+    fn test_cp24time2a_resolve() {
+        let time = CP24Time2a {
+            ms: 59_900,
+            min: 59,
+            iv: false,
+        };
+        // the device clock is behind, the previous hour
+        let reference = utc(2024, 12, 31, 23, 0, 0);
+        assert_eq!(
+            time.resolve(reference, &TimePolicy::Utc).unwrap(),
+            utc(2024, 12, 31, 22, 59, 59) + Duration::from_millis(900)
+        );
+        // the device clock is ahead, the next hour
+        let time = CP24Time2a {
+            ms: 500,
+            min: 0,
+            iv: false,
+        };
+        let reference = utc(2024, 12, 31, 23, 59, 59);
+        assert_eq!(
+            time.resolve(reference, &TimePolicy::Utc).unwrap(),
+            utc(2025, 1, 1, 0, 0, 0) + Duration::from_millis(500)
+        );
+        // minutes are local, half-hour offset
+        let policy = TimePolicy::Fixed(FixedOffset::east_opt(5 * 3600 + 1800).unwrap());
+        let time = CP24Time2a {
+            ms: 0,
+            min: 35,
+            iv: false,
+        };
+        assert_eq!(
+            time.resolve(utc(2024, 7, 1, 12, 0, 0), &policy).unwrap(),
+            utc(2024, 7, 1, 12, 5, 0)
+        );
+        // CP56Time2a reference
+        let reference = cp56(24, 7, 1, 17, 40, 0);
+        assert_eq!(
+            time.resolve(&reference, &policy).unwrap(),
+            utc(2024, 7, 1, 12, 5, 0)
+        );
+        assert!(
+            CP24Time2a {
+                ms: 60_000,
+                min: 0,
+                iv: false
+            }
+            .resolve(reference, &policy)
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_timestamp_from_cp24time2a() {
+        // a CP24Time2a of the past hour is resolved to the past hour, not to the current one
+        let reference = Timestamp::now() - Duration::from_secs(120);
+        let time = CP24Time2a::try_from(reference).unwrap();
+        let resolved = Timestamp::try_from(time).unwrap();
+        let diff =
+            resolved.try_into_datetime_utc().unwrap() - reference.try_into_datetime_utc().unwrap();
+        assert!(diff.abs() < chrono::TimeDelta::seconds(10));
+    }
+
+    #[test]
+    fn test_cp16time2a_resolve() {
+        let start = utc(2024, 7, 1, 12, 0, 0);
+        assert_eq!(
+            CP16Time2a { ms: 1_500 }.resolve(start),
+            start + Duration::from_millis(1_500)
+        );
+    }
 }